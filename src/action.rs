@@ -1,5 +1,15 @@
 use crate::error::Error as RetryError;
-use std::future::Future;
+use core::fmt;
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+use pin_project::pin_project;
+
+#[cfg(feature = "std")]
+use crate::runtime::{self, Instant};
 
 /// An action can be run multiple times and produces a future.
 pub trait Action {
@@ -11,6 +21,14 @@ pub trait Action {
     type Error;
 
     fn run(&mut self) -> Self::Future;
+
+    /// Called by the retry futures with the context of the upcoming attempt.
+    ///
+    /// Forwards to [`Action::run`] by default.
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        let _ = context;
+        self.run()
+    }
 }
 
 impl<R, E, T: Future<Output = Result<R, RetryError<E>>>, F: FnMut() -> T> Action for F {
@@ -22,3 +40,396 @@ impl<R, E, T: Future<Output = Result<R, RetryError<E>>>, F: FnMut() -> T> Action
         self()
     }
 }
+
+/// An action taking an input value, given a clone of it on every attempt, see
+/// [`Retry::spawn_with`](crate::Retry::spawn_with).
+///
+/// Implemented for closures `FnMut(Input) -> impl Future<Output = Result<T, RetryError<E>>>`.
+pub trait ActionWith<Input> {
+    /// The future that this action produces.
+    type Future: Future<Output = Result<Self::Item, RetryError<Self::Error>>>;
+    /// The item that the future may resolve with.
+    type Item;
+    /// The error that the future may resolve with.
+    type Error;
+
+    fn run(&mut self, input: Input) -> Self::Future;
+}
+
+impl<I, R, E, T: Future<Output = Result<R, RetryError<E>>>, F: FnMut(I) -> T> ActionWith<I> for F {
+    type Item = R;
+    type Error = E;
+    type Future = T;
+
+    fn run(&mut self, input: I) -> Self::Future {
+        self(input)
+    }
+}
+
+/// An action whose attempt futures borrow from the action itself, like a buffer or a client it
+/// owns, see [`Retry::spawn_borrowing`](crate::Retry::spawn_borrowing).
+///
+/// Unlike an [`Action`], whose futures outlive the call to [`Action::run`] and so need
+/// clones or `Arc`s of the state they use, the future of an attempt borrows the action until
+/// it completes. Implemented for every [`Action`].
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{BorrowingAction, ClassifyFuture, Retry};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # struct Client;
+/// # type SendFuture<'a> = std::future::Ready<Result<(), std::io::Error>>;
+/// # impl Client { fn send<'a>(&'a mut self, buf: &'a [u8]) -> SendFuture<'a> { std::future::ready(Ok(())) } }
+/// struct Upload {
+///     client: Client,
+///     payload: Vec<u8>,
+/// }
+///
+/// impl BorrowingAction for Upload {
+///     type Future<'a> = ClassifyFuture<SendFuture<'a>>;
+///     type Item = ();
+///     type Error = std::io::Error;
+///
+///     fn run_borrowed(&mut self) -> Self::Future<'_> {
+///         ClassifyFuture::new(self.client.send(&self.payload))
+///     }
+/// }
+///
+/// # async fn run(mut upload: Upload) -> Result<(), std::io::Error> {
+/// Retry::spawn_borrowing(FixedInterval::from_millis(10).take(3), &mut upload).await
+/// # }
+/// ```
+pub trait BorrowingAction {
+    /// The future that this action produces, borrowing the action for `'a`.
+    type Future<'a>: Future<Output = Result<Self::Item, RetryError<Self::Error>>>
+    where
+        Self: 'a;
+    /// The item that the future may resolve with.
+    type Item;
+    /// The error that the future may resolve with.
+    type Error;
+
+    /// Runs an attempt, borrowing the action until its future completes.
+    fn run_borrowed(&mut self) -> Self::Future<'_>;
+}
+
+impl<A: Action> BorrowingAction for A {
+    type Future<'a>
+        = A::Future
+    where
+        A: 'a;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run_borrowed(&mut self) -> Self::Future<'_> {
+        self.run()
+    }
+}
+
+/// Action adapter owning the input of an [`ActionWith`], cloned into every attempt, created
+/// by [`Retry::spawn_with`](crate::Retry::spawn_with).
+///
+/// Borrow a value by passing a reference or an `Arc` as the input, so attempts share it
+/// instead of cloning it.
+#[derive(Debug, Clone)]
+pub struct WithInput<I, A> {
+    input: I,
+    action: A,
+}
+
+impl<I, A> WithInput<I, A> {
+    /// Wraps `action`, given a clone of `input` on every attempt.
+    pub const fn new(input: I, action: A) -> Self {
+        WithInput { input, action }
+    }
+
+    /// Returns the input.
+    pub fn into_input(self) -> I {
+        self.input
+    }
+}
+
+impl<I: Clone, A: ActionWith<I>> Action for WithInput<I, A> {
+    type Future = A::Future;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        self.action.run(self.input.clone())
+    }
+}
+
+/// Context of the attempt an action is about to run.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AttemptContext<'a, E> {
+    /// 1-based number of the upcoming attempt.
+    pub attempt: usize,
+    /// Error of the previous attempt, `None` for the first one.
+    pub previous_error: Option<&'a E>,
+    /// Time since the retry future was created.
+    pub elapsed: Duration,
+}
+
+impl<'a, E> AttemptContext<'a, E> {
+    /// Creates a context, mostly useful to test actions.
+    pub const fn new(attempt: usize, previous_error: Option<&'a E>, elapsed: Duration) -> Self {
+        AttemptContext {
+            attempt,
+            previous_error,
+            elapsed,
+        }
+    }
+}
+
+impl<E> Clone for AttemptContext<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for AttemptContext<'_, E> {}
+
+/// Action adapter for closures that want the [`AttemptContext`],
+/// as in `FnMut(&AttemptContext<E>) -> impl Future<Output = Result<T, RetryError<E>>>`.
+///
+/// The returned future can't borrow the context, so copy out whatever it needs first:
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{ActionContext, AttemptContext, Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn query(replica: &str) -> Result<u64, std::io::Error> { Ok(0) }
+/// # async fn run() -> Result<u64, std::io::Error> {
+/// const REPLICAS: [&str; 2] = ["primary", "secondary"];
+///
+/// // switch replica on every attempt
+/// let action = ActionContext::new(|context: &AttemptContext<std::io::Error>| {
+///     let replica = REPLICAS[(context.attempt - 1) % REPLICAS.len()];
+///     async move { query(replica).await.map_err(RetryError::transient) }
+/// });
+///
+/// Retry::spawn(FixedInterval::from_millis(10).take(3), action).await
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub struct ActionContext<F, Fut> {
+    f: F,
+    attempts: usize,
+    started: Option<Instant>,
+    _future: PhantomData<fn() -> Fut>,
+}
+
+#[cfg(feature = "std")]
+impl<F, Fut> ActionContext<F, Fut> {
+    /// Wraps a closure receiving the context of each attempt.
+    pub const fn new(f: F) -> Self {
+        ActionContext {
+            f,
+            attempts: 0,
+            started: None,
+            _future: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F, Fut> fmt::Debug for ActionContext<F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ActionContext")
+            .field("attempts", &self.attempts)
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F, Fut, R, E> Action for ActionContext<F, Fut>
+where
+    F: FnMut(&AttemptContext<'_, E>) -> Fut,
+    Fut: Future<Output = Result<R, RetryError<E>>>,
+{
+    type Future = Fut;
+    type Item = R;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        // track the attempts ourselves when not driven by a retry future
+        self.attempts += 1;
+        let started = *self.started.get_or_insert_with(runtime::now);
+        let context = AttemptContext::new(self.attempts, None, runtime::elapsed(started));
+        (self.f)(&context)
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, E>) -> Self::Future {
+        self.attempts = context.attempt;
+        (self.f)(context)
+    }
+}
+
+/// Action adapter for closures returning a plain `Result<T, E>`, retrying every error as
+/// transient, created by [`Retry::spawn_any`](crate::Retry::spawn_any).
+///
+/// Use [`Classify`](crate::Classify) instead to tell transient and permanent errors apart.
+#[derive(Debug, Clone)]
+pub struct AnyError<F> {
+    action: F,
+}
+
+impl<F> AnyError<F> {
+    /// Wraps `action`, a closure returning a future of `Result<T, E>`.
+    pub const fn new(action: F) -> Self {
+        AnyError { action }
+    }
+}
+
+impl<F, Fut, T, E> Action for AnyError<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    type Future = AnyErrorFuture<Fut>;
+    type Item = T;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        AnyErrorFuture {
+            future: (self.action)(),
+        }
+    }
+}
+
+/// Future of an [`AnyError`] action.
+#[pin_project]
+#[derive(Debug)]
+pub struct AnyErrorFuture<F> {
+    #[pin]
+    future: F,
+}
+
+impl<F, T, E> Future for AnyErrorFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Output = Result<T, RetryError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project()
+            .future
+            .poll(cx)
+            .map(|result| result.map_err(RetryError::transient))
+    }
+}
+
+/// Action adapter resuming every attempt from the checkpoint carried by the error of a
+/// previous one, so e.g. a retried multi-part upload doesn't restart from zero.
+///
+/// `action` receives the latest checkpoint, `None` on the first attempt, and `checkpoint`
+/// extracts it from the error of a failed attempt. Errors without a checkpoint keep the one
+/// of an earlier attempt.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Resumable, Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// struct UploadError {
+///     uploaded_parts: usize,
+/// }
+///
+/// # async fn upload_from(part: usize) -> Result<(), UploadError> { Ok(()) }
+/// # async fn run() -> Result<(), UploadError> {
+/// let action = Resumable::new(
+///     |resume_from: Option<usize>| async move {
+///         upload_from(resume_from.unwrap_or(0)).await.map_err(RetryError::transient)
+///     },
+///     |err: &UploadError| Some(err.uploaded_parts),
+/// );
+///
+/// Retry::spawn(FixedInterval::from_millis(100).take(5), action).await
+/// # }
+/// ```
+pub struct Resumable<F, G, C> {
+    action: F,
+    checkpoint: G,
+    resume_from: Option<C>,
+}
+
+impl<F, G, C> Resumable<F, G, C> {
+    /// Wraps `action`, resumed from the checkpoints `checkpoint` extracts from its errors.
+    pub const fn new(action: F, checkpoint: G) -> Self {
+        Resumable {
+            action,
+            checkpoint,
+            resume_from: None,
+        }
+    }
+}
+
+impl<F, G, C: fmt::Debug> fmt::Debug for Resumable<F, G, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Resumable")
+            .field("resume_from", &self.resume_from)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F, G, C, Fut, T, E> Action for Resumable<F, G, C>
+where
+    F: FnMut(Option<C>) -> Fut,
+    G: FnMut(&E) -> Option<C>,
+    C: Clone,
+    Fut: Future<Output = Result<T, RetryError<E>>>,
+{
+    type Future = Fut;
+    type Item = T;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        (self.action)(self.resume_from.clone())
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, E>) -> Self::Future {
+        if let Some(checkpoint) = context.previous_error.and_then(&mut self.checkpoint) {
+            self.resume_from = Some(checkpoint);
+        }
+        self.run()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::ready;
+
+    #[test]
+    fn action_context_tracks_attempts_without_retry_future() {
+        let mut action = ActionContext::new(|context: &AttemptContext<()>| {
+            ready(Ok::<usize, RetryError<()>>(context.attempt))
+        });
+
+        assert_eq!(action.run().into_inner().ok(), Some(1));
+        assert_eq!(action.run().into_inner().ok(), Some(2));
+    }
+
+    #[test]
+    fn closures_ignore_context() {
+        let mut action = || ready(Ok::<u8, RetryError<()>>(1));
+        let context = AttemptContext::new(3, Some(&()), Duration::ZERO);
+
+        assert_eq!(action.run_with(&context).into_inner().ok(), Some(1));
+    }
+
+    #[test]
+    fn resumable_keeps_the_latest_checkpoint() {
+        let mut action = Resumable::new(
+            |resume_from: Option<u32>| ready(Ok::<_, RetryError<Option<u32>>>(resume_from)),
+            |err: &Option<u32>| *err,
+        );
+        let mut run = |previous: Option<&Option<u32>>| {
+            let context = AttemptContext::new(2, previous, Duration::ZERO);
+            action.run_with(&context).into_inner().ok().flatten()
+        };
+
+        assert_eq!(run(None), None);
+        assert_eq!(run(Some(&Some(3))), Some(3));
+        assert_eq!(run(Some(&None)), Some(3));
+        assert_eq!(run(Some(&Some(5))), Some(5));
+    }
+}
@@ -0,0 +1,128 @@
+//! Synchronous retries for code without an async runtime, enabled by the `blocking` feature.
+//!
+//! They share the strategies, [`Condition`]s and [`RetryError`] classification of the retry
+//! futures, but sleep the current thread with [`std::thread::sleep`] between attempts, so they
+//! need no runtime feature: `default-features = false, features = ["blocking"]` is enough.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::condition::{Condition, RetryContext};
+use crate::error::Error as RetryError;
+
+/// Calls `f` until it succeeds, fails with a permanent error, `condition` rejects its
+/// transient error or `strategy` runs out, blocking the current thread between attempts.
+///
+/// A `retry_after` of a transient error replaces the strategy delay, like in the retry futures.
+/// Async conditions, like [`ConditionAsync`](crate::ConditionAsync), can't be awaited here and
+/// are checked synchronously through [`Condition::should_retry_with`].
+///
+/// ```rust
+/// use std::time::Duration;
+/// use tokio_retry2::RetryError;
+/// use tokio_retry2::blocking;
+/// use tokio_retry2::strategy::ExponentialBackoff;
+///
+/// let mut attempts = 0;
+/// let result = blocking::retry(
+///     ExponentialBackoff::from_millis(1).take(3),
+///     |err: &std::io::Error| err.kind() != std::io::ErrorKind::NotFound,
+///     || {
+///         attempts += 1;
+///         if attempts < 3 {
+///             return Err(RetryError::transient(std::io::ErrorKind::TimedOut.into()));
+///         }
+///         Ok(attempts)
+///     },
+/// );
+///
+/// assert_eq!(result.unwrap(), 3);
+/// ```
+pub fn retry<S, C, F, T, E>(strategy: S, mut condition: C, mut f: F) -> Result<T, E>
+where
+    S: IntoIterator<Item = Duration>,
+    C: Condition<E>,
+    F: FnMut() -> Result<T, RetryError<E>>,
+{
+    let mut strategy = strategy.into_iter();
+    let started = Instant::now();
+    let mut attempts = 1;
+    loop {
+        let (err, retry_after) = match f() {
+            Ok(value) => return Ok(value),
+            Err(RetryError::Permanent(err)) => return Err(err),
+            Err(RetryError::Transient { err, retry_after }) => (err, retry_after),
+        };
+        let context = RetryContext::new(attempts, started.elapsed());
+        if !condition.should_retry_with(&err, &context) {
+            return Err(err);
+        }
+        let Some(delay) = strategy.next() else {
+            return Err(err);
+        };
+        thread::sleep(retry_after.unwrap_or(delay));
+        attempts += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::FixedInterval;
+
+    #[test]
+    fn retries_transient_errors_until_the_strategy_runs_out() {
+        let mut attempts = 0;
+        let result: Result<(), usize> = retry(
+            FixedInterval::from_millis(1).take(2),
+            |_: &usize| true,
+            || {
+                attempts += 1;
+                Err(RetryError::transient(attempts))
+            },
+        );
+
+        assert_eq!(result, Err(3));
+    }
+
+    #[test]
+    fn stops_on_permanent_and_rejected_errors() {
+        let permanent: Result<(), &str> = retry(
+            FixedInterval::from_millis(1),
+            |_: &&str| true,
+            || Err(RetryError::permanent("denied")),
+        );
+        let mut attempts = 0;
+        let rejected: Result<(), usize> = retry(
+            FixedInterval::from_millis(1),
+            |err: &usize| *err < 2,
+            || {
+                attempts += 1;
+                Err(RetryError::transient(attempts))
+            },
+        );
+
+        assert_eq!(permanent, Err("denied"));
+        assert_eq!(rejected, Err(2));
+    }
+
+    #[test]
+    fn retry_after_replaces_the_strategy_delay() {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let result = retry(
+            FixedInterval::from_millis(60_000),
+            |_: &()| true,
+            || {
+                attempts += 1;
+                match attempts {
+                    1 => Err(RetryError::retry_after((), Duration::from_millis(1))),
+                    _ => Ok(attempts),
+                }
+            },
+        );
+
+        assert_eq!(result, Ok(2));
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+}
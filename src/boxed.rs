@@ -0,0 +1,251 @@
+use std::fmt;
+use std::future::Future;
+use std::iter::{IntoIterator, Iterator};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::condition::{Always, Condition, RetryContext};
+use crate::error::Error as RetryError;
+use crate::future::{RetryIf, RetryPhase};
+use crate::handle::RetryHandle;
+use crate::notify::{DelayContext, NoNotify, Notify};
+use crate::stats::RetryStats;
+
+type BoxedStrategy = Box<dyn Iterator<Item = Duration> + Send>;
+
+type BoxedFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, RetryError<E>>> + Send>>;
+
+/// Type-erased strategy, condition and notify hook of a [`BoxedRetry`].
+///
+/// Every [`BoxedRetry`] failing with `E` shares one concrete future type, whatever the
+/// strategy, condition, notify hook and action, so call sites don't each instantiate a
+/// [`RetryIf`]. The price is an allocation per attempt and dynamic dispatch.
+///
+/// ```rust,no_run
+/// use tokio_retry2::{BoxedRetry, BoxedRetryPolicy, Retry, RetryError};
+/// use tokio_retry2::strategy::ExponentialBackoff;
+///
+/// async fn action() -> Result<u64, RetryError<std::io::Error>> {
+///     // do some real-world stuff here...
+/// #   Ok(0)
+/// }
+///
+/// fn policy() -> BoxedRetryPolicy<std::io::Error> {
+///     BoxedRetryPolicy::new(ExponentialBackoff::from_millis(10).take(3))
+///         .when(|err: &std::io::Error| err.kind() != std::io::ErrorKind::NotFound)
+/// }
+///
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let retry: BoxedRetry<u64, std::io::Error> = Retry::boxed(policy(), action);
+/// let result = retry.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BoxedRetryPolicy<E> {
+    strategy: BoxedStrategy,
+    condition: Box<dyn Condition<E> + Send>,
+    notify: Box<dyn Notify<E> + Send>,
+    max_elapsed: Option<Duration>,
+}
+
+impl<E> BoxedRetryPolicy<E> {
+    /// Creates a policy retrying every transient error silently according to `strategy`.
+    pub fn new<T>(strategy: T) -> Self
+    where
+        T: IntoIterator<Item = Duration>,
+        T::IntoIter: Send + 'static,
+    {
+        BoxedRetryPolicy {
+            strategy: Box::new(strategy.into_iter()),
+            condition: Box::new(Always),
+            notify: Box::new(NoNotify),
+            max_elapsed: None,
+        }
+    }
+
+    /// Only retries the transient errors satisfying `condition`.
+    pub fn when<C>(mut self, condition: C) -> Self
+    where
+        C: Condition<E> + Send + 'static,
+    {
+        self.condition = Box::new(condition);
+        self
+    }
+
+    /// Notifies `notify` before every retry.
+    pub fn notify<N>(mut self, notify: N) -> Self
+    where
+        N: Notify<E> + Send + 'static,
+    {
+        self.notify = Box::new(notify);
+        self
+    }
+
+    /// Stops scheduling retries after `max_elapsed`, see [`RetryIf::with_max_elapsed`].
+    pub const fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Starts retrying `action` with this policy.
+    pub fn retry<A>(self, action: A) -> BoxedRetry<A::Item, E>
+    where
+        A: Action<Error = E> + Send + 'static,
+        A::Future: Send + 'static,
+    {
+        let action = ErasedAction(Box::new(BoxingAction(action)));
+        let mut retry_if = RetryIf::spawn(
+            self.strategy,
+            action,
+            ErasedCondition(self.condition),
+            ErasedNotify(self.notify),
+        );
+        if let Some(max_elapsed) = self.max_elapsed {
+            retry_if = retry_if.with_max_elapsed(max_elapsed);
+        }
+        BoxedRetry { retry_if }
+    }
+}
+
+impl<E> fmt::Debug for BoxedRetryPolicy<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BoxedRetryPolicy")
+            .field("max_elapsed", &self.max_elapsed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Retry future with a type-erased policy and action, created by [`Retry::boxed`] or
+/// [`BoxedRetryPolicy::retry`].
+///
+/// [`Retry::boxed`]: crate::Retry::boxed
+#[pin_project]
+pub struct BoxedRetry<T, E> {
+    #[pin]
+    retry_if: RetryIf<BoxedStrategy, ErasedAction<T, E>, ErasedCondition<E>, ErasedNotify<E>>,
+}
+
+impl<T, E> BoxedRetry<T, E> {
+    /// Returns the live statistics of this retry, see [`RetryIf::stats`].
+    pub fn stats(&self) -> Arc<RetryStats> {
+        self.retry_if.stats()
+    }
+
+    /// Returns a handle controlling this retry, see [`RetryIf::handle`].
+    pub fn handle(&self) -> RetryHandle {
+        self.retry_if.handle()
+    }
+
+    /// Number of attempts started so far, see [`RetryIf::attempts`].
+    pub const fn attempts(&self) -> usize {
+        self.retry_if.attempts()
+    }
+
+    /// What the retry is doing, see [`RetryIf::phase`].
+    pub const fn phase(&self) -> RetryPhase {
+        self.retry_if.phase()
+    }
+}
+
+impl<T, E> fmt::Debug for BoxedRetry<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BoxedRetry")
+            .field("retry_if", &self.retry_if)
+            .finish()
+    }
+}
+
+impl<T, E> Future for BoxedRetry<T, E> {
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project().retry_if.poll(cx)
+    }
+}
+
+/// Boxes the futures of an action, so it fits behind [`ErasedAction`].
+struct BoxingAction<A>(A);
+
+impl<A> Action for BoxingAction<A>
+where
+    A: Action,
+    A::Future: Send + 'static,
+{
+    type Future = BoxedFuture<A::Item, A::Error>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        Box::pin(self.0.run())
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        Box::pin(self.0.run_with(context))
+    }
+}
+
+struct ErasedAction<T, E>(Box<dyn Action<Future = BoxedFuture<T, E>, Item = T, Error = E> + Send>);
+
+impl<T, E> Action for ErasedAction<T, E> {
+    type Future = BoxedFuture<T, E>;
+    type Item = T;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        self.0.run()
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, E>) -> Self::Future {
+        self.0.run_with(context)
+    }
+}
+
+struct ErasedCondition<E>(Box<dyn Condition<E> + Send>);
+
+impl<E> Condition<E> for ErasedCondition<E> {
+    fn should_retry(&mut self, error: &E) -> bool {
+        self.0.should_retry(error)
+    }
+
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        self.0.should_retry_with(error, context)
+    }
+
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        self.0.poll_should_retry(error, context, cx)
+    }
+}
+
+struct ErasedNotify<E>(Box<dyn Notify<E> + Send>);
+
+impl<E> Notify<E> for ErasedNotify<E> {
+    fn notify(&mut self, err: &E, duration: Duration) {
+        self.0.notify(err, duration)
+    }
+
+    fn notify_attempt(&mut self, err: &E, attempt: usize, duration: Duration) {
+        self.0.notify_attempt(err, attempt, duration)
+    }
+
+    fn notify_with(&mut self, err: &E, context: &DelayContext) {
+        self.0.notify_with(err, context)
+    }
+
+    fn poll_notified(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0.poll_notified(cx)
+    }
+
+    fn exhausted(&mut self, err: &E, context: &RetryContext) {
+        self.0.exhausted(err, context)
+    }
+}
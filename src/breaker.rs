@@ -0,0 +1,328 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::condition::Condition;
+use crate::runtime::{deadline_after, Clock, Instant, Time};
+
+/// Circuit breaker shared by retry futures, see
+/// [`RetryIf::with_circuit_breaker`](crate::RetryIf::with_circuit_breaker).
+///
+/// - **Closed**: attempts run, and their outcomes are recorded over a window of recent
+///   attempts. The circuit opens once the window is full and its failure rate reaches the
+///   threshold.
+/// - **Open**: attempts are refused for `open_for`. Retry futures fail at once with
+///   [`RetryFailure::CircuitOpen`](crate::RetryFailure::CircuitOpen), or stop retrying.
+/// - **Half-open**: after `open_for`, a single probe attempt runs. Its success closes the
+///   circuit, its failure opens it again. A probe of a retry future dropped before its
+///   outcome is known, e.g. by a deadline or a cancellation, counts as a failure. Outcomes of
+///   retry futures' attempts admitted before the circuit opened are ignored until it closes.
+///
+/// Transient errors count as failures. Successes and permanent errors count as successes,
+/// as the upstream answered.
+///
+/// Cloning a breaker shares it. As a [`Condition`], it only checks that the circuit is not
+/// open, so it can be used as the condition of a future it is registered with
+/// without counting failures twice.
+///
+/// The breaker measures `open_for` with the system clock, or the tokio clock inside a tokio
+/// runtime with `rt-tokio`, whatever the clock of the retry futures; use [`CircuitBreaker::with_clock`] to replace it.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    inner: Arc<Mutex<Breaker>>,
+}
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Attempts run normally.
+    Closed,
+    /// Attempts are refused.
+    Open,
+    /// A probe attempt decides whether to close the circuit again.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    failure_rate: f32,
+    window: usize,
+    open_for: Duration,
+    outcomes: VecDeque<bool>,
+    state: BreakerState,
+    probes: u64,
+    time: Time,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed,
+    Open { until: Instant },
+    HalfOpen { probe: u64 },
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker opening for `open_for` once `failure_rate` of the last
+    /// `window` attempts failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure_rate` is not within `0.0..=1.0` or `window` is zero.
+    pub fn new(failure_rate: f32, window: usize, open_for: Duration) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&failure_rate),
+            "`failure_rate` must be within 0.0..=1.0"
+        );
+        assert!(window > 0, "`window` must not be zero");
+        CircuitBreaker {
+            inner: Arc::new(Mutex::new(Breaker {
+                failure_rate,
+                window,
+                open_for,
+                outcomes: VecDeque::with_capacity(window),
+                state: BreakerState::Closed,
+                probes: 0,
+                time: Time::default(),
+            })),
+        }
+    }
+
+    /// Measures `open_for` with `clock`, e.g. the
+    /// [`MockClock`](crate::test_util::MockClock) of the retry futures under test.
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        self.lock().time = Time::new(Arc::new(clock));
+        self
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        let breaker = self.lock();
+        match breaker.state {
+            BreakerState::Closed => CircuitState::Closed,
+            BreakerState::Open { until } if breaker.time.now() < until => CircuitState::Open,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Asks to run an attempt, returns `false` if the circuit refuses it.
+    pub fn try_acquire(&self) -> bool {
+        self.lock().admit().is_some()
+    }
+
+    /// Asks to run an attempt whose outcome is recorded through the returned permit, which
+    /// fails the probe if it is dropped before.
+    pub(crate) fn acquire(&self) -> Option<BreakerPermit> {
+        let probe = self.lock().admit()?;
+        Some(BreakerPermit {
+            breaker: self.clone(),
+            probe,
+        })
+    }
+
+    /// Records a successful attempt.
+    pub fn record_success(&self) {
+        self.lock().record(true);
+    }
+
+    /// Records a failed attempt.
+    pub fn record_failure(&self) {
+        self.lock().record(false);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Breaker> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Attempt admitted by a [`CircuitBreaker`], see [`CircuitBreaker::acquire`].
+#[derive(Debug)]
+pub(crate) struct BreakerPermit {
+    breaker: CircuitBreaker,
+    probe: Option<u64>,
+}
+
+impl BreakerPermit {
+    /// Records the outcome of the attempt.
+    pub(crate) fn record(mut self, success: bool) {
+        let probe = self.probe.take();
+        let mut breaker = self.breaker.lock();
+        match breaker.state {
+            BreakerState::Closed => breaker.record(success),
+            BreakerState::HalfOpen { probe: running } if probe == Some(running) => {
+                breaker.record(success);
+            }
+            // admitted before the circuit opened, only the probe decides from there
+            BreakerState::HalfOpen { .. } | BreakerState::Open { .. } => {}
+        }
+    }
+}
+
+impl Drop for BreakerPermit {
+    fn drop(&mut self) {
+        if let Some(probe) = self.probe {
+            let mut breaker = self.breaker.lock();
+            if let BreakerState::HalfOpen { probe: running } = breaker.state {
+                if running == probe {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("circuit breaker probe dropped, opening again");
+                    breaker.open();
+                }
+            }
+        }
+    }
+}
+
+impl Breaker {
+    /// Admits an attempt, with the id of the probe when half-open, or refuses it.
+    fn admit(&mut self) -> Option<Option<u64>> {
+        match self.state {
+            BreakerState::Closed => Some(None),
+            BreakerState::Open { until } if self.time.now() < until => None,
+            BreakerState::Open { .. } => {
+                self.probes += 1;
+                self.state = BreakerState::HalfOpen { probe: self.probes };
+                Some(Some(self.probes))
+            }
+            BreakerState::HalfOpen { .. } => None,
+        }
+    }
+
+    fn record(&mut self, success: bool) {
+        match self.state {
+            BreakerState::Closed => {
+                if self.outcomes.len() == self.window {
+                    self.outcomes.pop_front();
+                }
+                self.outcomes.push_back(success);
+                let failures = self.outcomes.iter().filter(|success| !**success).count();
+                let rate = failures as f32 / self.window as f32;
+                if self.outcomes.len() == self.window && failures > 0 && rate >= self.failure_rate {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("circuit breaker opened");
+                    self.open();
+                }
+            }
+            BreakerState::HalfOpen { .. } if success => {
+                self.outcomes.clear();
+                self.state = BreakerState::Closed;
+            }
+            BreakerState::HalfOpen { .. } => self.open(),
+            BreakerState::Open { .. } => {}
+        }
+    }
+
+    fn open(&mut self) {
+        self.outcomes.clear();
+        self.state = BreakerState::Open {
+            until: deadline_after(self.time.now(), self.open_for),
+        };
+    }
+}
+
+impl<E> Condition<E> for CircuitBreaker {
+    fn should_retry(&mut self, _error: &E) -> bool {
+        self.state() != CircuitState::Open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_once_failure_rate_is_reached() {
+        let breaker = CircuitBreaker::new(0.5, 4, Duration::from_secs(60));
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+    }
+
+    #[test]
+    fn half_open_allows_a_single_probe() {
+        let breaker = CircuitBreaker::new(1.0, 1, Duration::ZERO);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.try_acquire());
+    }
+
+    #[test]
+    fn failed_probe_opens_again() {
+        let breaker = CircuitBreaker::new(1.0, 1, Duration::ZERO);
+        breaker.record_failure();
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        assert!(breaker.try_acquire());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropped_probe_opens_again() {
+        let open_for = Duration::from_secs(60);
+        let breaker = CircuitBreaker::new(1.0, 1, open_for);
+        breaker.record_failure();
+        tokio::time::advance(open_for).await;
+
+        let probe = breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_none());
+        drop(probe);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        tokio::time::advance(open_for).await;
+        let probe = breaker.acquire().unwrap();
+        probe.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stale_permits_leave_later_probes_alone() {
+        let breaker = CircuitBreaker::new(1.0, 1, Duration::ZERO);
+        breaker.record_failure();
+        let stale = breaker.acquire().unwrap();
+        breaker.record_failure();
+        let probe = breaker.acquire().unwrap();
+
+        drop(stale);
+        assert!(breaker.acquire().is_none());
+        probe.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn attempts_admitted_while_closed_leave_the_probe_alone() {
+        let open_for = Duration::from_secs(60);
+        let breaker = CircuitBreaker::new(1.0, 1, open_for);
+        let succeeding = breaker.acquire().unwrap();
+        let failing = breaker.acquire().unwrap();
+        breaker.record_failure();
+        tokio::time::advance(open_for).await;
+        let probe = breaker.acquire().unwrap();
+
+        succeeding.record(true);
+        failing.record(false);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.acquire().is_none());
+        probe.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn measures_open_for_with_its_clock() {
+        let clock = crate::test_util::MockClock::new();
+        let open_for = Duration::from_secs(60);
+        let breaker = CircuitBreaker::new(1.0, 1, open_for).with_clock(clock.clone());
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        clock.advance(open_for);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Fixed-point scale of the tokens, so that fractional deposits add up.
+const SCALE: u64 = 1000;
+
+/// Retry budget shared by many retry futures, like a token bucket: every success deposits a
+/// fraction of a token, every retry withdraws a whole token. Once the budget is empty, the
+/// retry futures give up right away with their last error instead of retrying.
+///
+/// Cloning a budget shares it. See [`RetryIf::with_budget`](crate::RetryIf::with_budget).
+///
+/// ```rust
+/// use tokio_retry2::RetryBudget;
+///
+/// // allow a burst of 10 retries, then roughly one retry per 5 successes
+/// let budget = RetryBudget::new(10, 0.2);
+/// assert!(budget.withdraw());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    inner: Arc<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: AtomicU64,
+    capacity: u64,
+    deposit: u64,
+}
+
+impl RetryBudget {
+    /// Creates a full budget of `max_retries` tokens, refilled by `retry_ratio` tokens on
+    /// every success.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `retry_ratio` is negative or not finite.
+    pub fn new(max_retries: u32, retry_ratio: f32) -> Self {
+        assert!(
+            retry_ratio.is_finite() && retry_ratio >= 0.,
+            "`retry_ratio` must be a finite, positive number"
+        );
+        let capacity = u64::from(max_retries) * SCALE;
+        RetryBudget {
+            inner: Arc::new(Bucket {
+                tokens: AtomicU64::new(capacity),
+                capacity,
+                deposit: (f64::from(retry_ratio) * SCALE as f64) as u64,
+            }),
+        }
+    }
+
+    /// Records a success, refilling the budget.
+    pub fn deposit(&self) {
+        let bucket = &self.inner;
+        let _ = bucket
+            .tokens
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tokens| {
+                Some(tokens.saturating_add(bucket.deposit).min(bucket.capacity))
+            });
+    }
+
+    /// Takes a token for a retry, returns `false` if the budget is empty.
+    pub fn withdraw(&self) -> bool {
+        self.inner
+            .tokens
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tokens| {
+                tokens.checked_sub(SCALE)
+            })
+            .is_ok()
+    }
+
+    /// Number of whole retries left in the budget.
+    pub fn remaining(&self) -> u64 {
+        self.inner.tokens.load(Ordering::Relaxed) / SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn withdraws_until_empty() {
+        let budget = RetryBudget::new(2, 0.5);
+        assert!(budget.withdraw());
+        assert!(budget.clone().withdraw());
+        assert!(!budget.withdraw());
+        assert_eq!(budget.remaining(), 0);
+    }
+
+    #[test]
+    fn deposits_fractions_up_to_capacity() {
+        let budget = RetryBudget::new(1, 0.5);
+        assert!(budget.withdraw());
+
+        budget.deposit();
+        assert!(!budget.withdraw());
+        budget.deposit();
+        assert!(budget.withdraw());
+
+        for _ in 0..10 {
+            budget.deposit();
+        }
+        assert_eq!(budget.remaining(), 1);
+    }
+}
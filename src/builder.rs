@@ -0,0 +1,426 @@
+use std::future::{Future, IntoFuture};
+use std::iter::{IntoIterator, Iterator};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(feature = "cancellation")]
+use tokio_util::sync::CancellationToken;
+
+use crate::action::Action;
+use crate::budget::RetryBudget;
+use crate::condition::{Always, Condition};
+#[cfg(feature = "jitter")]
+use crate::decide::RetryAfterJitter;
+use crate::future::{Guarded, RetryIf, SleepPastDeadline};
+#[cfg(feature = "metrics")]
+use crate::metrics::RetryMetrics;
+use crate::notify::{NoNotify, Notify, OnExhausted};
+use crate::policy::RetryPolicy;
+use crate::rate_limit::RateLimiter;
+use crate::runtime::{Clock, Instant, Time};
+use crate::shutdown::{OnShutdown, Shutdown};
+
+/// Builder for retry futures, created by [`Retry::builder`](crate::Retry::builder).
+///
+/// A strategy is required, every other option is optional. The future built by
+/// [`RetryBuilder::build`] resolves with a [`RetryFailure`](crate::RetryFailure),
+/// so deadlines and cancellation don't change its type.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use tokio_retry2::{Retry, RetryError};
+/// use tokio_retry2::strategy::ExponentialBackoff;
+///
+/// async fn action() -> Result<u64, RetryError<std::io::Error>> {
+///     // do some real-world stuff here...
+/// #   Ok(0)
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), tokio_retry2::RetryFailure<std::io::Error>> {
+/// let result = Retry::builder()
+///     .strategy(ExponentialBackoff::from_millis(10).take(5))
+///     .condition(|err: &std::io::Error| err.kind() != std::io::ErrorKind::NotFound)
+///     .notify(|err: &std::io::Error, duration| println!("{err}, retrying after {duration:?}"))
+///     .max_elapsed(Duration::from_secs(10))
+///     .build(action)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// With [`RetryBuilder::action`], the builder is awaited directly:
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+/// # async fn run() -> Result<u64, tokio_retry2::RetryFailure<std::io::Error>> {
+/// Retry::builder()
+///     .strategy(FixedInterval::from_millis(10).take(3))
+///     .action(action)
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryBuilder<S, C = Always, N = NoNotify> {
+    strategy: S,
+    condition: C,
+    notify: N,
+    max_elapsed: Option<Duration>,
+    initial_delay: Option<Duration>,
+    retry_after_bounds: Option<(Duration, Duration)>,
+    #[cfg(feature = "jitter")]
+    retry_after_jitter: Option<RetryAfterJitter>,
+    budget: Option<RetryBudget>,
+    rate_limiter: Option<RateLimiter>,
+    deadline: Option<Instant>,
+    past_deadline: SleepPastDeadline,
+    shutdown: Option<Shutdown>,
+    on_shutdown: OnShutdown,
+    clock: Option<Arc<dyn Clock>>,
+    #[cfg(feature = "cancellation")]
+    cancellation: Option<CancellationToken>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<RetryMetrics>,
+}
+
+impl RetryBuilder<()> {
+    /// Creates a builder without strategy, retrying every transient error silently.
+    pub const fn new() -> Self {
+        RetryBuilder {
+            strategy: (),
+            condition: Always,
+            notify: NoNotify,
+            max_elapsed: None,
+            initial_delay: None,
+            retry_after_bounds: None,
+            #[cfg(feature = "jitter")]
+            retry_after_jitter: None,
+            budget: None,
+            rate_limiter: None,
+            deadline: None,
+            past_deadline: SleepPastDeadline::GiveUp,
+            shutdown: None,
+            on_shutdown: OnShutdown::FinishAttempt,
+            clock: None,
+            #[cfg(feature = "cancellation")]
+            cancellation: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+}
+
+impl Default for RetryBuilder<()> {
+    fn default() -> Self {
+        RetryBuilder::new()
+    }
+}
+
+impl<S, C, N> RetryBuilder<S, C, N> {
+    /// Sets the strategy producing the delays between attempts.
+    pub fn strategy<T>(self, strategy: T) -> RetryBuilder<T, C, N>
+    where
+        T: IntoIterator<Item = Duration>,
+    {
+        RetryBuilder {
+            strategy,
+            condition: self.condition,
+            notify: self.notify,
+            max_elapsed: self.max_elapsed,
+            initial_delay: self.initial_delay,
+            retry_after_bounds: self.retry_after_bounds,
+            #[cfg(feature = "jitter")]
+            retry_after_jitter: self.retry_after_jitter,
+            budget: self.budget,
+            rate_limiter: self.rate_limiter,
+            deadline: self.deadline,
+            past_deadline: self.past_deadline,
+            shutdown: self.shutdown,
+            on_shutdown: self.on_shutdown,
+            clock: self.clock,
+            #[cfg(feature = "cancellation")]
+            cancellation: self.cancellation,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+
+    /// Sets the condition deciding which transient errors are retried.
+    pub fn condition<T>(self, condition: T) -> RetryBuilder<S, T, N> {
+        RetryBuilder {
+            strategy: self.strategy,
+            condition,
+            notify: self.notify,
+            max_elapsed: self.max_elapsed,
+            initial_delay: self.initial_delay,
+            retry_after_bounds: self.retry_after_bounds,
+            #[cfg(feature = "jitter")]
+            retry_after_jitter: self.retry_after_jitter,
+            budget: self.budget,
+            rate_limiter: self.rate_limiter,
+            deadline: self.deadline,
+            past_deadline: self.past_deadline,
+            shutdown: self.shutdown,
+            on_shutdown: self.on_shutdown,
+            clock: self.clock,
+            #[cfg(feature = "cancellation")]
+            cancellation: self.cancellation,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+
+    /// Sets the hook notified before every retry.
+    pub fn notify<T>(self, notify: T) -> RetryBuilder<S, C, T> {
+        RetryBuilder {
+            strategy: self.strategy,
+            condition: self.condition,
+            notify,
+            max_elapsed: self.max_elapsed,
+            initial_delay: self.initial_delay,
+            retry_after_bounds: self.retry_after_bounds,
+            #[cfg(feature = "jitter")]
+            retry_after_jitter: self.retry_after_jitter,
+            budget: self.budget,
+            rate_limiter: self.rate_limiter,
+            deadline: self.deadline,
+            past_deadline: self.past_deadline,
+            shutdown: self.shutdown,
+            on_shutdown: self.on_shutdown,
+            clock: self.clock,
+            #[cfg(feature = "cancellation")]
+            cancellation: self.cancellation,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+
+    /// Sets a callback invoked once the retry future gives up, see [`OnExhausted`].
+    pub fn on_exhausted<F>(self, f: F) -> RetryBuilder<S, C, OnExhausted<N, F>> {
+        RetryBuilder {
+            strategy: self.strategy,
+            condition: self.condition,
+            notify: OnExhausted::new(self.notify, f),
+            max_elapsed: self.max_elapsed,
+            initial_delay: self.initial_delay,
+            retry_after_bounds: self.retry_after_bounds,
+            #[cfg(feature = "jitter")]
+            retry_after_jitter: self.retry_after_jitter,
+            budget: self.budget,
+            rate_limiter: self.rate_limiter,
+            deadline: self.deadline,
+            past_deadline: self.past_deadline,
+            shutdown: self.shutdown,
+            on_shutdown: self.on_shutdown,
+            clock: self.clock,
+            #[cfg(feature = "cancellation")]
+            cancellation: self.cancellation,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+
+    /// Stops scheduling retries after `max_elapsed`, see [`RetryIf::with_max_elapsed`].
+    pub const fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Sleeps `delay` before the first attempt, see [`RetryIf::spawn_after`].
+    pub const fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = Some(delay);
+        self
+    }
+
+    /// Bounds the `retry_after` delay of transient errors, see [`RetryIf::clamp_retry_after`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub const fn clamp_retry_after(mut self, min: Duration, max: Duration) -> Self {
+        assert!(
+            min.as_nanos() <= max.as_nanos(),
+            "`min` must not be greater than `max`"
+        );
+        self.retry_after_bounds = Some((min, max));
+        self
+    }
+
+    /// Randomizes the `retry_after` delay of transient errors, see
+    /// [`RetryIf::jitter_retry_after`].
+    #[cfg(feature = "jitter")]
+    pub fn jitter_retry_after<J>(mut self, jitter: J) -> Self
+    where
+        J: crate::strategy::Jitter + Send + Sync + 'static,
+    {
+        self.retry_after_jitter = Some(RetryAfterJitter(Arc::new(jitter)));
+        self
+    }
+
+    /// Draws every retry from a shared budget, see [`RetryIf::with_budget`].
+    pub fn budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Gates every attempt through a shared rate limiter, see [`RetryIf::with_rate_limit`].
+    pub fn rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Bounds the whole retry operation by a deadline, see [`RetryIf::with_deadline`].
+    pub const fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets what happens when a sleep would end past the deadline, see
+    /// [`Guarded::on_sleep_past_deadline`].
+    pub const fn on_sleep_past_deadline(mut self, past_deadline: SleepPastDeadline) -> Self {
+        self.past_deadline = past_deadline;
+        self
+    }
+
+    /// Stops retrying once `shutdown` resolves, see [`RetryIf::until`].
+    ///
+    /// Every retry future built from this builder, or from a policy made of it, shares the
+    /// signal.
+    pub fn until(mut self, shutdown: impl Future<Output = ()> + Send + 'static) -> Self {
+        self.shutdown = Some(Shutdown::new(shutdown));
+        self
+    }
+
+    /// Sets what happens to a running attempt once the shutdown signal resolves, see
+    /// [`Guarded::on_shutdown`].
+    pub const fn on_shutdown(mut self, on_shutdown: OnShutdown) -> Self {
+        self.on_shutdown = on_shutdown;
+        self
+    }
+
+    /// Measures time and sleeps with `clock`, see [`RetryIf::with_clock`].
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Stops the retry operation once `token` is cancelled, see [`RetryIf::with_cancellation`].
+    #[cfg(feature = "cancellation")]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Emits the metrics of the retry future, see [`RetryIf::with_metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: RetryMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Turns this builder into a reusable, cloneable [`RetryPolicy`].
+    pub fn into_policy(self) -> RetryPolicy<S, C, N> {
+        RetryPolicy::from(self)
+    }
+
+    /// Sets the action to retry, the returned builder is awaited like the future of
+    /// [`RetryBuilder::build`].
+    pub fn action<A>(self, action: A) -> ActionBuilder<S, C, N, A> {
+        ActionBuilder {
+            builder: self,
+            action,
+        }
+    }
+
+    /// Builds the retry future for `action`, starting its first attempt.
+    ///
+    /// Per-attempt timeouts depend on the error type of the action, so they are set on the
+    /// returned future with [`Guarded::with_attempt_timeout`]. Use [`RetryBuilder::action`]
+    /// to await the builder without calling `build`.
+    pub fn build<A>(self, action: A) -> Guarded<S::IntoIter, A, C, N>
+    where
+        S: IntoIterator<Item = Duration>,
+        A: Action,
+        C: Condition<A::Error>,
+        N: Notify<A::Error>,
+    {
+        let mut retry_if = match self.initial_delay {
+            Some(delay) => RetryIf::delayed(
+                delay,
+                self.strategy.into_iter(),
+                action,
+                self.condition,
+                self.notify,
+                self.clock.map(Time::new).unwrap_or_default(),
+            ),
+            None => {
+                let retry_if = RetryIf::spawn(self.strategy, action, self.condition, self.notify);
+                match self.clock {
+                    Some(clock) => retry_if.with_clock(clock),
+                    None => retry_if,
+                }
+            }
+        };
+        if let Some(max_elapsed) = self.max_elapsed {
+            retry_if = retry_if.with_max_elapsed(max_elapsed);
+        }
+        if let Some((min, max)) = self.retry_after_bounds {
+            retry_if = retry_if.clamp_retry_after(min, max);
+        }
+        #[cfg(feature = "jitter")]
+        {
+            retry_if = retry_if.with_retry_after_jitter(self.retry_after_jitter);
+        }
+        if let Some(budget) = self.budget {
+            retry_if = retry_if.with_budget(budget);
+        }
+        if let Some(rate_limiter) = self.rate_limiter {
+            retry_if = retry_if.with_rate_limit(rate_limiter);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics {
+            retry_if = retry_if.with_metrics(metrics);
+        }
+
+        let mut guarded = Guarded::new(retry_if)
+            .on_sleep_past_deadline(self.past_deadline)
+            .on_shutdown(self.on_shutdown);
+        if let Some(deadline) = self.deadline {
+            guarded = guarded.with_deadline(deadline);
+        }
+        if let Some(shutdown) = self.shutdown {
+            guarded = guarded.with_shutdown(shutdown);
+        }
+        #[cfg(feature = "cancellation")]
+        if let Some(token) = self.cancellation {
+            guarded = guarded.with_cancellation(token);
+        }
+        guarded
+    }
+}
+
+/// [`RetryBuilder`] holding the action to retry, created by [`RetryBuilder::action`].
+///
+/// Awaiting it builds and runs the retry future.
+#[derive(Debug, Clone)]
+pub struct ActionBuilder<S, C, N, A> {
+    builder: RetryBuilder<S, C, N>,
+    action: A,
+}
+
+impl<S, C, N, A> IntoFuture for ActionBuilder<S, C, N, A>
+where
+    S: IntoIterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = <Guarded<S::IntoIter, A, C, N> as Future>::Output;
+    type IntoFuture = Guarded<S::IntoIter, A, C, N>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.builder.build(self.action)
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::condition::Condition;
+
+type Predicate<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// Condition giving each class of errors its own retry limit, tracked across the attempts of
+/// a retry future.
+///
+/// Classes are checked in order and the first one matching the error counts the retry. Errors
+/// of a class are retried until it used its limit, errors no class matches are never retried
+/// unless changed with [`ClassifiedCondition::otherwise`]. The strategy still bounds the total
+/// number of retries.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{ClassifiedCondition, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// #[derive(Debug)]
+/// enum DbError {
+///     Timeout,
+///     VersionConflict,
+///     Invalid,
+/// }
+///
+/// # async fn action() -> Result<(), RetryError<DbError>> { Ok(()) }
+/// # async fn run() -> Result<(), DbError> {
+/// let condition = ClassifiedCondition::new()
+///     .class(|err: &DbError| matches!(err, DbError::Timeout), 5)
+///     .class(|err: &DbError| matches!(err, DbError::VersionConflict), 2);
+///
+/// let strategy = FixedInterval::from_millis(10);
+/// RetryIf::spawn(strategy, action, condition, |_: &DbError, _| {}).await
+/// # }
+/// ```
+pub struct ClassifiedCondition<E> {
+    classes: Vec<Class<E>>,
+    otherwise: Budget,
+}
+
+struct Class<E> {
+    predicate: Predicate<E>,
+    budget: Budget,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    limit: usize,
+    used: usize,
+}
+
+impl Budget {
+    const fn new(limit: usize) -> Self {
+        Budget { limit, used: 0 }
+    }
+
+    fn try_use(&mut self) -> bool {
+        let retry = self.used < self.limit;
+        if retry {
+            self.used += 1;
+        }
+        retry
+    }
+}
+
+impl<E> ClassifiedCondition<E> {
+    /// Creates a condition retrying no error.
+    pub fn new() -> Self {
+        ClassifiedCondition {
+            classes: Vec::new(),
+            otherwise: Budget::new(0),
+        }
+    }
+
+    /// Retries the errors matching `predicate` up to `limit` times.
+    pub fn class(
+        mut self,
+        predicate: impl Fn(&E) -> bool + Send + Sync + 'static,
+        limit: usize,
+    ) -> Self {
+        self.classes.push(Class {
+            predicate: Arc::new(predicate),
+            budget: Budget::new(limit),
+        });
+        self
+    }
+
+    /// Retries the errors matching no class up to `limit` times, never by default.
+    pub fn otherwise(mut self, limit: usize) -> Self {
+        self.otherwise = Budget::new(limit);
+        self
+    }
+
+    /// Returns how many retries each class used, in the order the classes were added.
+    pub fn used(&self) -> impl Iterator<Item = usize> + '_ {
+        self.classes.iter().map(|class| class.budget.used)
+    }
+}
+
+impl<E> Default for ClassifiedCondition<E> {
+    fn default() -> Self {
+        ClassifiedCondition::new()
+    }
+}
+
+impl<E> Clone for ClassifiedCondition<E> {
+    fn clone(&self) -> Self {
+        ClassifiedCondition {
+            classes: self
+                .classes
+                .iter()
+                .map(|class| Class {
+                    predicate: class.predicate.clone(),
+                    budget: class.budget,
+                })
+                .collect(),
+            otherwise: self.otherwise,
+        }
+    }
+}
+
+impl<E> fmt::Debug for ClassifiedCondition<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClassifiedCondition")
+            .field(
+                "classes",
+                &self
+                    .classes
+                    .iter()
+                    .map(|class| class.budget)
+                    .collect::<Vec<_>>(),
+            )
+            .field("otherwise", &self.otherwise)
+            .finish()
+    }
+}
+
+impl<E> Condition<E> for ClassifiedCondition<E> {
+    fn should_retry(&mut self, error: &E) -> bool {
+        match self
+            .classes
+            .iter_mut()
+            .find(|class| (class.predicate)(error))
+        {
+            Some(class) => class.budget.try_use(),
+            None => self.otherwise.try_use(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum DbError {
+        Timeout,
+        VersionConflict,
+        Invalid,
+    }
+
+    fn condition() -> ClassifiedCondition<DbError> {
+        ClassifiedCondition::new()
+            .class(|err: &DbError| *err == DbError::Timeout, 5)
+            .class(|err: &DbError| *err == DbError::VersionConflict, 2)
+    }
+
+    #[test]
+    fn each_class_has_its_own_limit() {
+        let mut condition = condition();
+
+        assert!(condition.should_retry(&DbError::VersionConflict));
+        assert!(condition.should_retry(&DbError::Timeout));
+        assert!(condition.should_retry(&DbError::VersionConflict));
+        assert!(!condition.should_retry(&DbError::VersionConflict));
+        for _ in 0..4 {
+            assert!(condition.should_retry(&DbError::Timeout));
+        }
+        assert!(!condition.should_retry(&DbError::Timeout));
+        assert_eq!(condition.used().collect::<Vec<_>>(), [5, 2]);
+    }
+
+    #[test]
+    fn unmatched_errors_follow_otherwise() {
+        assert!(!condition().should_retry(&DbError::Invalid));
+
+        let mut condition = condition().otherwise(1);
+        assert!(condition.should_retry(&DbError::Invalid));
+        assert!(!condition.should_retry(&DbError::Invalid));
+    }
+}
@@ -0,0 +1,316 @@
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::condition::Condition;
+
+type Rule = Arc<dyn Fn(&(dyn Error + 'static)) -> Option<bool> + Send + Sync>;
+
+/// Condition classifying type-erased errors, like `Box<dyn Error>` or `anyhow::Error`, by
+/// downcasting them to concrete error types.
+///
+/// Rules are checked in order against the error and every error of its
+/// [`source`](Error::source) chain, the first matching rule decides. Errors no rule matches
+/// are retried, unless changed with [`Classifier::otherwise`].
+///
+/// ```rust,no_run
+/// # use std::io;
+/// # use tokio_retry2::{Classifier, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # #[derive(Debug)]
+/// # struct AuthError;
+/// # impl std::fmt::Display for AuthError {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result { f.write_str("unauthorized") }
+/// # }
+/// # impl std::error::Error for AuthError {}
+/// # type BoxError = Box<dyn std::error::Error + Send + Sync>;
+/// # async fn action() -> Result<(), RetryError<BoxError>> { Ok(()) }
+/// # async fn run() -> Result<(), BoxError> {
+/// let classifier = Classifier::new()
+///     .retry_if::<io::Error>(|err| err.kind() == io::ErrorKind::TimedOut)
+///     .permanent_if::<AuthError>(|_| true)
+///     .otherwise(false);
+///
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// RetryIf::spawn(strategy, action, classifier, |_: &BoxError, _| {}).await
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Classifier {
+    rules: Vec<Rule>,
+    otherwise: bool,
+}
+
+impl Classifier {
+    /// Creates a classifier retrying every error.
+    pub fn new() -> Self {
+        Classifier {
+            rules: Vec::new(),
+            otherwise: true,
+        }
+    }
+
+    /// Retries errors of type `T` matching `predicate`.
+    pub fn retry_if<T>(self, predicate: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self
+    where
+        T: Error + 'static,
+    {
+        self.rule(predicate, true)
+    }
+
+    /// Stops retrying on errors of type `T` matching `predicate`.
+    pub fn permanent_if<T>(self, predicate: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self
+    where
+        T: Error + 'static,
+    {
+        self.rule(predicate, false)
+    }
+
+    /// Sets whether errors matching no rule are retried, `true` by default.
+    pub fn otherwise(mut self, retry: bool) -> Self {
+        self.otherwise = retry;
+        self
+    }
+
+    /// Returns whether `error` should be retried.
+    pub fn classify(&self, error: &(dyn Error + 'static)) -> bool {
+        let mut source = Some(error);
+        while let Some(error) = source {
+            if let Some(retry) = self.rules.iter().find_map(|rule| rule(error)) {
+                return retry;
+            }
+            source = error.source();
+        }
+        self.otherwise
+    }
+
+    fn rule<T>(
+        mut self,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+        retry: bool,
+    ) -> Self
+    where
+        T: Error + 'static,
+    {
+        self.rules.push(Arc::new(move |error| {
+            error
+                .downcast_ref::<T>()
+                .filter(|error| predicate(error))
+                .map(|_| retry)
+        }));
+        self
+    }
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Classifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Classifier")
+            .field("rules", &self.rules.len())
+            .field("otherwise", &self.otherwise)
+            .finish()
+    }
+}
+
+impl Condition<Box<dyn Error>> for Classifier {
+    fn should_retry(&mut self, error: &Box<dyn Error>) -> bool {
+        self.classify(error.as_ref())
+    }
+}
+
+impl Condition<Box<dyn Error + Send + Sync>> for Classifier {
+    fn should_retry(&mut self, error: &Box<dyn Error + Send + Sync>) -> bool {
+        self.classify(error.as_ref())
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl Condition<anyhow::Error> for Classifier {
+    fn should_retry(&mut self, error: &anyhow::Error) -> bool {
+        self.classify(error.as_ref())
+    }
+}
+
+/// Condition retrying only the errors that are, or are caused by, an error of type `T`.
+///
+/// The error and every error of its [`source`](Error::source) chain are downcast to `T`, so
+/// error enums wrapping `T` as a source match too. Boxed errors and `anyhow::Error` don't
+/// implement [`Error`] themselves: classify them with a [`Classifier`], e.g.
+/// `Classifier::new().retry_if::<T>(|_| true).otherwise(false)`, or with
+/// [`IsError::matches`].
+///
+/// ```rust,no_run
+/// # use std::{fmt, io};
+/// # use tokio_retry2::{retry_if_is, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// #[derive(Debug)]
+/// enum FetchError {
+///     Io(io::Error),
+///     Parse(String),
+/// }
+/// # impl fmt::Display for FetchError {
+/// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("fetch failed") }
+/// # }
+///
+/// impl std::error::Error for FetchError {
+///     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+///         match self {
+///             FetchError::Io(err) => Some(err),
+///             FetchError::Parse(_) => None,
+///         }
+///     }
+/// }
+///
+/// # async fn action() -> Result<(), RetryError<FetchError>> { Ok(()) }
+/// # async fn run() -> Result<(), FetchError> {
+/// // retries `FetchError::Io` only
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// RetryIf::spawn(strategy, action, retry_if_is::<io::Error>(), |_: &FetchError, _| {}).await
+/// # }
+/// ```
+pub const fn retry_if_is<T: Error + 'static>() -> IsError<T> {
+    IsError(PhantomData)
+}
+
+/// Condition created by [`retry_if_is`].
+pub struct IsError<T>(PhantomData<fn(&T)>);
+
+impl<T> IsError<T>
+where
+    T: Error + 'static,
+{
+    /// Returns whether `error` or one of its sources is a `T`.
+    pub fn matches(&self, error: &(dyn Error + 'static)) -> bool {
+        let mut source = Some(error);
+        while let Some(error) = source {
+            if error.is::<T>() {
+                return true;
+            }
+            source = error.source();
+        }
+        false
+    }
+}
+
+impl<T> Clone for IsError<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for IsError<T> {}
+
+impl<T> fmt::Debug for IsError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("IsError")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T, E> Condition<E> for IsError<T>
+where
+    T: Error + 'static,
+    E: Error + 'static,
+{
+    fn should_retry(&mut self, error: &E) -> bool {
+        self.matches(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct AuthError;
+
+    impl fmt::Display for AuthError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("unauthorized")
+        }
+    }
+
+    impl Error for AuthError {}
+
+    #[derive(Debug)]
+    struct Context(io::Error);
+
+    impl fmt::Display for Context {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("while fetching")
+        }
+    }
+
+    impl Error for Context {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    fn classifier() -> Classifier {
+        Classifier::new()
+            .retry_if::<io::Error>(|err| err.kind() == io::ErrorKind::TimedOut)
+            .permanent_if::<io::Error>(|_| true)
+            .permanent_if::<AuthError>(|_| true)
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let mut classifier = classifier();
+        let timed_out: Box<dyn Error + Send + Sync> =
+            io::Error::from(io::ErrorKind::TimedOut).into();
+        let not_found: Box<dyn Error + Send + Sync> =
+            io::Error::from(io::ErrorKind::NotFound).into();
+        let auth: Box<dyn Error + Send + Sync> = Box::new(AuthError);
+
+        assert!(classifier.should_retry(&timed_out));
+        assert!(!classifier.should_retry(&not_found));
+        assert!(!classifier.should_retry(&auth));
+    }
+
+    #[test]
+    fn walks_the_source_chain() {
+        let mut classifier = classifier();
+        let wrapped: Box<dyn Error> = Box::new(Context(io::ErrorKind::TimedOut.into()));
+
+        assert!(classifier.should_retry(&wrapped));
+    }
+
+    #[test]
+    fn unmatched_errors_follow_otherwise() {
+        let unknown: Box<dyn Error> = "unknown".into();
+
+        assert!(classifier().should_retry(&unknown));
+        assert!(!classifier().otherwise(false).should_retry(&unknown));
+    }
+
+    #[test]
+    fn retry_if_is_matches_the_error_and_its_sources() {
+        let mut condition = retry_if_is::<io::Error>();
+        let boxed: Box<dyn Error + Send + Sync> = Box::new(Context(io::ErrorKind::NotFound.into()));
+
+        assert!(condition.should_retry(&io::Error::from(io::ErrorKind::NotFound)));
+        assert!(condition.should_retry(&Context(io::ErrorKind::NotFound.into())));
+        assert!(!condition.should_retry(&AuthError));
+        assert!(condition.matches(boxed.as_ref()));
+        assert!(!retry_if_is::<AuthError>().should_retry(&Context(io::ErrorKind::NotFound.into())));
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn classifies_anyhow_errors() {
+        let error =
+            anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound)).context("fetching");
+
+        assert!(!classifier().should_retry(&error));
+    }
+}
@@ -1,6 +1,326 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll, Waker};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::runtime::{self, Instant};
+
 /// Specifies under which conditions a retry is attempted.
 pub trait Condition<E> {
     fn should_retry(&mut self, error: &E) -> bool;
+
+    /// Called by the retry futures with the progress of the retry so far.
+    ///
+    /// Forwards to [`Condition::should_retry`] by default.
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        let _ = context;
+        self.should_retry(error)
+    }
+
+    /// Polled by the retry futures with the error of the failed attempt until it resolves,
+    /// allowing the decision to await asynchronous work, see [`ConditionAsync`].
+    ///
+    /// Resolves immediately with [`Condition::should_retry_with`] by default.
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        let _ = cx;
+        Poll::Ready(self.should_retry_with(error, context))
+    }
+}
+
+/// Condition that retries every transient error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Always;
+
+impl<E> Condition<E> for Always {
+    fn should_retry(&mut self, _error: &E) -> bool {
+        true
+    }
+}
+
+/// Condition that retries no error, making every transient error final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Never;
+
+impl<E> Condition<E> for Never {
+    fn should_retry(&mut self, _error: &E) -> bool {
+        false
+    }
+}
+
+/// Returns the condition retrying every transient error, see [`Always`].
+pub const fn always() -> Always {
+    Always
+}
+
+/// Returns the condition retrying no error, see [`Never`].
+pub const fn never() -> Never {
+    Never
+}
+
+/// Kinds of the I/O errors retried by [`transient_io_errors`] and classified as transient by
+/// the [`TransientError`](crate::TransientError) implementation of [`io::Error`].
+#[cfg(feature = "std")]
+pub(crate) const TRANSIENT_IO_KINDS: [io::ErrorKind; 9] = [
+    io::ErrorKind::ConnectionRefused,
+    io::ErrorKind::ConnectionReset,
+    io::ErrorKind::ConnectionAborted,
+    io::ErrorKind::NotConnected,
+    io::ErrorKind::BrokenPipe,
+    io::ErrorKind::TimedOut,
+    io::ErrorKind::Interrupted,
+    io::ErrorKind::WouldBlock,
+    io::ErrorKind::UnexpectedEof,
+];
+
+/// Returns the condition retrying the I/O errors of the given kinds, see [`IoKinds`].
+///
+/// ```rust,no_run
+/// # use std::io::{self, ErrorKind};
+/// # use tokio_retry2::{io_kinds, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn read() -> Result<Vec<u8>, RetryError<io::Error>> { Ok(Vec::new()) }
+/// # async fn run() -> Result<Vec<u8>, io::Error> {
+/// let condition = io_kinds([
+///     ErrorKind::TimedOut,
+///     ErrorKind::ConnectionReset,
+///     ErrorKind::Interrupted,
+/// ]);
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// RetryIf::spawn(strategy, read, condition, |_: &io::Error, _| {}).await
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn io_kinds(kinds: impl IntoIterator<Item = io::ErrorKind>) -> IoKinds {
+    IoKinds {
+        kinds: kinds.into_iter().collect(),
+    }
+}
+
+/// Returns the condition retrying connection failures, timeouts, interruptions and truncated
+/// streams, the I/O errors usually worth retrying.
+#[cfg(feature = "std")]
+pub fn transient_io_errors() -> IoKinds {
+    io_kinds(TRANSIENT_IO_KINDS)
+}
+
+/// Condition retrying the [`io::Error`]s of a set of kinds, created by [`io_kinds`] or
+/// [`transient_io_errors`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoKinds {
+    kinds: Vec<io::ErrorKind>,
+}
+
+#[cfg(feature = "std")]
+impl IoKinds {
+    /// Adds `kind` to the retried kinds.
+    pub fn with(mut self, kind: io::ErrorKind) -> Self {
+        if !self.matches(kind) {
+            self.kinds.push(kind);
+        }
+        self
+    }
+
+    /// Removes `kind` from the retried kinds.
+    pub fn without(mut self, kind: io::ErrorKind) -> Self {
+        self.kinds.retain(|retried| *retried != kind);
+        self
+    }
+
+    /// Returns whether errors of `kind` are retried.
+    pub fn matches(&self, kind: io::ErrorKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Condition<io::Error> for IoKinds {
+    fn should_retry(&mut self, error: &io::Error) -> bool {
+        self.matches(error.kind())
+    }
+}
+
+/// Combinators composing conditions from smaller predicates, implemented for every type.
+///
+/// The right-hand side is only evaluated when it decides the outcome, like `&&` and `||`.
+///
+/// ```rust,no_run
+/// # use std::io::{Error, ErrorKind};
+/// # use tokio_retry2::{ConditionExt, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<Error>> { Ok(()) }
+/// # async fn run() -> Result<(), Error> {
+/// let timed_out = |err: &Error| err.kind() == ErrorKind::TimedOut;
+/// let refused = |err: &Error| err.kind() == ErrorKind::ConnectionRefused;
+/// let maintenance = |err: &Error| err.to_string().contains("maintenance");
+/// let condition = timed_out.or(refused).and(maintenance.not());
+///
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// RetryIf::spawn(strategy, action, condition, |_: &Error, _| {}).await
+/// # }
+/// ```
+pub trait ConditionExt: Sized {
+    /// Retries the errors satisfying both `self` and `other`.
+    fn and<D>(self, other: D) -> And<Self, D> {
+        And {
+            first: self,
+            second: other,
+            decided: None,
+        }
+    }
+
+    /// Retries the errors satisfying `self` or `other`.
+    fn or<D>(self, other: D) -> Or<Self, D> {
+        Or {
+            first: self,
+            second: other,
+            decided: None,
+        }
+    }
+
+    /// Retries the errors not satisfying `self`.
+    fn not(self) -> Not<Self> {
+        Not { condition: self }
+    }
+}
+
+impl<T> ConditionExt for T {}
+
+/// Condition retrying the errors satisfying both of its conditions, see [`ConditionExt::and`].
+#[derive(Debug, Clone)]
+pub struct And<C, D> {
+    first: C,
+    second: D,
+    // outcome of `first` while `second` is pending
+    decided: Option<bool>,
+}
+
+impl<E, C, D> Condition<E> for And<C, D>
+where
+    C: Condition<E>,
+    D: Condition<E>,
+{
+    fn should_retry(&mut self, error: &E) -> bool {
+        self.first.should_retry(error) && self.second.should_retry(error)
+    }
+
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        self.first.should_retry_with(error, context)
+            && self.second.should_retry_with(error, context)
+    }
+
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        let first = match self.decided {
+            Some(first) => first,
+            None => *self
+                .decided
+                .insert(ready!(self.first.poll_should_retry(error, context, cx))),
+        };
+        let retry = first && ready!(self.second.poll_should_retry(error, context, cx));
+        self.decided = None;
+        Poll::Ready(retry)
+    }
+}
+
+/// Condition retrying the errors satisfying either of its conditions, see [`ConditionExt::or`].
+#[derive(Debug, Clone)]
+pub struct Or<C, D> {
+    first: C,
+    second: D,
+    // outcome of `first` while `second` is pending
+    decided: Option<bool>,
+}
+
+impl<E, C, D> Condition<E> for Or<C, D>
+where
+    C: Condition<E>,
+    D: Condition<E>,
+{
+    fn should_retry(&mut self, error: &E) -> bool {
+        self.first.should_retry(error) || self.second.should_retry(error)
+    }
+
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        self.first.should_retry_with(error, context)
+            || self.second.should_retry_with(error, context)
+    }
+
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        let first = match self.decided {
+            Some(first) => first,
+            None => *self
+                .decided
+                .insert(ready!(self.first.poll_should_retry(error, context, cx))),
+        };
+        let retry = first || ready!(self.second.poll_should_retry(error, context, cx));
+        self.decided = None;
+        Poll::Ready(retry)
+    }
+}
+
+/// Condition retrying the errors its condition rejects, see [`ConditionExt::not`].
+#[derive(Debug, Clone)]
+pub struct Not<C> {
+    condition: C,
+}
+
+impl<E, C: Condition<E>> Condition<E> for Not<C> {
+    fn should_retry(&mut self, error: &E) -> bool {
+        !self.condition.should_retry(error)
+    }
+
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        !self.condition.should_retry_with(error, context)
+    }
+
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        self.condition
+            .poll_should_retry(error, context, cx)
+            .map(|retry| !retry)
+    }
+}
+
+/// Progress of a retry future at the time a condition is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct RetryContext {
+    /// Number of attempts made so far, including the one that just failed.
+    pub attempts: usize,
+    /// Time since the retry future was created.
+    pub elapsed: Duration,
+}
+
+impl RetryContext {
+    /// Creates a context, mostly useful to test conditions.
+    pub const fn new(attempts: usize, elapsed: Duration) -> Self {
+        RetryContext { attempts, elapsed }
+    }
 }
 
 impl<E, F: FnMut(&E) -> bool> Condition<E> for F {
@@ -8,3 +328,276 @@ impl<E, F: FnMut(&E) -> bool> Condition<E> for F {
         self(error)
     }
 }
+
+/// Condition adapter for async predicates, as in `FnMut(&E) -> impl Future<Output = bool>`.
+///
+/// The returned future can't borrow the error, so copy out whatever it needs first:
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use tokio::sync::Mutex;
+/// # use tokio_retry2::{ConditionAsync, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let healthy = Arc::new(Mutex::new(true));
+/// let condition = ConditionAsync::new(move |_err: &std::io::Error| {
+///     let healthy = healthy.clone();
+///     async move { *healthy.lock().await }
+/// });
+///
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// RetryIf::spawn(strategy, action, condition, |_: &std::io::Error, _| {}).await
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub struct ConditionAsync<F, Fut> {
+    f: F,
+    pending: Option<Pin<Box<Fut>>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<F, Fut> ConditionAsync<F, Fut> {
+    /// Wraps a predicate returning the future that decides whether to retry.
+    pub const fn new(f: F) -> Self {
+        ConditionAsync { f, pending: None }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F, Fut> fmt::Debug for ConditionAsync<F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConditionAsync")
+            .field("pending", &self.pending.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E, F, Fut> Condition<E> for ConditionAsync<F, Fut>
+where
+    F: FnMut(&E) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    /// Synchronous evaluation only observes predicates that resolve without waiting,
+    /// a pending predicate is treated as not retryable.
+    fn should_retry(&mut self, error: &E) -> bool {
+        let mut cx = Context::from_waker(Waker::noop());
+        let retry = self.poll_should_retry(error, &RetryContext::default(), &mut cx);
+        self.pending = None;
+        retry == Poll::Ready(true)
+    }
+
+    fn poll_should_retry(
+        &mut self,
+        error: &E,
+        _context: &RetryContext,
+        cx: &mut Context<'_>,
+    ) -> Poll<bool> {
+        let future = self
+            .pending
+            .get_or_insert_with(|| Box::pin((self.f)(error)));
+        let poll = future.as_mut().poll(cx);
+        if poll.is_ready() {
+            self.pending = None;
+        }
+        poll
+    }
+}
+
+/// Condition adapter for predicates that also want the [`RetryContext`],
+/// as in `FnMut(&E, &RetryContext) -> bool`.
+///
+/// ```rust,no_run
+/// # use std::io::ErrorKind;
+/// # use tokio_retry2::{ConditionContext, RetryContext, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// // retry timeouts only for the first 3 attempts, then only retry refused connections
+/// let condition = ConditionContext::new(|err: &std::io::Error, context: &RetryContext| {
+///     match err.kind() {
+///         ErrorKind::TimedOut => context.attempts <= 3,
+///         ErrorKind::ConnectionRefused => true,
+///         _ => false,
+///     }
+/// });
+///
+/// let strategy = FixedInterval::from_millis(10).take(10);
+/// RetryIf::spawn(strategy, action, condition, |_: &std::io::Error, _| {}).await
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct ConditionContext<F> {
+    f: F,
+    attempts: usize,
+    started: Option<Instant>,
+}
+
+#[cfg(feature = "std")]
+impl<F> ConditionContext<F> {
+    /// Wraps a predicate receiving the error and the progress of the retry.
+    pub const fn new(f: F) -> Self {
+        ConditionContext {
+            f,
+            attempts: 0,
+            started: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, F> Condition<E> for ConditionContext<F>
+where
+    F: FnMut(&E, &RetryContext) -> bool,
+{
+    fn should_retry(&mut self, error: &E) -> bool {
+        // track the progress ourselves when not driven by a retry future
+        self.attempts += 1;
+        let started = *self.started.get_or_insert_with(runtime::now);
+        let context = RetryContext::new(self.attempts, runtime::elapsed(started));
+        (self.f)(error, &context)
+    }
+
+    fn should_retry_with(&mut self, error: &E, context: &RetryContext) -> bool {
+        (self.f)(error, context)
+    }
+}
+
+/// Condition adapter for predicates with state persisting across attempts,
+/// as in `FnMut(&mut S, &E) -> bool`.
+///
+/// ```rust,no_run
+/// # use std::collections::HashMap;
+/// # use std::io::ErrorKind;
+/// # use tokio_retry2::{ConditionState, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// // stop after 3 occurrences of the same error kind
+/// let condition = ConditionState::new(
+///     HashMap::<ErrorKind, usize>::new(),
+///     |seen: &mut HashMap<ErrorKind, usize>, err: &std::io::Error| {
+///         let count = seen.entry(err.kind()).or_default();
+///         *count += 1;
+///         *count < 3
+///     },
+/// );
+///
+/// let strategy = FixedInterval::from_millis(10).take(10);
+/// RetryIf::spawn(strategy, action, condition, |_: &std::io::Error, _| {}).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConditionState<S, F> {
+    state: S,
+    f: F,
+}
+
+impl<S, F> ConditionState<S, F> {
+    /// Wraps a predicate receiving the state and the error.
+    pub const fn new(state: S, f: F) -> Self {
+        ConditionState { state, f }
+    }
+
+    /// Returns the current state.
+    pub const fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Unwraps the adapter, returning its state.
+    pub fn into_state(self) -> S {
+        self.state
+    }
+}
+
+impl<E, S, F> Condition<E> for ConditionState<S, F>
+where
+    F: FnMut(&mut S, &E) -> bool,
+{
+    fn should_retry(&mut self, error: &E) -> bool {
+        (self.f)(&mut self.state, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condition_context_tracks_attempts_without_retry_future() {
+        let mut condition =
+            ConditionContext::new(|_: &(), context: &RetryContext| context.attempts < 3);
+
+        assert!(condition.should_retry(&()));
+        assert!(condition.should_retry(&()));
+        assert!(!condition.should_retry(&()));
+    }
+
+    #[test]
+    fn condition_state_persists_across_attempts() {
+        let mut condition = ConditionState::new([0; 2], |seen: &mut [usize; 2], e: &usize| {
+            seen[*e] += 1;
+            seen[*e] <= 2
+        });
+
+        assert!(condition.should_retry(&0));
+        assert!(condition.should_retry(&1));
+        assert!(condition.should_retry(&0));
+        assert!(!condition.should_retry(&0));
+        assert!(condition.should_retry(&1));
+        assert_eq!(condition.into_state(), [3, 2]);
+    }
+
+    #[test]
+    fn combinators_compose_conditions() {
+        let small = |e: &u8| *e < 10;
+        let even = |e: &u8| e.is_multiple_of(2);
+        let mut condition = small.and(even.not()).or(never());
+
+        assert!(condition.should_retry(&3));
+        assert!(!condition.should_retry(&4));
+        assert!(!condition.should_retry(&11));
+        assert!(always().or(Never).should_retry(&()));
+        assert!(!always().and(Never).should_retry(&()));
+    }
+
+    #[tokio::test]
+    async fn combinators_poll_the_second_condition_once_the_first_is_ready() {
+        let first = ConditionAsync::new(|_: &()| async {
+            tokio::task::yield_now().await;
+            true
+        });
+        let second = ConditionAsync::new(|_: &()| async {
+            tokio::task::yield_now().await;
+            false
+        });
+        let mut condition = first.and(second);
+        let context = RetryContext::default();
+
+        let retry = std::future::poll_fn(|cx| condition.poll_should_retry(&(), &context, cx)).await;
+        assert!(!retry);
+    }
+
+    #[test]
+    fn io_kinds_retry_the_listed_kinds() {
+        let mut condition = io_kinds([io::ErrorKind::TimedOut]).with(io::ErrorKind::Interrupted);
+
+        assert!(condition.should_retry(&io::ErrorKind::TimedOut.into()));
+        assert!(condition.should_retry(&io::ErrorKind::Interrupted.into()));
+        assert!(!condition.should_retry(&io::ErrorKind::NotFound.into()));
+        let mut transient = transient_io_errors().without(io::ErrorKind::WouldBlock);
+        assert!(transient.should_retry(&io::ErrorKind::ConnectionReset.into()));
+        assert!(!transient.should_retry(&io::ErrorKind::WouldBlock.into()));
+        assert!(!transient.should_retry(&io::ErrorKind::PermissionDenied.into()));
+    }
+
+    #[test]
+    fn closures_ignore_context() {
+        let mut condition = |e: &u8| *e > 1;
+
+        assert!(condition.should_retry_with(&2, &RetryContext::new(10, Duration::from_secs(1))));
+        assert!(!condition.should_retry_with(&1, &RetryContext::default()));
+    }
+}
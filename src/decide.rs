@@ -0,0 +1,160 @@
+use core::fmt;
+use core::time::Duration;
+
+#[cfg(feature = "jitter")]
+use alloc::sync::Arc;
+
+use crate::notify::{DelayContext, Notify};
+#[cfg(feature = "jitter")]
+use crate::strategy::Jitter;
+
+/// Randomizes the `retry_after` delays, see [`RetryIf::jitter_retry_after`](crate::RetryIf::jitter_retry_after).
+#[cfg(feature = "jitter")]
+#[derive(Clone)]
+pub(crate) struct RetryAfterJitter(pub(crate) Arc<dyn Jitter + Send + Sync>);
+
+#[cfg(feature = "jitter")]
+impl fmt::Debug for RetryAfterJitter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryAfterJitter").finish_non_exhaustive()
+    }
+}
+
+/// Delay before the next attempt, picked by [`Decider::next_delay`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NextDelay {
+    /// The `retry_after` delay requested by the error if any, else the strategy delay.
+    pub(crate) delay: Duration,
+    retry_after: Option<Duration>,
+}
+
+/// Decisions taken on the transient error of a failed attempt, shared by
+/// [`RetryIf`](crate::RetryIf) and [`retry_with_timer`](crate::retry_with_timer) so they retry
+/// alike: the strategy step, the `retry_after` rules and the notify hook.
+///
+/// The condition is asked first through [`Condition::poll_should_retry`](crate::Condition::poll_should_retry),
+/// and [`Notify::poll_notified`] is awaited after [`Decider::notify`], by the caller, which
+/// also sleeps and runs the attempts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Decider {
+    /// Bounds of the `retry_after` delays, see [`RetryIf::clamp_retry_after`](crate::RetryIf::clamp_retry_after).
+    pub(crate) retry_after_bounds: Option<(Duration, Duration)>,
+    #[cfg(feature = "jitter")]
+    pub(crate) retry_after_jitter: Option<RetryAfterJitter>,
+    // passed to `Notify::notify`, see `DelayContext::duration`
+    duration: Duration,
+}
+
+impl Decider {
+    /// Duration passed to the last [`Notify::notify`] plus the delay that followed.
+    pub(crate) const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Steps `strategy` for the delay before the next attempt, which the `retry_after` delay
+    /// requested by the error replaces once jittered and clamped. `None` once the strategy
+    /// runs out, even with a `retry_after` request.
+    pub(crate) fn next_delay<I>(
+        &self,
+        strategy: &mut I,
+        retry_after: Option<Duration>,
+    ) -> Option<NextDelay>
+    where
+        I: Iterator<Item = Duration>,
+    {
+        let strategy_delay = strategy.next()?;
+        let retry_after = retry_after.map(|retry_after| {
+            #[cfg(feature = "jitter")]
+            let retry_after = match &self.retry_after_jitter {
+                Some(jitter) => jitter.0.jitter(retry_after),
+                None => retry_after,
+            };
+            match self.retry_after_bounds {
+                Some((min, max)) => retry_after.clamp(min, max),
+                None => retry_after,
+            }
+        });
+        Some(NextDelay {
+            delay: retry_after.unwrap_or(strategy_delay),
+            retry_after,
+        })
+    }
+
+    /// Notifies `notify` of the retry after `next`, `attempt` being the 1-based number of the
+    /// failed attempt, and returns the context it was given.
+    pub(crate) fn notify<E, N>(
+        &mut self,
+        notify: &mut N,
+        err: &E,
+        next: NextDelay,
+        attempt: usize,
+        elapsed: Duration,
+        attempt_duration: Duration,
+    ) -> DelayContext
+    where
+        N: Notify<E>,
+    {
+        let duration = next.retry_after.unwrap_or(self.duration);
+        let context = DelayContext::new(attempt, next.delay, elapsed, duration)
+            .with_attempt_duration(attempt_duration);
+        notify.notify_with(err, &context);
+        self.duration = duration.saturating_add(next.delay);
+        context
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::strategy::FixedInterval;
+
+    #[test]
+    fn retry_after_replaces_the_strategy_delay_within_its_bounds() {
+        let decider = Decider {
+            retry_after_bounds: Some((Duration::from_secs(1), Duration::from_secs(5))),
+            ..Decider::default()
+        };
+        let mut strategy = FixedInterval::from_millis(10).take(3);
+        let delays =
+            [None, Some(Duration::from_secs(60)), Some(Duration::ZERO)].map(|retry_after| {
+                decider
+                    .next_delay(&mut strategy, retry_after)
+                    .unwrap()
+                    .delay
+            });
+
+        assert_eq!(
+            delays,
+            [
+                Duration::from_millis(10),
+                Duration::from_secs(5),
+                Duration::from_secs(1)
+            ]
+        );
+        assert!(decider
+            .next_delay(&mut strategy, Some(Duration::from_secs(1)))
+            .is_none());
+    }
+
+    #[test]
+    fn notifies_with_the_sum_of_the_previous_delays() {
+        let mut decider = Decider::default();
+        let mut strategy = FixedInterval::from_millis(10);
+        let mut durations = Vec::new();
+        let mut notify = |_: &(), duration| durations.push(duration);
+        for attempt in 1..=3 {
+            let next = decider.next_delay(&mut strategy, None).unwrap();
+            decider.notify(
+                &mut notify,
+                &(),
+                next,
+                attempt,
+                Duration::ZERO,
+                Duration::ZERO,
+            );
+        }
+
+        assert_eq!(durations, [0, 10, 20].map(Duration::from_millis));
+        assert_eq!(decider.duration(), Duration::from_millis(30));
+    }
+}
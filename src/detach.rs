@@ -0,0 +1,101 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::task::{JoinError, JoinHandle};
+
+use crate::handle::RetryHandle;
+use crate::stats::RetryStats;
+
+/// Retry running as a tokio task, created by
+/// [`RetryIf::spawn_detached`](crate::RetryIf::spawn_detached) and
+/// [`RetryIf::detach`](crate::RetryIf::detach).
+///
+/// Awaiting it resolves with the outcome of the retry, or with a [`JoinError`] if the task
+/// was aborted or panicked. Dropping it lets the retry run to completion in the background,
+/// which suits fire-and-forget side effects like audit writes.
+pub struct DetachedRetry<T, E> {
+    task: JoinHandle<Result<T, E>>,
+    handle: RetryHandle,
+    stats: Arc<RetryStats>,
+}
+
+impl<T, E> DetachedRetry<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    pub(crate) fn spawn<F>(
+        retry: F,
+        name: Option<&str>,
+        handle: RetryHandle,
+        stats: Arc<RetryStats>,
+    ) -> Self
+    where
+        F: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        // tokio only names tasks, for tokio-console, in `tokio_unstable` builds
+        #[cfg(all(tokio_unstable, feature = "detach-names"))]
+        let task = match name {
+            Some(name) => tokio::task::Builder::new()
+                .name(name)
+                .spawn(retry)
+                .expect("failed to spawn the retry task"),
+            None => tokio::spawn(retry),
+        };
+        #[cfg(not(all(tokio_unstable, feature = "detach-names")))]
+        let task = {
+            let _ = name;
+            tokio::spawn(retry)
+        };
+        DetachedRetry {
+            task,
+            handle,
+            stats,
+        }
+    }
+}
+
+impl<T, E> DetachedRetry<T, E> {
+    /// Aborts the task, dropping the running attempt or sleep.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    /// Returns `true` once the task finished, retrying or not.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Returns a handle to pause and resume the retry, see
+    /// [`RetryIf::handle`](crate::RetryIf::handle).
+    pub fn handle(&self) -> RetryHandle {
+        self.handle.clone()
+    }
+
+    /// Returns a handle to the live statistics of the retry, see
+    /// [`RetryIf::stats`](crate::RetryIf::stats).
+    pub fn stats(&self) -> Arc<RetryStats> {
+        self.stats.clone()
+    }
+}
+
+impl<T, E> Future for DetachedRetry<T, E> {
+    type Output = Result<Result<T, E>, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.task).poll(cx)
+    }
+}
+
+impl<T, E> fmt::Debug for DetachedRetry<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DetachedRetry")
+            .field("id", &self.task.id())
+            .field("finished", &self.task.is_finished())
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::iter::{IntoIterator, Iterator};
+use std::time::Duration;
+
+use crate::action::{Action, AttemptContext};
+use crate::condition::{Condition, RetryContext};
+use crate::error::Error as RetryError;
+use crate::runtime::{Instant, Time};
+
+/// Next step of a [`RetryDriver`], returned by [`RetryDriver::next_attempt`].
+#[derive(Debug)]
+pub enum NextAttempt<F, E> {
+    /// Drive this attempt, then report its error with [`RetryDriver::failed`].
+    Run(F),
+    /// Wait this long before asking for the next attempt.
+    Sleep(Duration),
+    /// Stop retrying, the action failed with this error.
+    GiveUp(E),
+}
+
+#[derive(Debug)]
+enum DriverState {
+    Ready,
+    Running,
+    Failed(Option<Duration>),
+    Permanent,
+    Done,
+}
+
+/// Retry state machine decoupled from any future, for retries embedded in a hand-written
+/// poll loop, like the one of a connection pool, where [`RetryIf`](crate::RetryIf) doesn't
+/// fit.
+///
+/// The driver never sleeps nor polls: [`RetryDriver::next_attempt`] says whether to run an
+/// attempt, sleep, or give up, and the caller reports the error of every failed attempt with
+/// [`RetryDriver::failed`]. Delays, `retry_after` requests and the condition work as in
+/// [`RetryIf`](crate::RetryIf), but conditions are evaluated with
+/// [`Condition::should_retry_with`], without awaiting.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{NextAttempt, RetryDriver, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn connect() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+/// # async fn run() -> Result<u64, std::io::Error> {
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// let mut driver = RetryDriver::new(strategy, connect, |_: &std::io::Error| true);
+/// loop {
+///     match driver.next_attempt() {
+///         NextAttempt::Run(attempt) => match attempt.await {
+///             Ok(conn) => return Ok(conn),
+///             Err(err) => driver.failed(err),
+///         },
+///         NextAttempt::Sleep(delay) => tokio::time::sleep(delay).await,
+///         NextAttempt::GiveUp(err) => return Err(err),
+///     }
+/// }
+/// # }
+/// ```
+pub struct RetryDriver<I, A, C>
+where
+    A: Action,
+{
+    strategy: I,
+    action: A,
+    condition: C,
+    state: DriverState,
+    attempts: usize,
+    last_error: Option<A::Error>,
+    started: Instant,
+    time: Time,
+}
+
+impl<I, A, C> RetryDriver<I, A, C>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+{
+    /// Creates a driver retrying `action` according to `strategy` while `condition` holds,
+    /// its first step runs the first attempt.
+    pub fn new<T>(strategy: T, action: A, condition: C) -> Self
+    where
+        T: IntoIterator<IntoIter = I, Item = Duration>,
+    {
+        let time = Time::default();
+        RetryDriver {
+            strategy: strategy.into_iter(),
+            action,
+            condition,
+            state: DriverState::Ready,
+            attempts: 0,
+            last_error: None,
+            started: time.now(),
+            time,
+        }
+    }
+
+    /// Returns the next step of the retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the attempt it returned before wasn't reported with
+    /// [`RetryDriver::failed`], or after giving up.
+    pub fn next_attempt(&mut self) -> NextAttempt<A::Future, A::Error> {
+        match std::mem::replace(&mut self.state, DriverState::Running) {
+            DriverState::Ready => {
+                self.attempts += 1;
+                let context = AttemptContext::new(
+                    self.attempts,
+                    self.last_error.as_ref(),
+                    self.time.elapsed(self.started),
+                );
+                NextAttempt::Run(self.action.run_with(&context))
+            }
+            DriverState::Failed(retry_after) => {
+                let err = self
+                    .last_error
+                    .as_ref()
+                    .expect("a failed attempt leaves its error");
+                let context = RetryContext::new(self.attempts, self.time.elapsed(self.started));
+                let delay = if self.condition.should_retry_with(err, &context) {
+                    self.strategy.next()
+                } else {
+                    None
+                };
+                match delay {
+                    Some(delay) => {
+                        self.state = DriverState::Ready;
+                        NextAttempt::Sleep(retry_after.unwrap_or(delay))
+                    }
+                    None => self.give_up(),
+                }
+            }
+            DriverState::Permanent => self.give_up(),
+            DriverState::Running => panic!("the running attempt wasn't reported as failed"),
+            DriverState::Done => panic!("`next_attempt` called after giving up"),
+        }
+    }
+
+    /// Reports the error of the attempt returned by [`RetryDriver::next_attempt`].
+    ///
+    /// A permanent error gives up on the next step.
+    pub fn failed(&mut self, err: RetryError<A::Error>) {
+        match err {
+            RetryError::Permanent(err) => {
+                self.last_error = Some(err);
+                self.state = DriverState::Permanent;
+            }
+            RetryError::Transient { err, retry_after } => {
+                self.last_error = Some(err);
+                self.state = DriverState::Failed(retry_after);
+            }
+        }
+    }
+
+    /// Number of attempts started so far.
+    pub const fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Time since the driver was created.
+    pub fn elapsed(&self) -> Duration {
+        self.time.elapsed(self.started)
+    }
+
+    fn give_up(&mut self) -> NextAttempt<A::Future, A::Error> {
+        self.state = DriverState::Done;
+        NextAttempt::GiveUp(
+            self.last_error
+                .take()
+                .expect("a failed attempt leaves its error"),
+        )
+    }
+}
+
+// the strategy, action and condition are usually closures, so only the progress is shown
+impl<I, A, C> fmt::Debug for RetryDriver<I, A, C>
+where
+    A: Action,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryDriver")
+            .field("state", &self.state)
+            .field("attempts", &self.attempts)
+            .field("elapsed", &self.time.elapsed(self.started))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{ready, Future, Ready};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::strategy::FixedInterval;
+
+    type Outcome = Result<(), RetryError<&'static str>>;
+
+    fn driver(
+        mut outcomes: Vec<Outcome>,
+    ) -> RetryDriver<
+        std::iter::Take<FixedInterval>,
+        impl FnMut() -> Ready<Outcome>,
+        impl FnMut(&&'static str) -> bool,
+    > {
+        outcomes.reverse();
+        RetryDriver::new(
+            FixedInterval::from_millis(10).take(2),
+            move || ready(outcomes.pop().unwrap()),
+            |err: &&str| *err != "rejected",
+        )
+    }
+
+    fn run<I, A, C>(driver: &mut RetryDriver<I, A, C>) -> Result<A::Item, RetryError<A::Error>>
+    where
+        I: Iterator<Item = Duration>,
+        A: Action,
+        C: Condition<A::Error>,
+    {
+        match driver.next_attempt() {
+            NextAttempt::Run(attempt) => {
+                let mut cx = Context::from_waker(Waker::noop());
+                match pin!(attempt).poll(&mut cx) {
+                    Poll::Ready(outcome) => outcome,
+                    Poll::Pending => panic!("expected a ready attempt"),
+                }
+            }
+            _ => panic!("expected an attempt"),
+        }
+    }
+
+    #[test]
+    fn sleeps_between_attempts_until_the_strategy_runs_out() {
+        let mut driver = driver(vec![
+            Err(RetryError::transient("busy")),
+            Err(RetryError::retry_after("busy", Duration::from_secs(1))),
+            Err(RetryError::transient("busy")),
+        ]);
+        let mut delays = Vec::new();
+        loop {
+            let err = run(&mut driver).unwrap_err();
+            driver.failed(err);
+            match driver.next_attempt() {
+                NextAttempt::Sleep(delay) => delays.push(delay),
+                NextAttempt::GiveUp(err) => {
+                    assert_eq!(err, "busy");
+                    break;
+                }
+                NextAttempt::Run(_) => panic!("expected a sleep"),
+            }
+        }
+
+        assert_eq!(delays, [Duration::from_millis(10), Duration::from_secs(1)]);
+        assert_eq!(driver.attempts(), 3);
+    }
+
+    #[test]
+    fn permanent_and_rejected_errors_give_up_right_away() {
+        for err in [
+            RetryError::permanent("busy"),
+            RetryError::transient("rejected"),
+        ] {
+            let mut driver = driver(vec![Err(err)]);
+            let err = run(&mut driver).unwrap_err();
+            driver.failed(err);
+
+            assert!(matches!(driver.next_attempt(), NextAttempt::GiveUp(_)));
+            assert_eq!(driver.attempts(), 1);
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use std::error;
-use std::fmt;
+use core::error;
+use core::fmt;
+use core::time::Duration;
 
-use std::time::Duration;
+#[cfg(feature = "std")]
+use crate::runtime::Instant;
 
 const TRANSIENT_ERROR: &str = "transient error";
 const PERMANENT_ERROR: &str = "permanent error";
@@ -71,6 +73,73 @@ impl<E> Error<E> {
             retry_after: Some(duration),
         }
     }
+
+    /// Same as [`Error::retry_after`], reading better next to [`Error::transient`].
+    pub fn transient_after(err: E, duration: Duration) -> Self {
+        Error::retry_after(err, duration)
+    }
+
+    /// Returns true for a transient error.
+    pub const fn is_transient(&self) -> bool {
+        matches!(self, Error::Transient { .. })
+    }
+
+    /// Returns true for a permanent error.
+    pub const fn is_permanent(&self) -> bool {
+        matches!(self, Error::Permanent(_))
+    }
+
+    /// Returns the delay requested by a transient error, if any.
+    pub const fn retry_after_delay(&self) -> Option<Duration> {
+        match self {
+            Error::Permanent(_) => None,
+            Error::Transient { retry_after, .. } => *retry_after,
+        }
+    }
+
+    /// Returns a reference to the wrapped error.
+    pub const fn get_ref(&self) -> &E {
+        match self {
+            Error::Permanent(err) | Error::Transient { err, .. } => err,
+        }
+    }
+
+    /// Unwraps the error, dropping whether it is transient.
+    pub fn into_inner(self) -> E {
+        match self {
+            Error::Permanent(err) | Error::Transient { err, .. } => err,
+        }
+    }
+
+    /// Maps the wrapped error with `f`, keeping whether it is transient and its delay.
+    ///
+    /// ```rust
+    /// # use tokio_retry2::RetryError;
+    /// let error = RetryError::transient("timed out").map(|err| err.len());
+    /// assert_eq!(error, RetryError::transient(9));
+    /// ```
+    pub fn map<F>(self, f: impl FnOnce(E) -> F) -> Error<F> {
+        match self {
+            Error::Permanent(err) => Error::Permanent(f(err)),
+            Error::Transient { err, retry_after } => Error::Transient {
+                err: f(err),
+                retry_after,
+            },
+        }
+    }
+
+    /// Turns the error into a permanent one, to stop retrying it.
+    pub fn into_permanent(self) -> Self {
+        Error::Permanent(self.into_inner())
+    }
+
+    /// Turns the error into a transient one, keeping the delay of a transient error.
+    pub fn into_transient(self) -> Self {
+        match self {
+            Error::Permanent(err) => Error::transient(err),
+            transient => transient,
+        }
+    }
 }
 
 impl<E> fmt::Display for Error<E>
@@ -166,6 +235,249 @@ where
     }
 }
 
+const ATTEMPT_TIMEOUT: &str = "retry attempt timed out";
+
+/// `AttemptTimeout` is the transient error of an attempt that ran longer than the
+/// timeout set by [`RetryIf::with_attempt_timeout`](crate::RetryIf::with_attempt_timeout).
+///
+/// Actions bounded by an attempt timeout need an error type convertible from it,
+/// which `std::io::Error` already is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AttemptTimeout;
+
+impl fmt::Display for AttemptTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.write_str(ATTEMPT_TIMEOUT)
+    }
+}
+
+impl error::Error for AttemptTimeout {}
+
+#[cfg(feature = "std")]
+impl From<AttemptTimeout> for std::io::Error {
+    fn from(err: AttemptTimeout) -> Self {
+        std::io::Error::new(std::io::ErrorKind::TimedOut, err)
+    }
+}
+
+const DEADLINE_EXCEEDED: &str = "retry deadline exceeded";
+const CANCELLED: &str = "retry cancelled";
+const CIRCUIT_OPEN: &str = "circuit breaker is open";
+const SHUT_DOWN: &str = "retry shut down";
+
+/// `RetryFailure` is the error value of retry futures that may stop for reasons other
+/// than the action failing, like [`RetryIf::with_deadline`](crate::RetryIf::with_deadline).
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RetryFailure<E> {
+    /// The action failed with a permanent error, or its transient error was not retried
+    /// any further.
+    Error(E),
+
+    /// The deadline of the retry operation passed before the action succeeded.
+    /// Carries the error of the last failed attempt, if any attempt failed.
+    DeadlineExceeded { last_error: Option<E> },
+
+    /// The retry operation was cancelled before the action succeeded.
+    /// Carries the error of the last failed attempt, if any attempt failed.
+    Cancelled { last_error: Option<E> },
+
+    /// The circuit breaker of the retry operation refused to start the first attempt,
+    /// see [`RetryIf::with_circuit_breaker`](crate::RetryIf::with_circuit_breaker).
+    CircuitOpen,
+
+    /// The shutdown signal of the retry operation resolved before the action succeeded,
+    /// see [`RetryIf::until`](crate::RetryIf::until).
+    /// Carries the error of the last failed attempt, if any attempt failed.
+    ShutDown { last_error: Option<E> },
+}
+
+impl<E> RetryFailure<E> {
+    /// Returns the last error of the action, if any attempt failed.
+    pub fn into_error(self) -> Option<E> {
+        match self {
+            RetryFailure::Error(err) => Some(err),
+            RetryFailure::DeadlineExceeded { last_error }
+            | RetryFailure::Cancelled { last_error }
+            | RetryFailure::ShutDown { last_error } => last_error,
+            RetryFailure::CircuitOpen => None,
+        }
+    }
+
+    /// Returns `true` if the retry operation stopped because its deadline passed.
+    pub const fn is_deadline_exceeded(&self) -> bool {
+        matches!(self, RetryFailure::DeadlineExceeded { .. })
+    }
+
+    /// Returns `true` if the retry operation was cancelled.
+    pub const fn is_cancelled(&self) -> bool {
+        matches!(self, RetryFailure::Cancelled { .. })
+    }
+
+    /// Returns `true` if the circuit breaker refused to start the retry operation.
+    pub const fn is_circuit_open(&self) -> bool {
+        matches!(self, RetryFailure::CircuitOpen)
+    }
+
+    /// Returns `true` if the retry operation stopped because of its shutdown signal.
+    pub const fn is_shut_down(&self) -> bool {
+        matches!(self, RetryFailure::ShutDown { .. })
+    }
+}
+
+impl<E> fmt::Display for RetryFailure<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            RetryFailure::Error(ref err) => err.fmt(f),
+            RetryFailure::DeadlineExceeded {
+                last_error: Some(ref err),
+            } => write!(f, "{DEADLINE_EXCEEDED}: {err}"),
+            RetryFailure::DeadlineExceeded { last_error: None } => f.write_str(DEADLINE_EXCEEDED),
+            RetryFailure::Cancelled {
+                last_error: Some(ref err),
+            } => write!(f, "{CANCELLED}: {err}"),
+            RetryFailure::Cancelled { last_error: None } => f.write_str(CANCELLED),
+            RetryFailure::CircuitOpen => f.write_str(CIRCUIT_OPEN),
+            RetryFailure::ShutDown {
+                last_error: Some(ref err),
+            } => write!(f, "{SHUT_DOWN}: {err}"),
+            RetryFailure::ShutDown { last_error: None } => f.write_str(SHUT_DOWN),
+        }
+    }
+}
+
+impl<E> error::Error for RetryFailure<E>
+where
+    E: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RetryFailure::Error(ref err) => err.source(),
+            RetryFailure::DeadlineExceeded { ref last_error }
+            | RetryFailure::Cancelled { ref last_error }
+            | RetryFailure::ShutDown { ref last_error } => last_error
+                .as_ref()
+                .map(|err| err as &(dyn error::Error + 'static)),
+            RetryFailure::CircuitOpen => None,
+        }
+    }
+}
+
+/// `RetryReport` is the error value of retry futures created with
+/// [`RetryIf::spawn_with_report`](crate::RetryIf::spawn_with_report), describing every attempt
+/// of a retry operation that failed.
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RetryReport<E> {
+    /// Number of attempts made, including the last one.
+    pub attempts: usize,
+    /// Time between the creation of the retry future and the final error.
+    pub elapsed: Duration,
+    /// Errors of the retried attempts, oldest first, without the final error.
+    pub errors: Vec<E>,
+    /// Error the retry operation failed with.
+    pub error: E,
+    /// Instants every attempt started at, oldest first.
+    pub attempt_times: Vec<Instant>,
+    /// Why the retry operation stopped.
+    pub reason: StopReason,
+}
+
+/// Progress of a retry operation giving up, see
+/// [`RetryIf::map_give_up`](crate::RetryIf::map_give_up).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GiveUpContext {
+    /// Number of attempts made, including the last one.
+    pub attempts: usize,
+    /// Time between the creation of the retry future and the final error.
+    pub elapsed: Duration,
+    /// Why the retry operation stopped.
+    pub reason: StopReason,
+}
+
+/// Why a retry operation stopped retrying, see [`RetryReport::reason`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StopReason {
+    /// The strategy, `max_elapsed`, the retry budget or the circuit breaker ran out.
+    Exhausted,
+    /// The action failed with a permanent error.
+    Permanent,
+    /// The condition rejected the last error.
+    Rejected,
+    /// The shutdown signal stopped scheduling retries, see
+    /// [`RetryIf::until`](crate::RetryIf::until).
+    ShutDown,
+}
+
+#[cfg(feature = "std")]
+impl<E> RetryReport<E> {
+    /// Discards the history, returning the error the retry operation failed with.
+    pub fn into_error(self) -> E {
+        self.error
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> fmt::Display for RetryReport<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "failed after {} attempts in {:?}: {}",
+            self.attempts, self.elapsed, self.error
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> error::Error for RetryReport<E>
+where
+    E: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// `FallbackError` is the error value of retry futures created with
+/// [`RetryIf::or_else_retry`](crate::RetryIf::or_else_retry), once both the primary and the
+/// fallback actions failed.
+#[derive(Debug, PartialEq)]
+pub struct FallbackError<E, F = E> {
+    /// Error the primary retry future failed with.
+    pub primary: E,
+    /// Error the fallback retry future failed with.
+    pub fallback: F,
+}
+
+impl<E, F> fmt::Display for FallbackError<E, F>
+where
+    E: fmt::Display,
+    F: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{} (fallback: {})", self.primary, self.fallback)
+    }
+}
+
+impl<E, F> error::Error for FallbackError<E, F>
+where
+    E: error::Error,
+    F: error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.fallback)
+    }
+}
+
 #[cfg(feature = "implicit_results")]
 #[derive(Debug, PartialEq)]
 pub enum RetryResult<T, E> {
@@ -284,6 +596,33 @@ mod test {
         );
     }
 
+    #[test]
+    fn create_transient_error_after() {
+        let retry_after = Duration::from_secs(42);
+        let e = Error::transient_after("err", retry_after);
+        assert!(e.is_transient());
+        assert_eq!(e.retry_after_delay(), Some(retry_after));
+        assert_eq!(e, Error::retry_after("err", retry_after));
+    }
+
+    #[test]
+    fn map_keeps_the_kind_and_delay() {
+        let retry_after = Duration::from_secs(42);
+        let e = Error::retry_after("err", retry_after).map(str::len);
+        assert_eq!(e, Error::retry_after(3, retry_after));
+
+        let e = Error::permanent("err").map(str::len);
+        assert!(e.is_permanent());
+        assert_eq!(e.into_inner(), 3);
+    }
+
+    #[test]
+    fn converts_between_transient_and_permanent() {
+        let e = Error::transient("err").into_permanent();
+        assert_eq!(e, Error::Permanent("err"));
+        assert_eq!(e.into_transient(), Error::transient("err"));
+    }
+
     #[test]
     fn map_transient_keeps_ok() {
         let result: Result<i32, Error<()>> = Ok(42).map_transient_err();
@@ -385,6 +724,77 @@ mod test {
         assert!(error.cause().is_none());
     }
 
+    #[test]
+    fn attempt_timeout_into_io_error() {
+        let err = std::io::Error::from(AttemptTimeout);
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(err.to_string(), ATTEMPT_TIMEOUT);
+    }
+
+    #[test]
+    fn fmt_deadline_exceeded() {
+        let failure = RetryFailure::DeadlineExceeded {
+            last_error: Some(TRANSIENT_ERROR),
+        };
+        assert_eq!(
+            format!("{}", failure),
+            "retry deadline exceeded: transient error"
+        );
+
+        let failure = RetryFailure::<&str>::DeadlineExceeded { last_error: None };
+        assert_eq!(format!("{}", failure), DEADLINE_EXCEEDED);
+    }
+
+    #[test]
+    fn source_deadline_exceeded() {
+        let failure = RetryFailure::DeadlineExceeded {
+            last_error: Some(MyError(TRANSIENT_ERROR)),
+        };
+        assert_eq!(failure.source().unwrap().to_string(), TRANSIENT_ERROR);
+        assert!(failure.is_deadline_exceeded());
+        assert_eq!(failure.into_error(), Some(MyError(TRANSIENT_ERROR)));
+    }
+
+    #[test]
+    fn fmt_report() {
+        let report = RetryReport {
+            attempts: 3,
+            elapsed: Duration::from_millis(20),
+            errors: vec![MyError(TRANSIENT_ERROR), MyError(TRANSIENT_ERROR)],
+            error: MyError(PERMANENT_ERROR),
+            attempt_times: vec![Instant::now(); 3],
+            reason: StopReason::Permanent,
+        };
+        assert_eq!(
+            report.to_string(),
+            "failed after 3 attempts in 20ms: permanent error"
+        );
+        assert_eq!(report.source().unwrap().to_string(), PERMANENT_ERROR);
+        assert_eq!(report.into_error(), MyError(PERMANENT_ERROR));
+    }
+
+    #[test]
+    fn fmt_fallback() {
+        let err = FallbackError {
+            primary: MyError(TRANSIENT_ERROR),
+            fallback: MyError(PERMANENT_ERROR),
+        };
+        assert_eq!(
+            err.to_string(),
+            "transient error (fallback: permanent error)"
+        );
+        assert_eq!(err.source().unwrap().to_string(), PERMANENT_ERROR);
+    }
+
+    #[test]
+    fn fmt_circuit_open() {
+        let failure = RetryFailure::<MyError>::CircuitOpen;
+        assert_eq!(failure.to_string(), CIRCUIT_OPEN);
+        assert!(failure.source().is_none());
+        assert!(failure.is_circuit_open());
+        assert_eq!(failure.into_error(), None);
+    }
+
     #[derive(Debug, PartialEq)]
     pub struct MyError(pub &'static str);
     impl fmt::Display for MyError {
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use crate::error::StopReason;
+
+/// Lifecycle event of a retry future, received from the channel returned by
+/// [`RetryIf::events`](crate::RetryIf::events).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum RetryEvent<E> {
+    /// An attempt started.
+    AttemptStarted {
+        /// 1-based number of the attempt.
+        attempt: usize,
+    },
+    /// An attempt failed.
+    AttemptFailed {
+        /// 1-based number of the attempt.
+        attempt: usize,
+        /// Error of the attempt.
+        err: E,
+        /// Delay before the next attempt, `None` if the attempt isn't retried.
+        delay: Option<Duration>,
+    },
+    /// The retry future started sleeping before the next attempt.
+    Sleeping {
+        /// Time it sleeps for.
+        delay: Duration,
+    },
+    /// An attempt succeeded.
+    Succeeded {
+        /// Number of attempts made, including the successful one.
+        attempts: usize,
+        /// Time since the retry future was created.
+        elapsed: Duration,
+    },
+    /// The retry future gave up.
+    Exhausted {
+        /// Number of attempts made.
+        attempts: usize,
+        /// Time since the retry future was created.
+        elapsed: Duration,
+        /// Why it stopped retrying.
+        reason: StopReason,
+    },
+}
+
+/// Sending half of the events of a retry future, cloning the errors it reports.
+pub(crate) struct EventSender<E> {
+    sender: mpsc::Sender<RetryEvent<E>>,
+    clone: fn(&E) -> E,
+}
+
+impl<E> EventSender<E> {
+    pub(crate) fn channel(capacity: usize) -> (Self, mpsc::Receiver<RetryEvent<E>>)
+    where
+        E: Clone,
+    {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let sender = EventSender {
+            sender,
+            clone: E::clone,
+        };
+        (sender, receiver)
+    }
+
+    /// Sends `event` unless the channel is full or closed, the retry never waits for it.
+    pub(crate) fn send(&self, event: RetryEvent<E>) {
+        let _ = self.sender.try_send(event);
+    }
+
+    pub(crate) fn failed(&self, attempt: usize, err: &E, delay: Option<Duration>) {
+        self.send(RetryEvent::AttemptFailed {
+            attempt,
+            err: (self.clone)(err),
+            delay,
+        });
+    }
+}
@@ -0,0 +1,88 @@
+use std::future::IntoFuture;
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::condition::{Always, Condition};
+use crate::future::RetryIf;
+use crate::notify::{NoNotify, Notify};
+
+/// Extension trait retrying any [`Action`], like a closure returning a future, in postfix
+/// style.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{RetryError, RetryExt};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn fetch(id: u64) -> Result<String, RetryError<std::io::Error>> { Ok(String::new()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let user = (|| fetch(42))
+///     .retry(ExponentialBackoff::from_millis(10).take(3))
+///     .when(|err: &std::io::Error| err.kind() == std::io::ErrorKind::TimedOut)
+///     .notify(|err: &std::io::Error, duration| println!("{err}, retrying after {duration:?}"))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait RetryExt: Action + Sized {
+    /// Retries this action according to `strategy` once awaited.
+    fn retry<T>(self, strategy: T) -> Retryable<T, Self>
+    where
+        T: IntoIterator<Item = Duration>,
+    {
+        Retryable {
+            strategy,
+            action: self,
+            condition: Always,
+            notify: NoNotify,
+        }
+    }
+}
+
+impl<A: Action> RetryExt for A {}
+
+/// Action configured for retries, created by [`RetryExt::retry`]. Awaiting it starts a
+/// [`RetryIf`].
+#[derive(Debug, Clone)]
+pub struct Retryable<T, A, C = Always, N = NoNotify> {
+    strategy: T,
+    action: A,
+    condition: C,
+    notify: N,
+}
+
+impl<T, A, C, N> Retryable<T, A, C, N> {
+    /// Only retries the transient errors satisfying `condition`.
+    pub fn when<D>(self, condition: D) -> Retryable<T, A, D, N> {
+        Retryable {
+            strategy: self.strategy,
+            action: self.action,
+            condition,
+            notify: self.notify,
+        }
+    }
+
+    /// Notifies `notify` before every retry.
+    pub fn notify<M>(self, notify: M) -> Retryable<T, A, C, M> {
+        Retryable {
+            strategy: self.strategy,
+            action: self.action,
+            condition: self.condition,
+            notify,
+        }
+    }
+}
+
+impl<T, A, C, N> IntoFuture for Retryable<T, A, C, N>
+where
+    T: IntoIterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Result<A::Item, A::Error>;
+    type IntoFuture = RetryIf<T::IntoIter, A, C, N>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        RetryIf::spawn(self.strategy, self.action, self.condition, self.notify)
+    }
+}
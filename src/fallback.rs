@@ -0,0 +1,84 @@
+use std::future::Future;
+use std::iter::Iterator;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::error::FallbackError;
+use crate::future::Retry;
+
+/// Future that retries a fallback action with its own strategy once the primary retry
+/// future failed. Created by [`RetryIf::or_else_retry`](crate::RetryIf::or_else_retry).
+///
+/// Fails with a [`FallbackError`] carrying the errors of both.
+#[pin_project]
+pub struct Fallback<P, I, A, E>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    #[pin]
+    primary: P,
+    #[pin]
+    fallback: Option<Retry<I, A>>,
+    pending: Option<(I, A)>,
+    primary_error: Option<E>,
+}
+
+impl<P, I, A, E> Fallback<P, I, A, E>
+where
+    P: Future<Output = Result<A::Item, E>>,
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    pub(crate) fn new(primary: P, strategy: I, action: A) -> Self {
+        Fallback {
+            primary,
+            fallback: None,
+            pending: Some((strategy, action)),
+            primary_error: None,
+        }
+    }
+}
+
+impl<P, I, A, E> Future for Fallback<P, I, A, E>
+where
+    P: Future<Output = Result<A::Item, E>>,
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    type Output = Result<A::Item, FallbackError<E, A::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut this = self.project();
+        if this.primary_error.is_none() {
+            match this.primary.poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(ok)) => return Poll::Ready(Ok(ok)),
+                Poll::Ready(Err(err)) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("primary retry failed, retrying fallback action");
+                    let (strategy, action) = this.pending.take().expect("polled after completion");
+                    this.fallback.set(Some(Retry::spawn(strategy, action)));
+                    *this.primary_error = Some(err);
+                }
+            }
+        }
+
+        let fallback = this
+            .fallback
+            .as_pin_mut()
+            .expect("the fallback starts once the primary retry failed");
+        match fallback.poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(ok)) => Poll::Ready(Ok(ok)),
+            Poll::Ready(Err(fallback)) => Poll::Ready(Err(FallbackError {
+                primary: this.primary_error.take().expect("polled after completion"),
+                fallback,
+            })),
+        }
+    }
+}
@@ -4,16 +4,45 @@ use std::fmt;
 use std::future::Future;
 use std::iter::{IntoIterator, Iterator};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use pin_project::pin_project;
-use tokio::time::{sleep_until, Duration, Instant, Sleep};
+#[cfg(feature = "events")]
+use tokio::sync::mpsc;
+#[cfg(feature = "cancellation")]
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
 
-use crate::error::Error as RetryError;
-use crate::notify::Notify;
+use crate::boxed::{BoxedRetry, BoxedRetryPolicy};
+use crate::breaker::{BreakerPermit, CircuitBreaker};
+use crate::budget::RetryBudget;
+use crate::builder::RetryBuilder;
+use crate::decide::Decider;
+#[cfg(feature = "jitter")]
+use crate::decide::RetryAfterJitter;
+#[cfg(feature = "detach")]
+use crate::detach::DetachedRetry;
+use crate::error::{
+    AttemptTimeout, Error as RetryError, GiveUpContext, RetryFailure, RetryReport, StopReason,
+};
+#[cfg(feature = "events")]
+use crate::events::{EventSender, RetryEvent};
+use crate::fallback::Fallback;
+use crate::handle::RetryHandle;
+use crate::handler::{handled, ErrorHandler, Handled};
+#[cfg(feature = "metrics")]
+use crate::metrics::RetryMetrics;
+use crate::notify::{DelayContext, Notify};
+use crate::rate_limit::RateLimiter;
+use crate::runtime::{deadline_after, Clock, Instant, Sleep, Time};
+use crate::shutdown::{OnShutdown, Shutdown};
+use crate::stats::RetryStats;
+#[cfg(feature = "jitter")]
+use crate::strategy::Jitter;
 
-use super::action::Action;
-use super::condition::Condition;
+use super::action::{Action, ActionWith, AnyError, AttemptContext, BorrowingAction, WithInput};
+use super::condition::{Condition, RetryContext};
 
 #[pin_project(project = RetryStateProj)]
 enum RetryState<A>
@@ -21,14 +50,63 @@ where
     A: Action,
 {
     Running(#[pin] A::Future),
-    Sleeping(#[pin] Sleep),
+    Deciding,
+    Notifying(Duration),
+    Sleeping,
+}
+
+/// What a retry future is doing, see [`RetryIf::phase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryPhase {
+    /// An attempt is running.
+    Running,
+    /// The condition is deciding whether the failed attempt is retried.
+    Deciding,
+    /// The notify hook is handling the failed attempt.
+    Notifying,
+    /// The future is sleeping before the next attempt.
+    Sleeping,
 }
 
 impl<A: Action> RetryState<A> {
-    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> RetryFuturePoll<A> {
+    const fn phase(&self) -> RetryPhase {
+        match self {
+            RetryState::Running(_) => RetryPhase::Running,
+            RetryState::Deciding => RetryPhase::Deciding,
+            RetryState::Notifying(_) => RetryPhase::Notifying,
+            RetryState::Sleeping => RetryPhase::Sleeping,
+        }
+    }
+
+    fn poll<C, N>(
+        self: Pin<&mut Self>,
+        condition: &mut C,
+        notify: &mut N,
+        last_error: Option<&A::Error>,
+        context: impl FnOnce() -> RetryContext,
+        sleep: Pin<&mut Option<Sleep>>,
+        cx: &mut Context,
+    ) -> RetryFuturePoll<A>
+    where
+        C: Condition<A::Error>,
+        N: Notify<A::Error>,
+    {
         match self.project() {
             RetryStateProj::Running(future) => RetryFuturePoll::Running(future.poll(cx)),
-            RetryStateProj::Sleeping(future) => RetryFuturePoll::Sleeping(future.poll(cx)),
+            RetryStateProj::Deciding => RetryFuturePoll::Deciding(condition.poll_should_retry(
+                last_error.expect("a failed attempt is stored while deciding"),
+                &context(),
+                cx,
+            )),
+            RetryStateProj::Notifying(delay) => {
+                RetryFuturePoll::Notifying(notify.poll_notified(cx).map(|()| *delay))
+            }
+            RetryStateProj::Sleeping => RetryFuturePoll::Sleeping(
+                sleep
+                    .as_pin_mut()
+                    .expect("the sleep is set while sleeping")
+                    .poll(cx),
+            ),
         }
     }
 }
@@ -38,6 +116,8 @@ where
     A: Action,
 {
     Running(Poll<Result<A::Item, RetryError<A::Error>>>),
+    Deciding(Poll<bool>),
+    Notifying(Poll<Duration>),
     Sleeping(Poll<()>),
 }
 
@@ -71,13 +151,63 @@ where
         }
     }
 
+    /// Same as [`Retry::spawn`], running the retry as a tokio task, see [`RetryIf::detach`].
+    #[cfg(feature = "detach")]
+    pub fn spawn_detached<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+    ) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        Retry::spawn(strategy, action).detach()
+    }
+
+    /// Same as [`Retry::spawn`], failing with a [`RetryReport`] of every attempt,
+    /// see [`RetryIf::spawn_with_report`].
+    pub fn spawn_with_report<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+    ) -> Reported<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        Retry::spawn(strategy, action).retry_if.with_report()
+    }
+
+    /// Same as [`Retry::spawn`], succeeding with a [`Retried`] carrying the number of
+    /// attempts and the elapsed time, see [`RetryIf::spawn_reporting`].
+    pub fn spawn_reporting<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+    ) -> Reporting<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        Retry::spawn(strategy, action).retry_if.reporting()
+    }
+
+    /// Same as [`Retry::spawn`], sleeping `delay` before the first attempt,
+    /// see [`RetryIf::spawn_after`].
+    pub fn spawn_after<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        delay: Duration,
+        strategy: T,
+        action: A,
+    ) -> Retry<I, A> {
+        Retry {
+            retry_if: RetryIf::spawn_after(
+                delay,
+                strategy,
+                action,
+                (|_| true) as fn(&A::Error) -> bool,
+                (|_, _| {}) as fn(&A::Error, std::time::Duration),
+            ),
+        }
+    }
+
     pub fn spawn_notify<T: IntoIterator<IntoIter = I, Item = Duration>, F>(
         strategy: T,
         action: A,
         notify: F,
     ) -> RetryIf<I, A, fn(&A::Error) -> bool, F>
     where
-        F: FnMut(&A::Error, std::time::Duration),
+        F: Notify<A::Error>,
     {
         RetryIf::spawn(
             strategy,
@@ -88,6 +218,560 @@ where
     }
 }
 
+impl<I, A> Retry<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    /// Returns a handle to the live statistics of the retry, see [`RetryIf::stats`].
+    pub fn stats(&self) -> Arc<RetryStats> {
+        self.retry_if.stats()
+    }
+
+    /// Returns a handle to pause and resume the retry, see [`RetryIf::handle`].
+    pub fn handle(&self) -> RetryHandle {
+        self.retry_if.handle()
+    }
+
+    /// Spawns the retry onto the tokio runtime, see [`RetryIf::detach`].
+    #[cfg(feature = "detach")]
+    pub fn detach(self) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        let (handle, stats) = (self.handle(), self.stats());
+        DetachedRetry::spawn(self, None, handle, stats)
+    }
+
+    /// Spawns the retry onto the tokio runtime as a named task, see
+    /// [`RetryIf::detach_named`].
+    #[cfg(feature = "detach")]
+    pub fn detach_named(self, name: &str) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        let (handle, stats) = (self.handle(), self.stats());
+        DetachedRetry::spawn(self, Some(name), handle, stats)
+    }
+
+    /// Hands the [`RetryReport`] of a failed retry to `hook`, see [`RetryIf::on_dead_letter`].
+    pub fn on_dead_letter<F, R>(
+        self,
+        hook: F,
+    ) -> DeadLetter<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration), F>
+    where
+        F: FnOnce(RetryReport<A::Error>) -> R,
+    {
+        self.retry_if.on_dead_letter(hook)
+    }
+
+    /// Converts the error of a failed retry with `f`, see [`RetryIf::map_give_up`].
+    pub fn map_give_up<F, R>(
+        self,
+        f: F,
+    ) -> GiveUp<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration), F>
+    where
+        F: FnOnce(A::Error, GiveUpContext) -> R,
+    {
+        self.retry_if.map_give_up(f)
+    }
+
+    /// Number of attempts started so far, see [`RetryIf::attempts`].
+    pub fn attempts(&self) -> usize {
+        self.retry_if.attempts()
+    }
+
+    /// What the retry is doing, see [`RetryIf::phase`].
+    pub fn phase(&self) -> RetryPhase {
+        self.retry_if.phase()
+    }
+
+    /// Returns `true` once the retry future completed, see [`RetryIf::is_terminated`].
+    pub fn is_terminated(&self) -> bool {
+        self.retry_if.is_terminated()
+    }
+
+    /// Cancels the retry and hands back its parts, see [`RetryIf::into_parts`].
+    pub fn into_parts(
+        self,
+    ) -> RetryParts<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        self.retry_if.into_parts()
+    }
+
+    /// Hands back the parts of a pinned retry, see [`RetryIf::take_parts`].
+    pub fn take_parts(
+        self: Pin<&mut Self>,
+    ) -> Option<RetryParts<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)>> {
+        self.project().retry_if.take_parts()
+    }
+
+    /// Sum of the delays scheduled so far, see [`RetryIf::duration`].
+    pub fn duration(&self) -> Duration {
+        self.retry_if.duration()
+    }
+
+    /// Time since the retry started, see [`RetryIf::elapsed`].
+    pub fn elapsed(&self) -> Duration {
+        self.retry_if.elapsed()
+    }
+
+    /// Instant of the next attempt while sleeping, see [`RetryIf::next_attempt_at`].
+    pub fn next_attempt_at(&self) -> Option<Instant> {
+        self.retry_if.next_attempt_at()
+    }
+
+    /// Time spent sleeping between attempts so far, see [`RetryIf::total_slept`].
+    pub fn total_slept(&self) -> Duration {
+        self.retry_if.total_slept()
+    }
+
+    /// Delay before the next attempt, see [`RetryIf::next_planned_delay`].
+    pub fn next_planned_delay(&self) -> Option<Duration>
+    where
+        I: Clone,
+    {
+        self.retry_if.next_planned_delay()
+    }
+
+    /// Measures time and sleeps with `clock`, see [`RetryIf::with_clock`].
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.retry_if = self.retry_if.with_clock(clock);
+        self
+    }
+
+    /// Retries as soon as a future returned by `trigger` resolves, see [`RetryIf::wake_on`].
+    pub fn wake_on<F, Fut>(mut self, trigger: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.retry_if = self.retry_if.wake_on(trigger);
+        self
+    }
+
+    /// Returns a channel receiving the events of the retry, see [`RetryIf::events`].
+    #[cfg(feature = "events")]
+    pub fn events(&mut self, capacity: usize) -> mpsc::Receiver<RetryEvent<A::Error>>
+    where
+        A::Error: Clone,
+    {
+        self.retry_if.events(capacity)
+    }
+
+    /// Emits the metrics of the retry, see [`RetryIf::with_metrics`].
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: RetryMetrics) -> Self {
+        self.retry_if = self.retry_if.with_metrics(metrics);
+        self
+    }
+
+    /// Records the last transient error in the `retry` span, see [`RetryIf::trace_errors`].
+    #[cfg(feature = "tracing")]
+    pub fn trace_errors(mut self) -> Self
+    where
+        A::Error: fmt::Debug,
+    {
+        self.retry_if = self.retry_if.trace_errors();
+        self
+    }
+
+    /// Replaces the span the retry runs in, see [`RetryIf::with_span`].
+    #[cfg(feature = "tracing")]
+    pub fn with_span(mut self, span: tracing::Span) -> Self {
+        self.retry_if = self.retry_if.with_span(span);
+        self
+    }
+
+    /// Retries a fallback action with its own strategy once this retry failed,
+    /// see [`RetryIf::or_else_retry`].
+    pub fn or_else_retry<T, F>(
+        self,
+        strategy: T,
+        action: F,
+    ) -> Fallback<Self, T::IntoIter, F, A::Error>
+    where
+        T: IntoIterator<Item = Duration>,
+        F: Action<Item = A::Item>,
+    {
+        Fallback::new(self, strategy.into_iter(), action)
+    }
+
+    /// Bounds the whole retry operation by a deadline, see [`RetryIf::with_deadline`].
+    pub fn with_deadline(
+        self,
+        deadline: Instant,
+    ) -> Guarded<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        self.retry_if.with_deadline(deadline)
+    }
+
+    /// Registers the retry with a shared circuit breaker, see [`RetryIf::with_circuit_breaker`].
+    pub fn with_circuit_breaker(
+        self,
+        breaker: CircuitBreaker,
+    ) -> Guarded<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        self.retry_if.with_circuit_breaker(breaker)
+    }
+
+    /// Stops the retry operation once `token` is cancelled, see [`RetryIf::with_cancellation`].
+    #[cfg(feature = "cancellation")]
+    pub fn with_cancellation(
+        self,
+        token: CancellationToken,
+    ) -> Guarded<I, A, fn(&A::Error) -> bool, fn(&A::Error, std::time::Duration)> {
+        self.retry_if.with_cancellation(token)
+    }
+}
+
+impl Retry<std::iter::Empty<Duration>, fn() -> std::future::Ready<Result<(), RetryError<()>>>> {
+    /// Starts building a retry future, see [`RetryBuilder`].
+    pub const fn builder() -> RetryBuilder<()> {
+        RetryBuilder::new()
+    }
+
+    /// Retries `action`, a closure returning a plain `Result<T, E>`, retrying every error
+    /// as transient, so it doesn't have to wrap them into [`RetryError`].
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::Retry;
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn connect() -> Result<(), std::io::Error> { Ok(()) }
+    /// # async fn run() -> Result<(), std::io::Error> {
+    /// Retry::spawn_any(FixedInterval::from_millis(10).take(3), connect).await
+    /// # }
+    /// ```
+    pub fn spawn_any<S, F, Fut, T, E>(strategy: S, action: F) -> Retry<S::IntoIter, AnyError<F>>
+    where
+        S: IntoIterator<Item = Duration>,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        Retry::spawn(strategy, AnyError::new(action))
+    }
+
+    /// Retries `action` with a clone of `input` on every attempt, e.g. the request or message
+    /// to send, so attempts after the first still have it.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # #[derive(Clone)]
+    /// # struct Message;
+    /// # async fn send(message: Message) -> Result<(), std::io::Error> { Ok(()) }
+    /// # async fn run(message: Message) -> Result<(), std::io::Error> {
+    /// Retry::spawn_with(FixedInterval::from_millis(10).take(3), message, |message| async move {
+    ///     send(message).await.map_err(RetryError::transient)
+    /// })
+    /// .await
+    /// # }
+    /// ```
+    pub fn spawn_with<S, In, A>(
+        strategy: S,
+        input: In,
+        action: A,
+    ) -> Retry<S::IntoIter, WithInput<In, A>>
+    where
+        S: IntoIterator<Item = Duration>,
+        In: Clone,
+        A: ActionWith<In>,
+    {
+        Retry::spawn(strategy, WithInput::new(input, action))
+    }
+
+    /// Retries `action` with a type-erased `policy`, see [`BoxedRetryPolicy`].
+    pub fn boxed<A>(policy: BoxedRetryPolicy<A::Error>, action: A) -> BoxedRetry<A::Item, A::Error>
+    where
+        A: Action + Send + 'static,
+        A::Future: Send + 'static,
+    {
+        policy.retry(action)
+    }
+
+    /// Retries `action` as long as `handler` decides to, with the delays it returns, in place
+    /// of a condition and strategy, see [`ErrorHandler`].
+    pub fn spawn_handled<A, H>(
+        action: A,
+        handler: H,
+    ) -> Retry<std::iter::Repeat<Duration>, Handled<A, H>>
+    where
+        A: Action,
+        H: ErrorHandler<A::Error>,
+    {
+        Retry::spawn(std::iter::repeat(Duration::ZERO), handled(action, handler))
+    }
+
+    /// Retries `action` with exclusive access to `state`, returning the state alongside the
+    /// result so progress like pagination cursors or partial downloads survives between
+    /// attempts.
+    ///
+    /// Attempts run through [`Retry::spawn`]. Like with [`Retry::spawn_async`], the future is
+    /// only `Send` if `action` doesn't borrow from its scope.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn fetch_page(cursor: usize) -> Result<Option<Vec<u8>>, std::io::Error> { Ok(None) }
+    /// # async fn run() -> Result<(), std::io::Error> {
+    /// let (pages, result) = Retry::spawn_with_state(
+    ///     FixedInterval::from_millis(10).take(3),
+    ///     Vec::new(),
+    ///     async |pages: &mut Vec<Vec<u8>>| {
+    ///         // resume after the pages fetched by previous attempts
+    ///         while let Some(page) = fetch_page(pages.len()).await.map_err(RetryError::transient)? {
+    ///             pages.push(page);
+    ///         }
+    ///         Ok(())
+    ///     },
+    /// )
+    /// .await;
+    /// # result
+    /// # }
+    /// ```
+    pub async fn spawn_with_state<T, S, F, R, E>(
+        strategy: T,
+        state: S,
+        action: F,
+    ) -> (S, Result<R, E>)
+    where
+        T: IntoIterator<Item = Duration>,
+        F: AsyncFnMut(&mut S) -> Result<R, RetryError<E>>,
+    {
+        let lent = Lent::new((action, state));
+        let result = Retry::spawn(strategy, || async {
+            let (mut action, mut state) = lent.take();
+            let result = action(&mut state).await;
+            lent.give_back((action, state));
+            result
+        })
+        .await;
+        let (_, state) = lent.into_inner();
+        (state, result)
+    }
+
+    /// Retries an async closure, which unlike an [`Action`] may borrow from the enclosing
+    /// scope. See also the [`retry!`](crate::retry!) macro.
+    ///
+    /// Attempts run through [`Retry::spawn`], with the same delays and `retry_after`
+    /// handling. The future is only `Send`, e.g. for `tokio::spawn`, if the closure doesn't
+    /// borrow from its scope: the compiler can't yet prove the futures of borrowing async
+    /// closures `Send` for every lifetime. Use an [`Action`] owning its state otherwise.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # struct Client;
+    /// # impl Client { async fn get(&self, url: &str) -> Result<u64, std::io::Error> { Ok(0) } }
+    /// # async fn run(client: Client) -> Result<u64, std::io::Error> {
+    /// let url = String::from("https://example.com");
+    /// Retry::spawn_async(FixedInterval::from_millis(10).take(3), async || {
+    ///     client.get(&url).await.map_err(RetryError::transient)
+    /// })
+    /// .await
+    /// # }
+    /// ```
+    pub async fn spawn_async<T, F, R, E>(strategy: T, action: F) -> Result<R, E>
+    where
+        T: IntoIterator<Item = Duration>,
+        F: AsyncFnMut() -> Result<R, RetryError<E>>,
+    {
+        let lent = Lent::new(action);
+        Retry::spawn(strategy, || async {
+            let mut action = lent.take();
+            let result = action().await;
+            lent.give_back(action);
+            result
+        })
+        .await
+    }
+
+    /// Retries a [`BorrowingAction`], whose attempt futures borrow from the action instead of
+    /// owning clones of its state.
+    ///
+    /// The action is borrowed for the whole retry, so it is still available afterwards, e.g.
+    /// to reuse its buffers. Attempts run through [`Retry::spawn`].
+    pub async fn spawn_borrowing<T, B>(strategy: T, action: &mut B) -> Result<B::Item, B::Error>
+    where
+        T: IntoIterator<Item = Duration>,
+        B: BorrowingAction + ?Sized,
+    {
+        let lent = Lent::new(action);
+        Retry::spawn(strategy, || async {
+            let action = lent.take();
+            let result = action.run_borrowed().await;
+            lent.give_back(action);
+            result
+        })
+        .await
+    }
+}
+
+/// Value lent to the attempt running, for attempt futures borrowing from their action or state,
+/// which an [`Action`] can't express.
+struct Lent<T>(Mutex<Option<T>>);
+
+impl<T> Lent<T> {
+    const fn new(value: T) -> Self {
+        Lent(Mutex::new(Some(value)))
+    }
+
+    fn take(&self) -> T {
+        self.slot()
+            .take()
+            .expect("attempts of a retry run one at a time")
+    }
+
+    fn give_back(&self, value: T) {
+        *self.slot() = Some(value);
+    }
+
+    fn into_inner(self) -> T {
+        self.0
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .expect("the last attempt gives the value back")
+    }
+
+    fn slot(&self) -> std::sync::MutexGuard<'_, Option<T>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Retries `action` according to `strategy`, same as [`Retry::spawn`].
+///
+/// Nothing is spawned: attempts run while the returned future is polled.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn connect() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// retry(FixedInterval::from_millis(10).take(3), || async {
+///     connect().await.map_err(RetryError::transient)
+/// })
+/// .await
+/// # }
+/// ```
+pub fn retry<T, A>(strategy: T, action: A) -> Retry<T::IntoIter, A>
+where
+    T: IntoIterator<Item = Duration>,
+    A: Action,
+{
+    Retry::spawn(strategy, action)
+}
+
+/// Retries `action` according to `strategy` while `condition` holds, same as
+/// [`RetryIf::spawn`] without a notify hook.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{retry_if, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn connect() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let action = || async { connect().await.map_err(RetryError::transient) };
+/// retry_if(FixedInterval::from_millis(10).take(3), action, |err: &std::io::Error| {
+///     err.kind() != std::io::ErrorKind::NotFound
+/// })
+/// .await
+/// # }
+/// ```
+pub fn retry_if<T, A, C>(
+    strategy: T,
+    action: A,
+    condition: C,
+) -> RetryIf<T::IntoIter, A, C, fn(&A::Error, std::time::Duration)>
+where
+    T: IntoIterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+{
+    RetryIf::spawn(
+        strategy,
+        action,
+        condition,
+        (|_, _| {}) as fn(&A::Error, std::time::Duration),
+    )
+}
+
+/// Retries a closure returning a plain `Result<T, E>`, same as [`Retry::spawn_any`].
+pub fn retry_any<S, F, Fut, T, E>(strategy: S, action: F) -> Retry<S::IntoIter, AnyError<F>>
+where
+    S: IntoIterator<Item = Duration>,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    Retry::spawn_any(strategy, action)
+}
+
+/// Retries `action` with a clone of `input` on every attempt, same as [`Retry::spawn_with`].
+pub fn retry_with<S, In, A>(
+    strategy: S,
+    input: In,
+    action: A,
+) -> Retry<S::IntoIter, WithInput<In, A>>
+where
+    S: IntoIterator<Item = Duration>,
+    In: Clone,
+    A: ActionWith<In>,
+{
+    Retry::spawn_with(strategy, input, action)
+}
+
+/// Retries `action` as long as `handler` decides to, same as [`Retry::spawn_handled`].
+pub fn retry_handled<A, H>(
+    action: A,
+    handler: H,
+) -> Retry<std::iter::Repeat<Duration>, Handled<A, H>>
+where
+    A: Action,
+    H: ErrorHandler<A::Error>,
+{
+    Retry::spawn_handled(action, handler)
+}
+
+/// Retries `action` with exclusive access to `state`, same as [`Retry::spawn_with_state`].
+pub async fn retry_with_state<T, S, F, R, E>(strategy: T, state: S, action: F) -> (S, Result<R, E>)
+where
+    T: IntoIterator<Item = Duration>,
+    F: AsyncFnMut(&mut S) -> Result<R, RetryError<E>>,
+{
+    Retry::spawn_with_state(strategy, state, action).await
+}
+
+/// Retries an async closure borrowing from its scope, same as [`Retry::spawn_async`].
+pub async fn retry_async<T, F, R, E>(strategy: T, action: F) -> Result<R, E>
+where
+    T: IntoIterator<Item = Duration>,
+    F: AsyncFnMut() -> Result<R, RetryError<E>>,
+{
+    Retry::spawn_async(strategy, action).await
+}
+
+/// Retries a [`BorrowingAction`], same as [`Retry::spawn_borrowing`].
+pub async fn retry_borrowing<T, B>(strategy: T, action: &mut B) -> Result<B::Item, B::Error>
+where
+    T: IntoIterator<Item = Duration>,
+    B: BorrowingAction + ?Sized,
+{
+    Retry::spawn_borrowing(strategy, action).await
+}
+
+impl<I, A> fmt::Debug for Retry<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Retry")
+            .field("retry_if", &self.retry_if)
+            .finish()
+    }
+}
+
 impl<I, A> Future for Retry<I, A>
 where
     I: Iterator<Item = Duration>,
@@ -101,6 +785,44 @@ where
     }
 }
 
+#[cfg(feature = "fused")]
+impl<I, A> futures_core::FusedFuture for Retry<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    fn is_terminated(&self) -> bool {
+        self.retry_if.is_terminated()
+    }
+}
+
+/// Span of a retry future, created under the current span.
+#[cfg(all(feature = "tracing", not(feature = "otel")))]
+fn retry_span() -> tracing::Span {
+    tracing::info_span!(
+        "retry",
+        attempt = 1,
+        delay_ms = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+        error = tracing::field::Empty,
+    )
+}
+
+/// Span of a retry future, created under the current span, with the status fields recognized
+/// by `tracing-opentelemetry`.
+#[cfg(feature = "otel")]
+fn retry_span() -> tracing::Span {
+    tracing::info_span!(
+        "retry",
+        attempt = 1,
+        delay_ms = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+        error = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+        otel.status_message = tracing::field::Empty,
+    )
+}
+
 /// Future that drives multiple attempts at an action via a retry strategy. Retries are only attempted if
 /// the `Error` returned by the future satisfies a given condition.
 #[pin_project]
@@ -111,13 +833,100 @@ where
     C: Condition<A::Error>,
     N: Notify<A::Error>,
 {
-    strategy: I,
+    // `None` once handed back by `take_parts`
+    parts: Option<RetryParts<I, A, C, N>>,
     #[pin]
     state: RetryState<A>,
-    action: A,
-    condition: C,
-    duration: Duration,
-    notify: N,
+    // reset between attempts rather than recreated, to reuse the timer entry
+    #[pin]
+    sleep: Option<Sleep>,
+    time: Time,
+    decider: Decider,
+    attempts: usize,
+    started: Instant,
+    last_error: Option<A::Error>,
+    retry_after: Option<Duration>,
+    attempt_timer: Option<AttemptTimer<A::Error>>,
+    max_elapsed: Option<Duration>,
+    history: Option<Vec<A::Error>>,
+    planned_delay: Option<Duration>,
+    shutting_down: bool,
+    sleep_started: Option<Instant>,
+    slept: Duration,
+    attempt_started: Instant,
+    attempt_duration: Duration,
+    attempt_times: Vec<Instant>,
+    stop_reason: StopReason,
+    terminated: bool,
+    stats: OnceLock<Arc<RetryStats>>,
+    handle: OnceLock<RetryHandle>,
+    paused_remaining: Option<Duration>,
+    budget: Option<RetryBudget>,
+    breaker: Option<CircuitBreaker>,
+    permit: Option<BreakerPermit>,
+    rate_limiter: Option<RateLimiter>,
+    // the rate limiter slot of the next attempt is reserved, it's waiting for it
+    rate_slot: bool,
+    // the first attempt created by `spawn` waits for its rate limiter slot
+    first_slot: bool,
+    // behind a mutex to keep the retry future `Sync`, it's never contended
+    wake_on: Option<Mutex<WakeOn>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<RetryMetrics>,
+    #[cfg(feature = "events")]
+    events: Option<EventSender<A::Error>>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    trace_error: Option<fn(&tracing::Span, &A::Error)>,
+}
+
+/// Strategy, action, condition and notify hook of a retry future, handed back by
+/// [`RetryIf::into_parts`] and [`RetryIf::take_parts`] to reuse them.
+///
+/// The strategy keeps its position: it yields the delays the retry didn't use.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RetryParts<I, A, C, N> {
+    /// The strategy, advanced past the delays already used.
+    pub strategy: I,
+    /// The action, with whatever state its attempts left in it.
+    pub action: A,
+    /// The condition deciding which errors are retried.
+    pub condition: C,
+    /// The hook notified of every retry.
+    pub notify: N,
+}
+
+fn parts<I, A, C, N>(parts: &mut Option<RetryParts<I, A, C, N>>) -> &mut RetryParts<I, A, C, N> {
+    parts
+        .as_mut()
+        .expect("a retry future is never polled after handing back its parts")
+}
+
+type TriggerFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Cuts the sleeps between attempts short, see [`RetryIf::wake_on`].
+struct WakeOn {
+    trigger: Box<dyn FnMut() -> TriggerFuture + Send>,
+    // the trigger of the current sleep, created once it starts
+    pending: Option<TriggerFuture>,
+}
+
+/// Bounds the duration of each attempt, see [`RetryIf::with_attempt_timeout`].
+struct AttemptTimer<E> {
+    timeout: Duration,
+    sleep: Pin<Box<Sleep>>,
+    error: fn() -> E,
+}
+
+impl<E> AttemptTimer<E> {
+    fn poll(&mut self, cx: &mut Context) -> Poll<E> {
+        match self.sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready((self.error)()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
 }
 
 impl<I, A, C, N> RetryIf<I, A, C, N>
@@ -133,53 +942,1206 @@ where
         condition: C,
         notify: N,
     ) -> RetryIf<I, A, C, N> {
-        RetryIf {
-            strategy: strategy.into_iter(),
-            state: RetryState::Running(action.run()),
+        let first = action.run_with(&AttemptContext::new(1, None, Duration::ZERO));
+        RetryIf::new(
+            strategy.into_iter(),
             action,
             condition,
-            duration: Duration::from_millis(0),
             notify,
-        }
+            RetryState::Running(first),
+            Time::default(),
+        )
     }
 
-    fn attempt(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
-        let future = {
-            let mut this = self.as_mut().project();
-            this.action.run()
-        };
-        self.as_mut()
-            .project()
-            .state
-            .set(RetryState::Running(future));
-        self.poll(cx)
+    /// Same as [`RetryIf::spawn`], running the retry as a tokio task, see
+    /// [`RetryIf::detach`].
+    #[cfg(feature = "detach")]
+    pub fn spawn_detached<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+    ) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        RetryIf::spawn(strategy, action, condition, notify).detach()
     }
 
-    fn retry(
-        mut self: Pin<&mut Self>,
-        err: A::Error,
-        cx: &mut Context,
-    ) -> Result<Poll<Result<A::Item, A::Error>>, A::Error> {
-        match self.as_mut().project().strategy.next() {
-            None => {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("ending retry: strategy reached its limit");
-                Err(err)
-            }
-            Some(duration) => {
-                *self.as_mut().project().duration += duration;
-                let deadline = Instant::now() + duration;
-                let future = sleep_until(deadline);
-                self.as_mut()
-                    .project()
-                    .state
-                    .set(RetryState::Sleeping(future));
-                Ok(self.poll(cx))
-            }
+    /// Same as [`RetryIf::spawn`], sleeping `delay` before the first attempt, e.g. when
+    /// reacting to a disconnect where an immediate attempt is bound to fail.
+    ///
+    /// The action isn't called before the delay has passed. The initial sleep counts towards
+    /// [`RetryIf::total_slept`], and can be paused or cut short through the [`RetryHandle`]
+    /// like the sleeps between attempts.
+    pub fn spawn_after<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        delay: Duration,
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+    ) -> RetryIf<I, A, C, N> {
+        RetryIf::delayed(
+            delay,
+            strategy.into_iter(),
+            action,
+            condition,
+            notify,
+            Time::default(),
+        )
+    }
+
+    /// Creates the retry future sleeping `delay` with `time` before its first attempt.
+    pub(crate) fn delayed(
+        delay: Duration,
+        strategy: I,
+        action: A,
+        condition: C,
+        notify: N,
+        time: Time,
+    ) -> RetryIf<I, A, C, N> {
+        let mut retry_if = RetryIf::new(
+            strategy,
+            action,
+            condition,
+            notify,
+            RetryState::Sleeping,
+            time,
+        );
+        retry_if.planned_delay = Some(delay);
+        retry_if.sleep_started = Some(retry_if.started);
+        retry_if.sleep = Some(retry_if.time.sleep_until(retry_if.time.after(delay)));
+        retry_if
+    }
+
+    fn new(
+        strategy: I,
+        action: A,
+        condition: C,
+        notify: N,
+        state: RetryState<A>,
+        time: Time,
+    ) -> RetryIf<I, A, C, N> {
+        // the first attempt only counts once it has started
+        let attempts = match state {
+            RetryState::Running(_) => 1,
+            _ => 0,
+        };
+        let started = time.now();
+        RetryIf {
+            parts: Some(RetryParts {
+                strategy,
+                action,
+                condition,
+                notify,
+            }),
+            state,
+            sleep: None,
+            started,
+            time,
+            decider: Decider::default(),
+            attempts,
+            last_error: None,
+            retry_after: None,
+            attempt_timer: None,
+            max_elapsed: None,
+            history: None,
+            planned_delay: None,
+            shutting_down: false,
+            sleep_started: None,
+            slept: Duration::ZERO,
+            attempt_started: started,
+            attempt_duration: Duration::ZERO,
+            attempt_times: Vec::new(),
+            stop_reason: StopReason::Exhausted,
+            terminated: false,
+            stats: OnceLock::new(),
+            handle: OnceLock::new(),
+            paused_remaining: None,
+            budget: None,
+            breaker: None,
+            permit: None,
+            rate_limiter: None,
+            rate_slot: false,
+            first_slot: false,
+            wake_on: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+            #[cfg(feature = "events")]
+            events: None,
+            #[cfg(feature = "tracing")]
+            span: retry_span(),
+            #[cfg(feature = "tracing")]
+            trace_error: None,
+        }
+    }
+
+    /// Measures time and sleeps with `clock` instead of the runtime timer and the system
+    /// clock, e.g. a [`MockClock`](crate::test_util::MockClock) in tests.
+    ///
+    /// Set it before the other options: the retry restarts at the current instant of `clock`,
+    /// and sleeps or deadlines set earlier keep using the runtime timer.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        let time = Time::new(Arc::new(clock));
+        self.started = time.now();
+        self.time = time;
+        self
+    }
+
+    /// Retries as soon as a future returned by `trigger` resolves rather than once the delay
+    /// elapsed, e.g. once a `watch` channel reports that connectivity came back.
+    ///
+    /// Every sleep between attempts calls `trigger` for a fresh future and ends at the
+    /// earliest of the future and the delay, so the delays of the strategy and `retry_after`
+    /// become the longest waits. Pick a strategy with long delays to wait for the trigger
+    /// alone. A trigger resolving right away retries without waiting at all, so wait for a
+    /// change rather than for a state that may already hold. The wait for a slot of the
+    /// [`RateLimiter`] of the retry isn't cut short.
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn sync() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+    /// # async fn run(online: tokio::sync::watch::Receiver<bool>) -> Result<(), std::io::Error> {
+    /// Retry::spawn(FixedInterval::new(Duration::from_secs(300)), sync)
+    ///     .wake_on(move || {
+    ///         let mut online = online.clone();
+    ///         async move {
+    ///             let _ = online.changed().await;
+    ///             let _ = online.wait_for(|online| *online).await;
+    ///         }
+    ///     })
+    ///     .await
+    /// # }
+    /// ```
+    pub fn wake_on<F, Fut>(mut self, mut trigger: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.wake_on = Some(Mutex::new(WakeOn {
+            trigger: Box::new(move || Box::pin(trigger())),
+            pending: None,
+        }));
+        self
+    }
+
+    /// Gates every attempt through a shared [`RateLimiter`], delaying attempts that would
+    /// exceed its rate, on top of the strategy delay.
+    ///
+    /// The slot of an attempt is reserved once it's due, when the future is polled.
+    pub fn with_rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        // the first attempt created by `spawn` isn't polled before its slot
+        self.first_slot = matches!(self.state, RetryState::Running(_));
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Registers the retry with a shared [`CircuitBreaker`], recording the outcome of every
+    /// attempt and stopping with the last error once the circuit refuses a retry.
+    ///
+    /// The returned future resolves with [`RetryFailure::CircuitOpen`] without polling the
+    /// first attempt if the circuit refuses it.
+    pub fn with_circuit_breaker(self, breaker: CircuitBreaker) -> Guarded<I, A, C, N> {
+        Guarded::new(self).with_circuit_breaker(breaker)
+    }
+
+    /// Draws every retry from a shared [`RetryBudget`], giving up with the last error once it
+    /// is empty. Successful attempts refill the budget.
+    pub fn with_budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Emits [`RetryMetrics`] through the `metrics` facade while this retry future runs:
+    /// attempts, retries, give-ups, the delay before each retry and the total duration.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: RetryMetrics) -> Self {
+        // the first attempt was created by `spawn`
+        metrics.record_attempts(self.attempts);
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Returns a channel receiving the [`RetryEvent`]s of this retry future: every attempt
+    /// started and failed, every sleep, the success or giving up.
+    ///
+    /// The channel buffers up to `capacity` events. The retry never waits for the receiver:
+    /// events are dropped while the channel is full. Calling it again replaces the channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    #[cfg(feature = "events")]
+    pub fn events(&mut self, capacity: usize) -> mpsc::Receiver<RetryEvent<A::Error>>
+    where
+        A::Error: Clone,
+    {
+        let (sender, receiver) = EventSender::channel(capacity);
+        // the first attempt was created by `spawn`
+        if let RetryState::Running(_) = self.state {
+            sender.send(RetryEvent::AttemptStarted {
+                attempt: self.attempts,
+            });
+        }
+        self.events = Some(sender);
+        receiver
+    }
+
+    /// Records the `Debug` representation of the last transient error in the `error` field of
+    /// the `retry` span, so it shows up with the retry events.
+    #[cfg(feature = "tracing")]
+    pub fn trace_errors(mut self) -> Self
+    where
+        A::Error: fmt::Debug,
+    {
+        self.trace_error = Some(|span, err| {
+            span.record("error", tracing::field::debug(err));
+        });
+        self
+    }
+
+    /// Replaces the `retry` span the retry future runs in.
+    ///
+    /// The span is entered on every poll, so attempts started after a sleep still run in it and
+    /// its context, like an OpenTelemetry trace, carries over. Declare the `attempt`,
+    /// `delay_ms`, `elapsed_ms` and `error` fields as [`tracing::field::Empty`] to have them
+    /// recorded, as well as `otel.status_code` and `otel.status_message` with the `otel`
+    /// feature.
+    #[cfg(feature = "tracing")]
+    pub fn with_span(mut self, span: tracing::Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Returns a [`RetryHandle`] to pause and resume this retry future.
+    pub fn handle(&self) -> RetryHandle {
+        self.handle.get_or_init(RetryHandle::default).clone()
+    }
+
+    /// Spawns this retry onto the tokio runtime, for retried side effects nobody waits for,
+    /// like audit writes.
+    ///
+    /// The returned [`DetachedRetry`] can be awaited for the outcome, aborted, or dropped to
+    /// let the retry finish in the background.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::ExponentialBackoff;
+    /// # async fn write_audit_log() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+    /// # async fn run() {
+    /// let strategy = ExponentialBackoff::from_millis(10).take(5);
+    /// Retry::spawn(strategy, write_audit_log).detach_named("audit-log");
+    /// # }
+    /// ```
+    #[cfg(feature = "detach")]
+    pub fn detach(self) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        let (handle, stats) = (self.handle(), self.stats());
+        DetachedRetry::spawn(self, None, handle, stats)
+    }
+
+    /// Same as [`RetryIf::detach`], naming the task `name` in tokio-console for binaries
+    /// built with `--cfg tokio_unstable` and the `detach-names` feature, which enables the
+    /// `tracing` feature of tokio.
+    #[cfg(feature = "detach")]
+    pub fn detach_named(self, name: &str) -> DetachedRetry<A::Item, A::Error>
+    where
+        Self: Send + 'static,
+        A::Item: Send + 'static,
+        A::Error: Send + 'static,
+    {
+        let (handle, stats) = (self.handle(), self.stats());
+        DetachedRetry::spawn(self, Some(name), handle, stats)
+    }
+
+    /// Retries a fallback action, like a mirror region, with its own strategy once this retry
+    /// failed. The returned future fails with a [`FallbackError`](crate::FallbackError)
+    /// carrying the errors of both actions.
+    ///
+    /// The fallback action only runs once this retry failed.
+    pub fn or_else_retry<T, F>(
+        self,
+        strategy: T,
+        action: F,
+    ) -> Fallback<Self, T::IntoIter, F, A::Error>
+    where
+        T: IntoIterator<Item = Duration>,
+        F: Action<Item = A::Item>,
+    {
+        Fallback::new(self, strategy.into_iter(), action)
+    }
+
+    /// Returns a handle to the live [`RetryStats`] of this retry future, updated while it runs.
+    pub fn stats(&self) -> Arc<RetryStats> {
+        self.stats
+            .get_or_init(|| Arc::new(RetryStats::new(self.started, self.attempts)))
+            .clone()
+    }
+
+    /// Number of attempts started so far, including a running one.
+    pub const fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Returns `true` once the retry future completed, polling it afterwards returns
+    /// `Poll::Pending` without running the action again.
+    pub const fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Cancels the retry and hands back its strategy, action, condition and notify hook, so an
+    /// expensive action, like one owning a client, can be reused. The running attempt or
+    /// sleep, if any, is dropped.
+    ///
+    /// Pinned futures are polled in place and can't be moved anymore, use
+    /// [`RetryIf::take_parts`] for them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parts were already taken with [`RetryIf::take_parts`].
+    pub fn into_parts(mut self) -> RetryParts<I, A, C, N> {
+        self.parts
+            .take()
+            .expect("the parts of the retry future were already taken")
+    }
+
+    /// Hands back the strategy, action, condition and notify hook of a pinned retry future,
+    /// typically once it resolved, e.g. to reuse its action for the next operation.
+    ///
+    /// Taking the parts of a running retry cancels it: it then stays pending without running
+    /// the action again. Returns `None` if the parts were already taken.
+    ///
+    /// ```rust
+    /// # use std::pin::pin;
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn run() {
+    /// let retry = Retry::spawn(FixedInterval::from_millis(10).take(3), || async {
+    ///     Ok::<_, RetryError<()>>(42)
+    /// });
+    /// let mut retry = pin!(retry);
+    /// assert_eq!(retry.as_mut().await, Ok(42));
+    ///
+    /// let parts = retry.take_parts().unwrap();
+    /// assert_eq!(parts.strategy.count(), 3);
+    /// # }
+    /// ```
+    pub fn take_parts(self: Pin<&mut Self>) -> Option<RetryParts<I, A, C, N>> {
+        let this = self.project();
+        *this.terminated = true;
+        this.parts.take()
+    }
+
+    /// What the retry future is doing.
+    pub const fn phase(&self) -> RetryPhase {
+        self.state.phase()
+    }
+
+    /// Sum of the delays scheduled so far, including a running sleep. This is the duration
+    /// passed to [`Notify::notify`] when no `retry_after` is requested.
+    pub const fn duration(&self) -> Duration {
+        self.decider.duration()
+    }
+
+    /// Time since the retry future was created.
+    pub fn elapsed(&self) -> Duration {
+        self.time.elapsed(self.started)
+    }
+
+    /// Instant the next attempt starts at, while sleeping before it.
+    pub fn next_attempt_at(&self) -> Option<Instant> {
+        match (&self.state, &self.sleep) {
+            (RetryState::Sleeping, Some(sleep)) => Some(sleep.deadline()),
+            _ => None,
+        }
+    }
+
+    /// Time spent sleeping between attempts so far, excluding a running sleep.
+    pub const fn total_slept(&self) -> Duration {
+        self.slept
+    }
+
+    /// Delay before the next attempt: the one being notified or slept, else the next delay
+    /// of the strategy if the running attempt fails, ignoring `retry_after` and limits.
+    pub fn next_planned_delay(&self) -> Option<Duration>
+    where
+        I: Clone,
+    {
+        self.planned_delay.or_else(|| {
+            let parts = self.parts.as_ref()?;
+            parts.strategy.clone().next()
+        })
+    }
+
+    /// Bounds the `retry_after` delay requested by transient errors to `min..=max`, so that
+    /// a misbehaving upstream can't park the retry future for too long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn clamp_retry_after(mut self, min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "`min` must not be greater than `max`");
+        self.decider.retry_after_bounds = Some((min, max));
+        self
+    }
+
+    /// Randomizes the `retry_after` delay requested by transient errors with `jitter`, so
+    /// that clients told to come back after the same delay don't all come back at once.
+    ///
+    /// Strategy delays aren't affected, and [`RetryIf::clamp_retry_after`] bounds the
+    /// randomized delay. As `retry_after` is usually the least the server wants us to wait,
+    /// prefer a jitter which only lengthens the delay, like `jitter_range(1.0, 1.2)` for
+    /// +0-20% or [`Additive`](crate::strategy::Additive).
+    #[cfg(feature = "jitter")]
+    pub fn jitter_retry_after<J>(mut self, jitter: J) -> Self
+    where
+        J: Jitter + Send + Sync + 'static,
+    {
+        self.decider.retry_after_jitter = Some(RetryAfterJitter(Arc::new(jitter)));
+        self
+    }
+
+    #[cfg(feature = "jitter")]
+    pub(crate) fn with_retry_after_jitter(mut self, jitter: Option<RetryAfterJitter>) -> Self {
+        self.decider.retry_after_jitter = jitter;
+        self
+    }
+
+    /// Same as [`RetryIf::spawn`], failing with a [`RetryReport`] carrying the number of
+    /// attempts, the elapsed time and the error of every attempt, see [`RetryIf::with_report`].
+    pub fn spawn_with_report<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+    ) -> Reported<I, A, C, N> {
+        RetryIf::spawn(strategy, action, condition, notify).with_report()
+    }
+
+    /// Keeps the error of every failed attempt, failing with a [`RetryReport`] instead of
+    /// the last error only.
+    pub fn with_report(mut self) -> Reported<I, A, C, N> {
+        self.history = Some(Vec::new());
+        Reported { retry_if: self }
+    }
+
+    /// Same as [`RetryIf::spawn`], succeeding with a [`Retried`] carrying the number of
+    /// attempts and the elapsed time, see [`RetryIf::reporting`].
+    pub fn spawn_reporting<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+    ) -> Reporting<I, A, C, N> {
+        RetryIf::spawn(strategy, action, condition, notify).reporting()
+    }
+
+    /// Succeeds with a [`Retried`] instead of the bare value, so the number of attempts a
+    /// success took can be recorded at the call site.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{Retry, RetryError};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn fetch() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+    /// # async fn run() -> Result<(), std::io::Error> {
+    /// let retried = Retry::spawn_reporting(FixedInterval::from_millis(10).take(3), fetch).await?;
+    /// if retried.retried {
+    ///     println!("succeeded after {} attempts", retried.attempts);
+    /// }
+    /// let value = retried.value;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reporting(self) -> Reporting<I, A, C, N> {
+        Reporting { retry_if: self }
+    }
+
+    /// Hands the [`RetryReport`] of the retry to `hook` once it gives up, e.g. to enqueue the
+    /// operation for manual inspection, and fails with what `hook` returns.
+    ///
+    /// The report holds every error, the instant every attempt started at and why retrying
+    /// stopped. The hook identifies the operation through what it captures.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{RetryError, RetryIf, RetryReport};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// # async fn deliver(order: u64) -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+    /// # fn dead_letter(order: u64, report: &RetryReport<std::io::Error>) {}
+    /// # async fn run(order: u64) -> Result<(), std::io::Error> {
+    /// let strategy = FixedInterval::from_millis(100).take(3);
+    /// RetryIf::spawn(strategy, || deliver(order), |_: &std::io::Error| true, |_: &std::io::Error, _| {})
+    ///     .on_dead_letter(|report| {
+    ///         dead_letter(order, &report);
+    ///         report.into_error()
+    ///     })
+    ///     .await
+    /// # }
+    /// ```
+    pub fn on_dead_letter<F, R>(self, hook: F) -> DeadLetter<I, A, C, N, F>
+    where
+        F: FnOnce(RetryReport<A::Error>) -> R,
+    {
+        DeadLetter {
+            reported: self.with_report(),
+            hook: Some(hook),
+        }
+    }
+
+    /// Converts the error the retry gives up with, with the number of attempts, the elapsed
+    /// time and why it stopped, e.g. into a `RetriesExhausted` variant of the caller's error.
+    ///
+    /// Unlike [`RetryIf::on_dead_letter`], the errors of the previous attempts aren't kept.
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{GiveUpContext, Retry, RetryError, StopReason};
+    /// # use tokio_retry2::strategy::FixedInterval;
+    /// #[derive(Debug)]
+    /// enum FetchError {
+    ///     Failed(std::io::Error),
+    ///     RetriesExhausted { attempts: usize, source: std::io::Error },
+    /// }
+    ///
+    /// # async fn fetch() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+    /// # async fn run() -> Result<u64, FetchError> {
+    /// Retry::spawn(FixedInterval::from_millis(100).take(3), fetch)
+    ///     .map_give_up(|source, context: GiveUpContext| match context.reason {
+    ///         StopReason::Exhausted => FetchError::RetriesExhausted {
+    ///             attempts: context.attempts,
+    ///             source,
+    ///         },
+    ///         _ => FetchError::Failed(source),
+    ///     })
+    ///     .await
+    /// # }
+    /// ```
+    pub fn map_give_up<F, R>(self, f: F) -> GiveUp<I, A, C, N, F>
+    where
+        F: FnOnce(A::Error, GiveUpContext) -> R,
+    {
+        GiveUp {
+            retry_if: self,
+            f: Some(f),
+        }
+    }
+
+    /// Stops scheduling retries once `max_elapsed` has passed since the retry future was created,
+    /// resolving with the error of the last attempt.
+    ///
+    /// Unlike [`RetryIf::with_deadline`], running attempts and sleeps are not interrupted.
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Same as [`RetryIf::spawn`], bounding each attempt by `attempt_timeout`,
+    /// see [`RetryIf::with_attempt_timeout`].
+    pub fn spawn_with_attempt_timeout<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+        attempt_timeout: Duration,
+    ) -> RetryIf<I, A, C, N>
+    where
+        A::Error: From<AttemptTimeout>,
+    {
+        RetryIf::spawn(strategy, action, condition, notify).with_attempt_timeout(attempt_timeout)
+    }
+
+    /// Bounds each individual attempt by `attempt_timeout`.
+    ///
+    /// An attempt still running after `attempt_timeout` is dropped and treated as a transient
+    /// error, converted from [`AttemptTimeout`], which goes through the condition and the
+    /// strategy like any other transient error.
+    pub fn with_attempt_timeout(mut self, attempt_timeout: Duration) -> Self
+    where
+        A::Error: From<AttemptTimeout>,
+    {
+        // the first attempt was created by `spawn`
+        let deadline = deadline_after(self.started, attempt_timeout);
+        self.attempt_timer = Some(AttemptTimer {
+            timeout: attempt_timeout,
+            sleep: Box::pin(self.time.sleep_until(deadline)),
+            error: || A::Error::from(AttemptTimeout),
+        });
+        self
+    }
+
+    /// Bounds the whole retry operation by a deadline.
+    ///
+    /// Once the deadline passes, the returned future resolves with
+    /// [`RetryFailure::DeadlineExceeded`] carrying the last error of the action, if any,
+    /// even if it is currently running an attempt or sleeping between attempts.
+    pub fn with_deadline(self, deadline: Instant) -> Guarded<I, A, C, N> {
+        Guarded::new(self).with_deadline(deadline)
+    }
+
+    /// Stops the retry operation once `token` is cancelled.
+    ///
+    /// The returned future resolves with [`RetryFailure::Cancelled`] carrying the last error
+    /// of the action, if any, as soon as the token fires, dropping a running attempt or
+    /// interrupting the sleep between attempts.
+    #[cfg(feature = "cancellation")]
+    pub fn with_cancellation(self, token: CancellationToken) -> Guarded<I, A, C, N> {
+        Guarded::new(self).with_cancellation(token)
+    }
+
+    /// Stops retrying once `shutdown` resolves, e.g. when a service starts draining.
+    ///
+    /// The returned future resolves with [`RetryFailure::ShutDown`] carrying the last error of
+    /// the action, if any, instead of sleeping before another attempt. A running attempt is
+    /// allowed to finish by default, see [`Guarded::on_shutdown`].
+    ///
+    /// ```rust,no_run
+    /// # use tokio_retry2::{RetryError, RetryIf};
+    /// # use tokio_retry2::strategy::ExponentialBackoff;
+    /// # async fn deliver() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+    /// # async fn run(shutdown: tokio::sync::oneshot::Receiver<()>) {
+    /// let strategy = ExponentialBackoff::from_millis(100).take(10);
+    /// let result = RetryIf::spawn(strategy, deliver, |_: &std::io::Error| true, |_: &std::io::Error, _| {})
+    ///     .until(async move {
+    ///         let _ = shutdown.await;
+    ///     })
+    ///     .await;
+    /// if result.as_ref().is_err_and(|failure| failure.is_shut_down()) {
+    ///     // hand the delivery over to the next instance
+    /// }
+    /// # }
+    /// ```
+    pub fn until(self, shutdown: impl Future<Output = ()> + Send + 'static) -> Guarded<I, A, C, N> {
+        Guarded::new(self).until(shutdown)
+    }
+
+    /// Ends the retry from its [`Guarded`] wrapper, failing the circuit breaker probe it holds
+    /// and returning the last error.
+    fn abandon(self: Pin<&mut Self>) -> Option<A::Error> {
+        let this = self.project();
+        if let Some(permit) = this.permit.take() {
+            permit.record(false);
+        }
+        *this.terminated = true;
+        this.last_error.take()
+    }
+
+    /// Stops scheduling retries, see [`RetryIf::until`].
+    fn shut_down(self: Pin<&mut Self>) {
+        *self.project().shutting_down = true;
+    }
+
+    fn attempt(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
+        let future = {
+            let mut this = self.as_mut().project();
+            *this.attempts += 1;
+            *this.planned_delay = None;
+            if let Some(sleep_started) = this.sleep_started.take() {
+                *this.slept += this.time.elapsed(sleep_started);
+            }
+            *this.attempt_started = this.time.now();
+            if this.history.is_some() {
+                this.attempt_times.push(this.time.now());
+            }
+            if let Some(stats) = this.stats.get() {
+                stats.record_wake();
+                stats.record_attempt(*this.attempts);
+            }
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = this.metrics.as_ref() {
+                metrics.record_attempts(1);
+            }
+            #[cfg(feature = "events")]
+            if let Some(events) = this.events.as_ref() {
+                events.send(RetryEvent::AttemptStarted {
+                    attempt: *this.attempts,
+                });
+            }
+            #[cfg(feature = "tracing")]
+            this.span.record("attempt", *this.attempts);
+            if let Some(timer) = this.attempt_timer.as_mut() {
+                let deadline = this.time.after(timer.timeout);
+                timer.sleep.as_mut().reset(deadline);
+            }
+            let context = AttemptContext::new(
+                *this.attempts,
+                this.last_error.as_ref(),
+                this.time.elapsed(*this.started),
+            );
+            parts(this.parts).action.run_with(&context)
+        };
+        self.as_mut()
+            .project()
+            .state
+            .set(RetryState::Running(future));
+        self.poll_retry(cx)
+    }
+
+    /// Starts the next attempt once the rate limiter allows it, sleeping until its slot
+    /// otherwise.
+    fn admit(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
+        let this = self.as_mut().project();
+        if let (Some(rate_limiter), false) = (this.rate_limiter.as_ref(), *this.rate_slot) {
+            let now = this.time.now();
+            let start = rate_limiter.reserve_at(now);
+            if start > now {
+                *this.rate_slot = true;
+                if let Some(sleep) = this.sleep.as_pin_mut() {
+                    sleep.reset(start);
+                }
+                return self.poll_retry(cx);
+            }
+        }
+        *self.as_mut().project().rate_slot = false;
+        self.attempt(cx)
+    }
+
+    /// Holds the first attempt created by `spawn` back until the rate limiter lets it start,
+    /// returns `true` to hold.
+    fn hold_first(self: Pin<&mut Self>, cx: &mut Context) -> bool {
+        let mut this = self.project();
+        if !*this.first_slot {
+            return false;
+        }
+        if this.sleep.is_none() {
+            let now = this.time.now();
+            let start = match this.rate_limiter.as_ref() {
+                Some(rate_limiter) => rate_limiter.reserve_at(now),
+                None => now,
+            };
+            if start <= now {
+                *this.first_slot = false;
+                return false;
+            }
+            this.sleep.set(Some(this.time.sleep_until(start)));
+        }
+        let waiting = this.sleep.as_mut().as_pin_mut();
+        if waiting.is_some_and(|sleep| sleep.poll(cx).is_pending()) {
+            return true;
+        }
+        // the attempt only starts now
+        *this.first_slot = false;
+        *this.attempt_started = this.time.now();
+        if let Some(timer) = this.attempt_timer.as_mut() {
+            let deadline = this.time.after(timer.timeout);
+            timer.sleep.as_mut().reset(deadline);
+        }
+        false
+    }
+
+    /// Moves the sleep before the next attempt to `start`, or to now if `start` is past.
+    fn reschedule(self: Pin<&mut Self>, start: Instant) {
+        let this = self.project();
+        let start = cmp::max(start, this.time.now());
+        if let Some(sleep) = this.sleep.as_pin_mut() {
+            // the attempt can't start before its rate limiter slot
+            let start = match *this.rate_slot {
+                true => cmp::max(start, sleep.deadline()),
+                false => start,
+            };
+            sleep.reset(start);
+        }
+    }
+
+    /// Picks the delay before the next attempt and notifies about it, or gives up.
+    fn schedule(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
+        let this = self.as_mut().project();
+        if *this.shutting_down {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("ending retry: shutting down");
+            return self.exhaust(StopReason::ShutDown);
+        }
+        if let Some(max_elapsed) = *this.max_elapsed {
+            if this.time.elapsed(*this.started) >= max_elapsed {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("ending retry: `max_elapsed` reached");
+                return self.exhaust(StopReason::Exhausted);
+            }
+        }
+
+        let retry_after = this.retry_after.take();
+        let Some(next) = this
+            .decider
+            .next_delay(&mut parts(this.parts).strategy, retry_after)
+        else {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("ending retry: strategy reached its limit");
+            return self.exhaust(StopReason::Exhausted);
+        };
+
+        if let Some(breaker) = this.breaker.as_ref() {
+            match breaker.acquire() {
+                Some(permit) => *this.permit = Some(permit),
+                None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("ending retry: circuit breaker is open");
+                    return self.exhaust(StopReason::Exhausted);
+                }
+            }
+        }
+
+        if let Some(budget) = this.budget.as_ref() {
+            if !budget.withdraw() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("ending retry: retry budget is empty");
+                return self.exhaust(StopReason::Exhausted);
+            }
+        }
+
+        let mut this = self.as_mut().project();
+        let next_delay = next.delay;
+        *this.planned_delay = Some(next_delay);
+        let err = this
+            .last_error
+            .as_ref()
+            .expect("a failed attempt is stored while retrying");
+        let context = this.decider.notify(
+            &mut parts(this.parts).notify,
+            err,
+            next,
+            *this.attempts,
+            this.time.elapsed(*this.started),
+            *this.attempt_duration,
+        );
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = this.metrics.as_ref() {
+            metrics.record_retry(next_delay);
+        }
+        #[cfg(feature = "events")]
+        if let Some(events) = this.events.as_ref() {
+            events.failed(*this.attempts, err, Some(next_delay));
+        }
+        #[cfg(feature = "tracing")]
+        {
+            let (delay_ms, elapsed_ms) =
+                (next_delay.as_millis(), context.total_elapsed.as_millis());
+            this.span.record("delay_ms", delay_ms);
+            this.span.record("elapsed_ms", elapsed_ms);
+            tracing::info!(
+                attempt = *this.attempts,
+                delay_ms,
+                elapsed_ms,
+                attempt_ms = this.attempt_duration.as_millis(),
+                "retrying after transient error"
+            );
+        }
+        // whatever the notify hook, which can't log errors without a `Display` bound
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        ::log::info!(
+            target: "tokio_retry2",
+            "attempt {} failed, retrying in {:?}",
+            *this.attempts,
+            next_delay
+        );
+        this.state.set(RetryState::Notifying(next_delay));
+        self.poll_retry(cx)
+    }
+
+    fn sleep(
+        mut self: Pin<&mut Self>,
+        delay: Duration,
+        cx: &mut Context,
+    ) -> Poll<Result<A::Item, A::Error>> {
+        *self.as_mut().project().planned_delay = Some(delay);
+        *self.as_mut().project().sleep_started = Some(self.time.now());
+        let deadline = self.time.after(delay);
+        if let Some(stats) = self.stats.get() {
+            stats.record_sleep(delay);
+        }
+        #[cfg(feature = "events")]
+        if let Some(events) = self.events.as_ref() {
+            events.send(RetryEvent::Sleeping { delay });
+        }
+        let mut this = self.as_mut().project();
+        match this.sleep.as_mut().as_pin_mut() {
+            Some(sleep) => sleep.reset(deadline),
+            None => this.sleep.set(Some(this.time.sleep_until(deadline))),
+        }
+        this.state.set(RetryState::Sleeping);
+        self.poll_retry(cx)
+    }
+
+    /// Gives up on the last transient error.
+    fn exhaust(self: Pin<&mut Self>, reason: StopReason) -> Poll<Result<A::Item, A::Error>> {
+        let this = self.project();
+        *this.stop_reason = reason;
+        let err = this
+            .last_error
+            .take()
+            .expect("a failed attempt is stored before giving up");
+        let context = RetryContext::new(*this.attempts, this.time.elapsed(*this.started));
+        parts(this.parts).notify.exhausted(&err, &context);
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = this.metrics.as_ref() {
+            metrics.record_exhausted();
+            metrics.record_finished(context.elapsed);
+        }
+        #[cfg(feature = "events")]
+        if let Some(events) = this.events.as_ref() {
+            events.failed(*this.attempts, &err, None);
+            events.send(RetryEvent::Exhausted {
+                attempts: *this.attempts,
+                elapsed: context.elapsed,
+                reason,
+            });
+        }
+        #[cfg(feature = "otel")]
+        {
+            this.span.record("otel.status_code", "ERROR");
+            this.span.record("otel.status_message", "retries exhausted");
+        }
+        Poll::Ready(Err(err))
+    }
+
+    fn decide(
+        mut self: Pin<&mut Self>,
+        err: A::Error,
+        retry_after: Option<Duration>,
+        cx: &mut Context,
+    ) -> Poll<Result<A::Item, A::Error>> {
+        let mut this = self.as_mut().project();
+        *this.attempt_duration = this.time.elapsed(*this.attempt_started);
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = this.metrics.as_ref() {
+            metrics.record_failed_attempt(*this.attempt_duration);
+        }
+        if let Some(stats) = this.stats.get() {
+            stats.record_error();
+        }
+        if let Some(permit) = this.permit.take() {
+            permit.record(false);
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(trace_error) = *this.trace_error {
+            trace_error(this.span, &err);
+        }
+        if let Some(previous) = this.last_error.replace(err) {
+            if let Some(history) = this.history.as_mut() {
+                history.push(previous);
+            }
+        }
+        *this.retry_after = retry_after;
+        this.state.set(RetryState::Deciding);
+        self.poll_retry(cx)
+    }
+
+    /// Freezes the sleep before the next attempt while paused, returns `true` to hold.
+    fn hold(self: Pin<&mut Self>, cx: &mut Context) -> bool {
+        let this = self.project();
+        let Some(handle) = this.handle.get() else {
+            return false;
+        };
+        let (RetryStateProj::Sleeping, Some(sleep)) =
+            (this.state.project(), this.sleep.as_pin_mut())
+        else {
+            return false;
+        };
+        // woken by `pause` to freeze the sleep right away
+        handle.register(cx.waker());
+        if handle.is_paused() {
+            if this.paused_remaining.is_none() {
+                let remaining = sleep.deadline().saturating_duration_since(this.time.now());
+                *this.paused_remaining = Some(remaining);
+            }
+            return true;
+        }
+        if handle.take_retry_now() {
+            this.paused_remaining.take();
+            // already starting as soon as its rate limiter slot allows
+            if !*this.rate_slot {
+                sleep.reset(this.time.now());
+            }
+        } else if let Some(remaining) = this.paused_remaining.take() {
+            sleep.reset(this.time.after(remaining));
+        }
+        false
+    }
+
+    /// Polls the retry inside its span, so its events nest under it.
+    fn poll_in_span(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
+        // a finished retry stays pending instead of running its action again
+        if self.terminated {
+            return Poll::Pending;
+        }
+        #[cfg(feature = "tracing")]
+        let span = self.span.clone();
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        let poll = self.as_mut().poll_retry(cx);
+        if poll.is_ready() {
+            *self.project().terminated = true;
+        }
+        poll
+    }
+
+    /// Ends the sleep before the next attempt once the trigger of [`RetryIf::wake_on`]
+    /// resolves.
+    fn poll_trigger(self: Pin<&mut Self>, cx: &mut Context) {
+        let this = self.project();
+        let Some(wake_on) = this.wake_on.as_mut() else {
+            return;
+        };
+        let wake_on = wake_on
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // the sleep holding back the first attempt isn't a retry, and the wait for a rate
+        // limiter slot can't be cut short, leave them alone
+        let (RetryStateProj::Sleeping, Some(sleep), 1.., false) = (
+            this.state.project(),
+            this.sleep.as_pin_mut(),
+            *this.attempts,
+            *this.rate_slot,
+        ) else {
+            wake_on.pending = None;
+            return;
+        };
+        let pending = wake_on.pending.get_or_insert_with(|| (wake_on.trigger)());
+        if pending.as_mut().poll(cx).is_ready() {
+            wake_on.pending = None;
+            sleep.reset(this.time.now());
+        }
+    }
+
+    fn poll_retry(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<A::Item, A::Error>> {
+        if self.as_mut().hold(cx) || self.as_mut().hold_first(cx) {
+            return Poll::Pending;
+        }
+        self.as_mut().poll_trigger(cx);
+        let this = self.as_mut().project();
+        let context = || RetryContext::new(*this.attempts, this.time.elapsed(*this.started));
+        let parts = parts(this.parts);
+        match this.state.poll(
+            &mut parts.condition,
+            &mut parts.notify,
+            this.last_error.as_ref(),
+            context,
+            this.sleep,
+            cx,
+        ) {
+            RetryFuturePoll::Running(poll_result) => match poll_result {
+                Poll::Ready(Ok(ok)) => {
+                    if let Some(budget) = self.budget.as_ref() {
+                        budget.deposit();
+                    }
+                    if let Some(permit) = self.as_mut().project().permit.take() {
+                        permit.record(true);
+                    }
+                    #[cfg(feature = "metrics")]
+                    if let Some(metrics) = self.metrics.as_ref() {
+                        metrics.record_finished(self.time.elapsed(self.started));
+                    }
+                    #[cfg(feature = "events")]
+                    if let Some(events) = self.events.as_ref() {
+                        events.send(RetryEvent::Succeeded {
+                            attempts: self.attempts,
+                            elapsed: self.time.elapsed(self.started),
+                        });
+                    }
+                    #[cfg(feature = "otel")]
+                    self.span.record("otel.status_code", "OK");
+                    #[cfg(feature = "tracing")]
+                    if self.attempts > 1 {
+                        tracing::info!(
+                            attempts = self.attempts,
+                            elapsed_ms = self.time.elapsed(self.started).as_millis(),
+                            "succeeded after retrying"
+                        );
+                    }
+                    Poll::Ready(Ok(ok))
+                }
+                Poll::Pending => {
+                    let this = self.as_mut().project();
+                    let timed_out = this.attempt_timer.as_mut().map(|timer| timer.poll(cx));
+                    match timed_out {
+                        Some(Poll::Ready(err)) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!("attempt timed out");
+                            self.decide(err, None, cx)
+                        }
+                        _ => Poll::Pending,
+                    }
+                }
+                Poll::Ready(Err(error)) => match error {
+                    RetryError::Permanent(err) => {
+                        let this = self.as_mut().project();
+                        *this.stop_reason = StopReason::Permanent;
+                        *this.attempt_duration = this.time.elapsed(*this.attempt_started);
+                        if let Some(stats) = self.stats.get() {
+                            stats.record_error();
+                        }
+                        if let Some(permit) = self.as_mut().project().permit.take() {
+                            permit.record(true);
+                        }
+                        #[cfg(feature = "metrics")]
+                        if let Some(metrics) = self.metrics.as_ref() {
+                            metrics.record_failed_attempt(self.attempt_duration);
+                            metrics.record_finished(self.time.elapsed(self.started));
+                        }
+                        #[cfg(feature = "events")]
+                        if let Some(events) = self.events.as_ref() {
+                            events.failed(self.attempts, &err, None);
+                            events.send(RetryEvent::Exhausted {
+                                attempts: self.attempts,
+                                elapsed: self.time.elapsed(self.started),
+                                reason: StopReason::Permanent,
+                            });
+                        }
+                        #[cfg(feature = "otel")]
+                        {
+                            self.span.record("otel.status_code", "ERROR");
+                            self.span.record("otel.status_message", "permanent error");
+                        }
+                        Poll::Ready(Err(err))
+                    }
+                    RetryError::Transient { err, retry_after } => self.decide(err, retry_after, cx),
+                },
+            },
+            RetryFuturePoll::Deciding(poll_result) => match poll_result {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(true) => self.schedule(cx),
+                Poll::Ready(false) => self.exhaust(StopReason::Rejected),
+            },
+            RetryFuturePoll::Notifying(poll_result) => match poll_result {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(delay) => self.sleep(delay, cx),
+            },
+            RetryFuturePoll::Sleeping(poll_result) => match poll_result {
+                Poll::Pending => Poll::Pending,
+                Poll::Ready(_) => self.admit(cx),
+            },
         }
     }
 }
 
+// the strategy, action and hooks are usually closures, so only the progress is shown
+impl<I, A, C, N> fmt::Debug for RetryIf<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RetryIf")
+            .field("phase", &self.phase())
+            .field("attempts", &self.attempts)
+            .field("duration", &self.duration())
+            .field("elapsed", &self.elapsed())
+            .field("next_attempt_at", &self.next_attempt_at())
+            .field("max_elapsed", &self.max_elapsed)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<I, A, C, N> Future for RetryIf<I, A, C, N>
 where
     I: Iterator<Item = Duration>,
@@ -189,33 +2151,503 @@ where
 {
     type Output = Result<A::Item, A::Error>;
 
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.poll_in_span(cx)
+    }
+}
+
+#[cfg(feature = "fused")]
+impl<I, A, C, N> futures_core::FusedFuture for RetryIf<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// Future that drives a [`RetryIf`] and reports every failed attempt once it gives up.
+/// Created by [`RetryIf::with_report`].
+#[pin_project]
+pub struct Reported<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    retry_if: RetryIf<I, A, C, N>,
+}
+
+impl<I, A, C, N> Future for Reported<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Result<A::Item, RetryReport<A::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut retry_if = self.project().retry_if;
+        match retry_if.as_mut().poll_in_span(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(ok)) => Poll::Ready(Ok(ok)),
+            Poll::Ready(Err(error)) => {
+                let this = retry_if.project();
+                let mut errors = this.history.take().unwrap_or_default();
+                // a permanent error leaves the previous transient error behind
+                errors.extend(this.last_error.take());
+                // a delayed first attempt records its own start
+                let first = (this.attempt_times.len() < *this.attempts).then_some(*this.started);
+                let attempt_times = first
+                    .into_iter()
+                    .chain(this.attempt_times.drain(..))
+                    .collect();
+                Poll::Ready(Err(RetryReport {
+                    attempts: *this.attempts,
+                    elapsed: this.time.elapsed(*this.started),
+                    errors,
+                    error,
+                    attempt_times,
+                    reason: *this.stop_reason,
+                }))
+            }
+        }
+    }
+}
+
+/// Value of a successful retry operation, with how many attempts it took.
+/// Created by [`RetryIf::reporting`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retried<T> {
+    /// Value of the successful attempt.
+    pub value: T,
+    /// Number of attempts made, including the successful one.
+    pub attempts: usize,
+    /// Time between the creation of the retry future and the success.
+    pub total_elapsed: Duration,
+    /// Whether the action failed before it succeeded.
+    pub retried: bool,
+}
+
+impl<T> Retried<T> {
+    /// Discards the statistics, returning the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Future that drives a [`RetryIf`] and reports how many attempts it took once it succeeds.
+/// Created by [`RetryIf::reporting`].
+#[pin_project]
+pub struct Reporting<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    retry_if: RetryIf<I, A, C, N>,
+}
+
+impl<I, A, C, N> Future for Reporting<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Result<Retried<A::Item>, A::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut retry_if = self.project().retry_if;
+        match retry_if.as_mut().poll_in_span(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Ready(Ok(value)) => {
+                let this = retry_if.project();
+                Poll::Ready(Ok(Retried {
+                    value,
+                    attempts: *this.attempts,
+                    total_elapsed: this.time.elapsed(*this.started),
+                    retried: *this.attempts > 1,
+                }))
+            }
+        }
+    }
+}
+
+/// Future that drives a [`RetryIf`] and hands its [`RetryReport`] to a hook once it gives up.
+/// Created by [`RetryIf::on_dead_letter`].
+#[pin_project]
+pub struct DeadLetter<I, A, C, N, F>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    reported: Reported<I, A, C, N>,
+    hook: Option<F>,
+}
+
+impl<I, A, C, N, F, R> Future for DeadLetter<I, A, C, N, F>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+    F: FnOnce(RetryReport<A::Error>) -> R,
+{
+    type Output = Result<A::Item, R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        this.reported.poll(cx).map_err(|report| {
+            let hook = this.hook.take().expect("polled after completion");
+            hook(report)
+        })
+    }
+}
+
+/// Future that drives a [`RetryIf`] and converts the error it gives up with.
+/// Created by [`RetryIf::map_give_up`].
+#[pin_project]
+pub struct GiveUp<I, A, C, N, F>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    retry_if: RetryIf<I, A, C, N>,
+    f: Option<F>,
+}
+
+impl<I, A, C, N, F, R> Future for GiveUp<I, A, C, N, F>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+    F: FnOnce(A::Error, GiveUpContext) -> R,
+{
+    type Output = Result<A::Item, R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut retry_if = this.retry_if;
+        retry_if.as_mut().poll_in_span(cx).map_err(|err| {
+            let context = GiveUpContext {
+                attempts: retry_if.attempts,
+                elapsed: retry_if.elapsed(),
+                reason: retry_if.stop_reason,
+            };
+            let f = this.f.take().expect("polled after completion");
+            f(err, context)
+        })
+    }
+}
+
+/// Future that drives a [`RetryIf`] which may also stop for reasons other than the action
+/// failing, like an overall deadline or a cancellation. Created by [`RetryIf::with_deadline`].
+#[pin_project]
+pub struct Guarded<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    retry_if: RetryIf<I, A, C, N>,
+    deadline: Option<Pin<Box<Sleep>>>,
+    past_deadline: SleepPastDeadline,
+    #[cfg(feature = "cancellation")]
+    cancellation: Option<Pin<Box<WaitForCancellationFutureOwned>>>,
+    shutdown: Option<Shutdown>,
+    on_shutdown: OnShutdown,
+    breaker: Option<CircuitBreaker>,
+    terminated: bool,
+}
+
+/// What a [`Guarded`] retry future does when the delay before its next attempt would end
+/// past its deadline, see [`Guarded::on_sleep_past_deadline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SleepPastDeadline {
+    /// Gives up right away with [`RetryFailure::DeadlineExceeded`] instead of sleeping until
+    /// the deadline.
+    #[default]
+    GiveUp,
+    /// Shortens the sleep to make a last attempt, started the given duration before the
+    /// deadline or right away if that is already past.
+    Truncate(Duration),
+}
+
+impl<I, A, C, N> fmt::Debug for Guarded<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Guarded")
+            .field("retry_if", &self.retry_if)
+            .field(
+                "deadline",
+                &self.deadline.as_ref().map(|sleep| sleep.deadline()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, A, C, N> Guarded<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    pub(crate) fn new(retry_if: RetryIf<I, A, C, N>) -> Self {
+        Guarded {
+            retry_if,
+            deadline: None,
+            past_deadline: SleepPastDeadline::GiveUp,
+            #[cfg(feature = "cancellation")]
+            cancellation: None,
+            shutdown: None,
+            on_shutdown: OnShutdown::FinishAttempt,
+            breaker: None,
+            terminated: false,
+        }
+    }
+
+    /// Registers the retry with a shared circuit breaker, see [`RetryIf::with_circuit_breaker`].
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.retry_if.breaker = Some(breaker.clone());
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Returns a handle to the live statistics of the retry, see [`RetryIf::stats`].
+    pub fn stats(&self) -> Arc<RetryStats> {
+        self.retry_if.stats()
+    }
+
+    /// Returns a handle to pause and resume the retry, see [`RetryIf::handle`].
+    pub fn handle(&self) -> RetryHandle {
+        self.retry_if.handle()
+    }
+
+    /// Number of attempts started so far, see [`RetryIf::attempts`].
+    pub const fn attempts(&self) -> usize {
+        self.retry_if.attempts()
+    }
+
+    /// What the retry is doing, see [`RetryIf::phase`].
+    pub const fn phase(&self) -> RetryPhase {
+        self.retry_if.phase()
+    }
+
+    /// Returns a channel receiving the events of the retry, see [`RetryIf::events`].
+    #[cfg(feature = "events")]
+    pub fn events(&mut self, capacity: usize) -> mpsc::Receiver<RetryEvent<A::Error>>
+    where
+        A::Error: Clone,
+    {
+        self.retry_if.events(capacity)
+    }
+
+    /// Bounds each individual attempt, see [`RetryIf::with_attempt_timeout`].
+    pub fn with_attempt_timeout(mut self, attempt_timeout: Duration) -> Self
+    where
+        A::Error: From<AttemptTimeout>,
+    {
+        self.retry_if = self.retry_if.with_attempt_timeout(attempt_timeout);
+        self
+    }
+
+    /// Records the last transient error in the `retry` span, see [`RetryIf::trace_errors`].
+    #[cfg(feature = "tracing")]
+    pub fn trace_errors(mut self) -> Self
+    where
+        A::Error: fmt::Debug,
+    {
+        self.retry_if = self.retry_if.trace_errors();
+        self
+    }
+
+    /// Sets what happens when the delay before the next attempt would end past the deadline.
+    ///
+    /// Gives up right away by default, rather than sleeping through the rest of the deadline.
+    pub const fn on_sleep_past_deadline(mut self, past_deadline: SleepPastDeadline) -> Self {
+        self.past_deadline = past_deadline;
+        self
+    }
+
+    /// Replaces the deadline of the retry operation.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(Box::pin(self.retry_if.time.sleep_until(deadline)));
+        self
+    }
+
+    /// Replaces the shutdown signal of the retry operation, see [`RetryIf::until`].
+    pub fn until(self, shutdown: impl Future<Output = ()> + Send + 'static) -> Self {
+        self.with_shutdown(Shutdown::new(shutdown))
+    }
+
+    pub(crate) fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Sets what happens to a running attempt once the shutdown signal resolves,
+    /// [`OnShutdown::FinishAttempt`] by default.
+    pub const fn on_shutdown(mut self, on_shutdown: OnShutdown) -> Self {
+        self.on_shutdown = on_shutdown;
+        self
+    }
+
+    /// Replaces the cancellation token of the retry operation.
+    #[cfg(feature = "cancellation")]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(Box::pin(token.cancelled_owned()));
+        self
+    }
+}
+
+/// Wraps the error a retry of a [`Guarded`] future gave up with.
+fn failure<I, A, C, N>(retry_if: &RetryIf<I, A, C, N>, err: A::Error) -> RetryFailure<A::Error>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    match retry_if.stop_reason {
+        StopReason::ShutDown => RetryFailure::ShutDown {
+            last_error: Some(err),
+        },
+        _ => RetryFailure::Error(err),
+    }
+}
+
+impl<I, A, C, N> Future for Guarded<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Result<A::Item, RetryFailure<A::Error>>;
+
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-        match self.as_mut().project().state.poll(cx) {
-            RetryFuturePoll::Running(poll_result) => match poll_result {
-                Poll::Ready(Ok(ok)) => Poll::Ready(Ok(ok)),
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Err(error)) => match error {
-                    RetryError::Permanent(err) => Poll::Ready(Err(err)),
-                    RetryError::Transient { err, retry_after } => {
-                        if self.as_mut().project().condition.should_retry(&err) {
-                            let duration =
-                                retry_after.unwrap_or(self.as_ref().project_ref().duration.clone());
-                            self.as_mut().project().notify.notify(&err, duration);
-                            *self.as_mut().project().duration = duration;
-                            match self.retry(err, cx) {
-                                Ok(poll) => poll,
-                                Err(err) => Poll::Ready(Err(err)),
-                            }
-                        } else {
-                            Poll::Ready(Err(err))
+        // a finished retry stays pending instead of running its action again
+        if self.terminated {
+            return Poll::Pending;
+        }
+        let poll = self.as_mut().poll_guarded(cx);
+        if poll.is_ready() {
+            *self.project().terminated = true;
+        }
+        poll
+    }
+}
+
+impl<I, A, C, N> Guarded<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    fn poll_guarded(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<A::Item, RetryFailure<A::Error>>> {
+        let mut this = self.project();
+        // checked once, before the first attempt runs
+        if let Some(breaker) = this.breaker.take() {
+            match breaker.acquire() {
+                Some(permit) => *this.retry_if.as_mut().project().permit = Some(permit),
+                None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("ending retry: circuit breaker is open");
+                    return Poll::Ready(Err(RetryFailure::CircuitOpen));
+                }
+            }
+        }
+
+        if let Some(shutdown) = this.shutdown.as_ref() {
+            if shutdown.poll(cx).is_ready() {
+                *this.shutdown = None;
+                let finish = *this.on_shutdown == OnShutdown::FinishAttempt
+                    && this.retry_if.phase() == RetryPhase::Running;
+                if !finish {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("ending retry: shutting down");
+                    let last_error = this.retry_if.abandon();
+                    return Poll::Ready(Err(RetryFailure::ShutDown { last_error }));
+                }
+                this.retry_if.as_mut().shut_down();
+            }
+        }
+
+        if let Poll::Ready(result) = this.retry_if.as_mut().poll_in_span(cx) {
+            return Poll::Ready(result.map_err(|err| failure(&this.retry_if, err)));
+        }
+
+        if let (Some(deadline), Some(next_attempt)) =
+            (this.deadline.as_ref(), this.retry_if.next_attempt_at())
+        {
+            let deadline = deadline.deadline();
+            if next_attempt >= deadline {
+                match *this.past_deadline {
+                    SleepPastDeadline::GiveUp => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!("ending retry: next attempt would start past the deadline");
+                        let last_error = this.retry_if.abandon();
+                        return Poll::Ready(Err(RetryFailure::DeadlineExceeded { last_error }));
+                    }
+                    SleepPastDeadline::Truncate(last_attempt) => {
+                        let start = deadline.checked_sub(last_attempt).unwrap_or(deadline);
+                        this.retry_if.as_mut().reschedule(start);
+                        if let Poll::Ready(result) = this.retry_if.as_mut().poll_in_span(cx) {
+                            return Poll::Ready(result.map_err(|err| failure(&this.retry_if, err)));
                         }
                     }
-                },
-            },
-            RetryFuturePoll::Sleeping(poll_result) => match poll_result {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(_) => self.attempt(cx),
-            },
+                }
+            }
+        }
+
+        if let Some(deadline) = this.deadline.as_mut() {
+            if deadline.as_mut().poll(cx).is_ready() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("ending retry: deadline exceeded");
+                let last_error = this.retry_if.abandon();
+                return Poll::Ready(Err(RetryFailure::DeadlineExceeded { last_error }));
+            }
+        }
+
+        #[cfg(feature = "cancellation")]
+        if let Some(cancellation) = this.cancellation.as_mut() {
+            if cancellation.as_mut().poll(cx).is_ready() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("ending retry: cancelled");
+                let last_error = this.retry_if.abandon();
+                return Poll::Ready(Err(RetryFailure::Cancelled { last_error }));
+            }
         }
+
+        Poll::Pending
     }
 }
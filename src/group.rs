@@ -0,0 +1,158 @@
+use std::future::Future;
+use std::iter::IntoIterator;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+
+use crate::action::{Action, AttemptContext};
+use crate::error::Error as RetryError;
+use crate::future::Retry;
+use crate::join::JoinAll;
+
+/// Group of retry futures sharing one strategy and a limit on concurrently running attempts.
+///
+/// Sleeping between attempts doesn't hold a slot, so items backing off let others run.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{RetryError, RetryGroup};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn reprocess(job: u64) -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() {
+/// // retry every job with the same strategy, running at most 8 attempts at once
+/// let group = RetryGroup::new(ExponentialBackoff::from_millis(10).take(5), 8);
+/// let results = group
+///     .run_all((0..100).map(|job| move || reprocess(job)))
+///     .await;
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryGroup<S> {
+    strategy: S,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<S> RetryGroup<S>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Creates a group running at most `max_concurrent` attempts at once.
+    pub fn new(strategy: S, max_concurrent: usize) -> Self {
+        RetryGroup {
+            strategy,
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Retries `action` with the strategy of the group, each attempt waiting for a slot.
+    pub fn retry<A: Action>(&self, action: A) -> Retry<S::IntoIter, Limited<A>> {
+        let action = Limited {
+            action,
+            semaphore: self.semaphore.clone(),
+        };
+        Retry::spawn(self.strategy.clone(), action)
+    }
+
+    /// Retries every action, resolving with their results in order once all are done.
+    pub fn run_all<T, A>(&self, actions: T) -> RunAll<S::IntoIter, A>
+    where
+        T: IntoIterator<Item = A>,
+        A: Action,
+    {
+        RunAll {
+            all: JoinAll::new(actions.into_iter().map(|action| self.retry(action))),
+        }
+    }
+}
+
+/// Action adapter waiting for a slot of its [`RetryGroup`] before every attempt.
+#[derive(Debug)]
+pub struct Limited<A> {
+    action: A,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<A: Action> Action for Limited<A> {
+    type Future = Permitted<A::Future>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        Permitted::new(self.action.run(), &self.semaphore)
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        Permitted::new(self.action.run_with(context), &self.semaphore)
+    }
+}
+
+type Acquire = Pin<Box<dyn Future<Output = Result<OwnedSemaphorePermit, AcquireError>> + Send>>;
+
+/// Future of a [`Limited`] attempt, holding its slot while it runs.
+#[pin_project]
+pub struct Permitted<F> {
+    acquire: Option<Acquire>,
+    permit: Option<OwnedSemaphorePermit>,
+    #[pin]
+    future: F,
+}
+
+impl<F> Permitted<F> {
+    fn new(future: F, semaphore: &Arc<Semaphore>) -> Self {
+        Permitted {
+            acquire: Some(Box::pin(semaphore.clone().acquire_owned())),
+            permit: None,
+            future,
+        }
+    }
+}
+
+impl<F, T, E> Future for Permitted<F>
+where
+    F: Future<Output = Result<T, RetryError<E>>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Some(acquire) = this.acquire.as_mut() {
+            match acquire.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                // the group owns the semaphore and never closes it
+                Poll::Ready(permit) => *this.permit = permit.ok(),
+            }
+            *this.acquire = None;
+        }
+        let poll = this.future.poll(cx);
+        if poll.is_ready() {
+            *this.permit = None;
+        }
+        poll
+    }
+}
+
+/// Future retrying every action of a [`RetryGroup::run_all`] call.
+#[pin_project]
+pub struct RunAll<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    #[pin]
+    all: JoinAll<Retry<I, Limited<A>>>,
+}
+
+impl<I, A> Future for RunAll<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    type Output = Vec<Result<A::Item, A::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project().all.poll(cx)
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
+/// Handle controlling an in-flight retry future, returned by
+/// [`RetryIf::handle`](crate::RetryIf::handle).
+///
+/// While paused, no new attempt starts: the sleep before the next attempt is frozen and
+/// restarts from the paused point on resume. A running attempt is not interrupted.
+///
+/// [`RetryHandle::retry_now`] cuts the sleep short instead.
+#[derive(Debug, Clone, Default)]
+pub struct RetryHandle {
+    inner: Arc<Control>,
+}
+
+#[derive(Debug, Default)]
+struct Control {
+    paused: AtomicBool,
+    retry_now: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl RetryHandle {
+    /// Halts further attempts until [`RetryHandle::resume`] is called.
+    pub fn pause(&self) {
+        self.inner.paused.store(true, Ordering::SeqCst);
+        self.wake();
+    }
+
+    /// Lets the retry future continue where it was paused.
+    pub fn resume(&self) {
+        self.inner.paused.store(false, Ordering::SeqCst);
+        self.wake();
+    }
+
+    /// Returns `true` if the retry future is paused.
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::SeqCst)
+    }
+
+    /// Starts the next attempt right away, skipping the rest of the sleep before it.
+    ///
+    /// While an attempt runs, the sleep after it is skipped. While paused, the attempt starts
+    /// on resume.
+    pub fn retry_now(&self) {
+        self.inner.retry_now.store(true, Ordering::SeqCst);
+        self.wake();
+    }
+
+    pub(crate) fn take_retry_now(&self) -> bool {
+        self.inner.retry_now.swap(false, Ordering::SeqCst)
+    }
+
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut slot = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match slot.as_ref() {
+            Some(current) if current.will_wake(waker) => {}
+            _ => *slot = Some(waker.clone()),
+        }
+    }
+
+    fn wake(&self) {
+        let waker = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
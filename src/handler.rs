@@ -0,0 +1,204 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::error::Error as RetryError;
+
+/// What to do with the transient error of an attempt, returned by an [`ErrorHandler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryDecision {
+    /// Retries right away, or after the `retry_after` delay the error carries.
+    Retry,
+    /// Retries after the given delay.
+    RetryAfter(Duration),
+    /// Stops retrying, failing with the error.
+    Stop,
+}
+
+/// Decides, from each transient error alone, whether and when to retry, in place of a
+/// separate condition and strategy, like the `ErrorHandler` of `futures-retry`.
+///
+/// Implemented for closures `FnMut(usize, &E) -> RetryDecision`, called with the 1-based
+/// number of the failed attempt. Permanent errors still stop right away without reaching the
+/// handler. Use it with [`Retry::spawn_handled`](crate::Retry::spawn_handled), or wrap an
+/// action with [`handled`] to keep a strategy bounding the retries.
+///
+/// ```rust,no_run
+/// # use std::io;
+/// # use std::time::Duration;
+/// # use tokio_retry2::{Retry, RetryDecision, RetryError};
+/// # async fn connect() -> Result<(), RetryError<io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), io::Error> {
+/// Retry::spawn_handled(connect, |attempt, err: &io::Error| match err.kind() {
+///     _ if attempt >= 5 => RetryDecision::Stop,
+///     io::ErrorKind::Interrupted => RetryDecision::Retry,
+///     io::ErrorKind::ConnectionRefused => RetryDecision::RetryAfter(Duration::from_secs(1)),
+///     _ => RetryDecision::Stop,
+/// })
+/// .await
+/// # }
+/// ```
+pub trait ErrorHandler<E> {
+    /// Decides what to do after attempt number `attempt` failed with `error`.
+    fn handle(&mut self, attempt: usize, error: &E) -> RetryDecision;
+}
+
+impl<E, F> ErrorHandler<E> for F
+where
+    F: FnMut(usize, &E) -> RetryDecision,
+{
+    fn handle(&mut self, attempt: usize, error: &E) -> RetryDecision {
+        self(attempt, error)
+    }
+}
+
+/// Wraps `action` so `handler` decides the retry of each of its transient errors.
+///
+/// A decision to retry turns the error into a `retry_after` request, which replaces the delay
+/// of the retry strategy, and a decision to stop makes it permanent. The retry strategy
+/// still bounds the number of retries, [`Retry::spawn_handled`](crate::Retry::spawn_handled)
+/// leaves it up to the handler.
+pub fn handled<A, H>(action: A, handler: H) -> Handled<A, H>
+where
+    A: Action,
+    H: ErrorHandler<A::Error>,
+{
+    Handled {
+        action,
+        handler: Arc::new(Mutex::new(handler)),
+        attempts: 0,
+    }
+}
+
+/// Action adapter letting an [`ErrorHandler`] decide the retries, see [`handled`].
+#[derive(Debug)]
+pub struct Handled<A, H> {
+    action: A,
+    handler: Arc<Mutex<H>>,
+    attempts: usize,
+}
+
+impl<A, H> Action for Handled<A, H>
+where
+    A: Action,
+    H: ErrorHandler<A::Error>,
+{
+    type Future = HandledFuture<A::Future, H>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        self.attempts += 1;
+        HandledFuture {
+            future: self.action.run(),
+            handler: self.handler.clone(),
+            attempt: self.attempts,
+        }
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        self.attempts = context.attempt;
+        HandledFuture {
+            future: self.action.run_with(context),
+            handler: self.handler.clone(),
+            attempt: context.attempt,
+        }
+    }
+}
+
+/// Future of a [`Handled`] attempt.
+#[pin_project]
+#[derive(Debug)]
+pub struct HandledFuture<Fut, H> {
+    #[pin]
+    future: Fut,
+    handler: Arc<Mutex<H>>,
+    attempt: usize,
+}
+
+impl<Fut, H, T, E> Future for HandledFuture<Fut, H>
+where
+    Fut: Future<Output = Result<T, RetryError<E>>>,
+    H: ErrorHandler<E>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        this.future.poll(cx).map_err(|error| {
+            let RetryError::Transient { err, retry_after } = error else {
+                return error;
+            };
+            let mut handler = this
+                .handler
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            match handler.handle(*this.attempt, &err) {
+                RetryDecision::Retry => {
+                    RetryError::retry_after(err, retry_after.unwrap_or(Duration::ZERO))
+                }
+                RetryDecision::RetryAfter(delay) => RetryError::retry_after(err, delay),
+                RetryDecision::Stop => RetryError::permanent(err),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use super::*;
+
+    fn handler(attempt: usize, err: &&str) -> RetryDecision {
+        match *err {
+            _ if attempt >= 3 => RetryDecision::Stop,
+            "busy" => RetryDecision::RetryAfter(Duration::from_secs(1)),
+            "refused" => RetryDecision::Retry,
+            _ => RetryDecision::Stop,
+        }
+    }
+
+    async fn outcome(err: RetryError<&'static str>, attempt: usize) -> RetryError<&'static str> {
+        let mut err = Some(err);
+        let mut action = handled(
+            move || future::ready(Err::<(), _>(err.take().unwrap())),
+            handler,
+        );
+        let context = AttemptContext::new(attempt, None, Duration::ZERO);
+        action.run_with(&context).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn decisions_become_retry_after_requests() {
+        let busy = outcome(RetryError::transient("busy"), 1).await;
+        let refused = outcome(RetryError::transient("refused"), 1).await;
+        let requested = outcome(
+            RetryError::retry_after("refused", Duration::from_secs(5)),
+            1,
+        )
+        .await;
+
+        assert_eq!(busy.retry_after_delay(), Some(Duration::from_secs(1)));
+        assert_eq!(refused.retry_after_delay(), Some(Duration::ZERO));
+        assert_eq!(requested.retry_after_delay(), Some(Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn stop_and_permanent_errors_end_the_retry() {
+        assert!(outcome(RetryError::transient("busy"), 3)
+            .await
+            .is_permanent());
+        assert!(outcome(RetryError::transient("gone"), 1)
+            .await
+            .is_permanent());
+        assert!(outcome(RetryError::permanent("busy"), 1)
+            .await
+            .is_permanent());
+    }
+}
@@ -0,0 +1,159 @@
+use std::future::Future;
+use std::iter::{IntoIterator, Iterator};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::error::Error as RetryError;
+use crate::runtime::{deadline_after, now, Sleep};
+
+/// Future that hedges slow attempts at an action: whenever the running attempts take longer
+/// than `hedge_after`, one more attempt is started concurrently, and the first attempt to
+/// succeed wins, dropping the others.
+///
+/// Every attempt failing with a transient error takes one delay of the strategy, hedged
+/// attempts included. Once every running attempt failed, the latest delay is slept before
+/// starting over, like [`Retry`](crate::Retry). Once the strategy is exhausted no more
+/// attempts are started, and the future fails with the last error when the running ones fail.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use tokio_retry2::{Hedge, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn action() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+/// # async fn run() -> Result<u64, std::io::Error> {
+/// // start a second request if the first one takes more than 50ms
+/// Hedge::spawn(ExponentialBackoff::from_millis(10).take(3), action, Duration::from_millis(50)).await
+/// # }
+/// ```
+#[pin_project]
+pub struct Hedge<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    strategy: I,
+    action: A,
+    hedge_after: Duration,
+    max_hedges: usize,
+    running: Vec<Pin<Box<A::Future>>>,
+    hedge: Pin<Box<Sleep>>,
+    backoff: Option<Pin<Box<Sleep>>>,
+    last_error: Option<A::Error>,
+    delay: Option<Duration>,
+    exhausted: bool,
+}
+
+impl<I, A> Hedge<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    /// Starts the first attempt of `action`, hedging it after `hedge_after`.
+    pub fn spawn<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        mut action: A,
+        hedge_after: Duration,
+    ) -> Hedge<I, A> {
+        Hedge {
+            strategy: strategy.into_iter(),
+            running: vec![Box::pin(action.run())],
+            action,
+            hedge_after,
+            max_hedges: 1,
+            hedge: Box::pin(Sleep::after(hedge_after)),
+            backoff: None,
+            last_error: None,
+            delay: None,
+            exhausted: false,
+        }
+    }
+
+    /// Sets how many hedged attempts may run alongside the original one, 1 by default.
+    pub fn max_hedges(mut self, max_hedges: usize) -> Self {
+        self.max_hedges = max_hedges;
+        self
+    }
+}
+
+impl<I, A> Future for Hedge<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    type Output = Result<A::Item, A::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        loop {
+            if let Some(backoff) = this.backoff.as_mut() {
+                if backoff.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                *this.backoff = None;
+                this.running.push(Box::pin(this.action.run()));
+                this.hedge
+                    .as_mut()
+                    .reset(deadline_after(now(), *this.hedge_after));
+            }
+
+            let mut index = 0;
+            while index < this.running.len() {
+                match this.running[index].as_mut().poll(cx) {
+                    Poll::Pending => index += 1,
+                    Poll::Ready(Ok(ok)) => return Poll::Ready(Ok(ok)),
+                    Poll::Ready(Err(RetryError::Permanent(err))) => return Poll::Ready(Err(err)),
+                    Poll::Ready(Err(RetryError::Transient { err, retry_after })) => {
+                        drop(this.running.swap_remove(index));
+                        *this.last_error = Some(err);
+                        if *this.exhausted {
+                            continue;
+                        }
+                        match this.strategy.next() {
+                            Some(duration) => *this.delay = Some(retry_after.unwrap_or(duration)),
+                            None => {
+                                #[cfg(feature = "tracing")]
+                                tracing::warn!("ending retry: strategy reached its limit");
+                                *this.exhausted = true;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if this.running.is_empty() {
+                if *this.exhausted {
+                    let err = this
+                        .last_error
+                        .take()
+                        .expect("a failed attempt is stored while retrying");
+                    return Poll::Ready(Err(err));
+                }
+                let delay = this
+                    .delay
+                    .take()
+                    .expect("a failed attempt takes a delay unless exhausted");
+                *this.backoff = Some(Box::pin(Sleep::after(delay)));
+                continue;
+            }
+
+            if !*this.exhausted
+                && this.running.len() <= *this.max_hedges
+                && this.hedge.as_mut().poll(cx).is_ready()
+            {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("hedging slow attempt");
+                this.running.push(Box::pin(this.action.run()));
+                this.hedge
+                    .as_mut()
+                    .reset(deadline_after(now(), *this.hedge_after));
+                continue;
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
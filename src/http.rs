@@ -0,0 +1,78 @@
+use std::time::{Duration, SystemTime};
+
+use http::header::{HeaderMap, RETRY_AFTER};
+
+use crate::error::Error as RetryError;
+
+/// Parses a `Retry-After` value, either delay seconds or an HTTP-date.
+///
+/// Dates in the past resolve to a zero delay.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Reads the delay requested by the `Retry-After` header, if present and valid.
+pub fn retry_after_from_header(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value)
+}
+
+impl<E> RetryError<E> {
+    /// Creates a transient error which is retried after the delay of the `Retry-After`
+    /// header, or according to the defined strategy policy without a valid header.
+    pub fn transient_with_header(err: E, headers: &HeaderMap) -> Self {
+        RetryError::Transient {
+            err,
+            retry_after: retry_after_from_header(headers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn parses_delay_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("-1"), None);
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn parses_http_date() {
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        let delay = parse_retry_after(&date).unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn transient_error_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            RetryError::transient_with_header((), &headers),
+            RetryError::transient(())
+        );
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(
+            RetryError::transient_with_header((), &headers),
+            RetryError::retry_after((), Duration::from_secs(3))
+        );
+    }
+}
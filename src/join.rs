@@ -0,0 +1,359 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::iter::IntoIterator;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::condition::Condition;
+use crate::error::RetryFailure;
+use crate::future::Guarded;
+use crate::notify::Notify;
+use crate::policy::RetryPolicy;
+
+type Running<I, A, C, N> = Joined<Guarded<I, A, C, N>>;
+
+/// Retries every action concurrently with `policy`, resolving with their results in order
+/// once all are done.
+///
+/// Every action runs its own retry future with a fresh clone of the strategy, see
+/// [`RetryPolicy::run`]. Use [`try_retry_all`] to stop at the first failure instead, or a
+/// [`RetryGroup`](crate::RetryGroup) to also bound how many attempts run at once.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{retry_all, Retry, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn backfill(day: u32) -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+/// # async fn run() {
+/// let policy = Retry::builder()
+///     .strategy(ExponentialBackoff::from_millis(10).take(5))
+///     .into_policy();
+/// let results = retry_all(&policy, (1..=31).map(|day| move || backfill(day))).await;
+/// # }
+/// ```
+pub fn retry_all<S, C, N, T, A>(
+    policy: &RetryPolicy<S, C, N>,
+    actions: T,
+) -> RetryAll<S::IntoIter, A, C, N>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+    T: IntoIterator<Item = A>,
+    A: Action,
+    C: Condition<A::Error> + Clone,
+    N: Notify<A::Error> + Clone,
+{
+    RetryAll {
+        all: JoinAll::new(actions.into_iter().map(|action| policy.run(action))),
+    }
+}
+
+/// Same as [`retry_all`], failing with the error of the first action that gives up.
+///
+/// The retries of the other actions are dropped once one fails.
+pub fn try_retry_all<S, C, N, T, A>(
+    policy: &RetryPolicy<S, C, N>,
+    actions: T,
+) -> TryRetryAll<S::IntoIter, A, C, N>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+    T: IntoIterator<Item = A>,
+    A: Action,
+    C: Condition<A::Error> + Clone,
+    N: Notify<A::Error> + Clone,
+{
+    let futures = Joined::new(actions.into_iter().map(|action| policy.run(action)));
+    TryRetryAll {
+        results: (0..futures.len()).map(|_| None).collect(),
+        futures,
+    }
+}
+
+/// Futures polled together, each with a waker of its own, so a wake only polls the futures
+/// it concerns.
+pub(crate) struct Joined<F> {
+    futures: Vec<Option<Pin<Box<F>>>>,
+    slots: Vec<Arc<Slot>>,
+    queue: Arc<WakeQueue>,
+    remaining: usize,
+}
+
+/// Futures of a [`Joined`] woken since they were last polled, and the task polling them.
+#[derive(Default)]
+struct WakeQueue {
+    woken: Mutex<VecDeque<usize>>,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Waker of one future of a [`Joined`].
+struct Slot {
+    index: usize,
+    queued: AtomicBool,
+    queue: Arc<WakeQueue>,
+}
+
+impl Wake for Slot {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.queued.swap(true, Ordering::AcqRel) {
+            let mut woken = self
+                .queue
+                .woken
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            woken.push_back(self.index);
+        }
+        let waker = self
+            .queue
+            .waker
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<F: Future> Joined<F> {
+    pub(crate) fn new(futures: impl IntoIterator<Item = F>) -> Self {
+        let futures: Vec<_> = futures
+            .into_iter()
+            .map(|future| Some(Box::pin(future)))
+            .collect();
+        let queue = Arc::new(WakeQueue::default());
+        // every future is polled once to start
+        let slots = (0..futures.len())
+            .map(|index| {
+                Arc::new(Slot {
+                    index,
+                    queued: AtomicBool::new(true),
+                    queue: queue.clone(),
+                })
+            })
+            .collect();
+        *queue
+            .woken
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = (0..futures.len()).collect();
+        Joined {
+            remaining: futures.len(),
+            futures,
+            slots,
+            queue,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Drops the futures still running.
+    pub(crate) fn clear(&mut self) {
+        self.futures.clear();
+        self.remaining = 0;
+    }
+
+    /// Polls the woken futures, resolving with the index and output of the next one done,
+    /// or with `None` once all are done.
+    pub(crate) fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<(usize, F::Output)>> {
+        if self.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        {
+            let mut waker = self
+                .queue
+                .waker
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !waker
+                .as_ref()
+                .is_some_and(|waker| waker.will_wake(cx.waker()))
+            {
+                *waker = Some(cx.waker().clone());
+            }
+        }
+        // at most one round, so futures waking themselves right away don't starve the task
+        for _ in 0..self.futures.len() {
+            let next = self
+                .queue
+                .woken
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .pop_front();
+            let Some(index) = next else {
+                return Poll::Pending;
+            };
+            let slot = &self.slots[index];
+            slot.queued.store(false, Ordering::Release);
+            let Some(future) = self.futures[index].as_mut() else {
+                continue;
+            };
+            let waker = Waker::from(slot.clone());
+            if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                self.futures[index] = None;
+                self.remaining -= 1;
+                return Poll::Ready(Some((index, output)));
+            }
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/// Future polling [`Joined`] futures, resolving with their outputs in order once all are done.
+#[pin_project]
+pub(crate) struct JoinAll<F: Future> {
+    futures: Joined<F>,
+    outputs: Vec<Option<F::Output>>,
+}
+
+impl<F: Future> JoinAll<F> {
+    pub(crate) fn new(futures: impl IntoIterator<Item = F>) -> Self {
+        let futures = Joined::new(futures);
+        JoinAll {
+            outputs: (0..futures.len()).map(|_| None).collect(),
+            futures,
+        }
+    }
+}
+
+impl<F: Future> Future for JoinAll<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        while let Some((index, output)) = std::task::ready!(this.futures.poll_next(cx)) {
+            this.outputs[index] = Some(output);
+        }
+        Poll::Ready(
+            this.outputs
+                .iter_mut()
+                .map(|output| output.take().expect("polled after completion"))
+                .collect(),
+        )
+    }
+}
+
+/// Future retrying every action of a [`retry_all`] call.
+#[pin_project]
+pub struct RetryAll<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    #[pin]
+    all: JoinAll<Guarded<I, A, C, N>>,
+}
+
+impl<I, A, C, N> Future for RetryAll<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Vec<Result<A::Item, RetryFailure<A::Error>>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project().all.poll(cx)
+    }
+}
+
+/// Future retrying every action of a [`try_retry_all`] call.
+#[pin_project]
+pub struct TryRetryAll<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    futures: Running<I, A, C, N>,
+    results: Vec<Option<A::Item>>,
+}
+
+impl<I, A, C, N> Future for TryRetryAll<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+{
+    type Output = Result<Vec<A::Item>, RetryFailure<A::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        while let Some((index, result)) = std::task::ready!(this.futures.poll_next(cx)) {
+            match result {
+                Ok(item) => this.results[index] = Some(item),
+                Err(err) => {
+                    this.futures.clear();
+                    return Poll::Ready(Err(err));
+                }
+            }
+        }
+        Poll::Ready(Ok(this
+            .results
+            .iter_mut()
+            .map(|result| result.take().expect("polled after completion"))
+            .collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    #[test]
+    fn wakes_poll_only_the_woken_futures() {
+        let polls: Vec<Arc<AtomicUsize>> = (0..3).map(|_| Arc::default()).collect();
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..3).map(|_| oneshot::channel::<usize>()).unzip();
+        let futures = receivers
+            .into_iter()
+            .zip(polls.clone())
+            .map(|(mut receiver, polls)| {
+                std::future::poll_fn(move |cx| {
+                    polls.fetch_add(1, Ordering::SeqCst);
+                    Pin::new(&mut receiver).poll(cx).map(Result::unwrap)
+                })
+            });
+        let mut all = Box::pin(JoinAll::new(futures));
+        let mut cx = Context::from_waker(Waker::noop());
+        let polled = || {
+            polls
+                .iter()
+                .map(|polls| polls.load(Ordering::SeqCst))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(all.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(polled(), [1, 1, 1]);
+
+        let mut senders = senders.into_iter().enumerate().rev();
+        let (index, sender) = senders.next().unwrap();
+        sender.send(index).unwrap();
+        assert!(all.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(polled(), [1, 1, 2]);
+
+        for (index, sender) in senders {
+            sender.send(index).unwrap();
+        }
+        assert_eq!(all.as_mut().poll(&mut cx), Poll::Ready(vec![0, 1, 2]));
+        assert_eq!(polled(), [2, 2, 2]);
+    }
+}
@@ -103,10 +103,60 @@
 //! }
 //! ```
 //!
+//! ## Deadlines
+//!
+//! `with_deadline` bounds the whole retry operation, stopping it even while sleeping
+//! between attempts. The error of the last failed attempt is kept in the resulting `RetryFailure`:
+//!
+//! ```rust,no_run
+//! use std::time::{Duration, Instant};
+//! use tokio_retry2::{Retry, RetryError, RetryFailure};
+//! use tokio_retry2::strategy::ExponentialBackoff;
+//!
+//! async fn action() -> Result<u64, RetryError<std::io::Error>> {
+//!     // do some real-world stuff here...
+//! #   Ok(0)
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let deadline = Instant::now() + Duration::from_secs(5);
+//! match Retry::spawn(ExponentialBackoff::from_millis(10), action).with_deadline(deadline).await {
+//!     Ok(value) => println!("got {value}"),
+//!     Err(RetryFailure::DeadlineExceeded { last_error }) => println!("timed out, last error: {last_error:?}"),
+//!     Err(failure) => println!("failed: {failure}"),
+//! }
+//! # }
+//! ```
+//!
+//! ## Builder
+//!
+//! [`Retry::builder`] combines every option without picking a constructor upfront:
+//!
+//! ```rust,no_run
+//! # use std::time::Duration;
+//! # use tokio_retry2::{Retry, RetryError};
+//! # use tokio_retry2::strategy::FixedInterval;
+//! # async fn action() -> Result<u64, RetryError<()>> { Ok(0) }
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), tokio_retry2::RetryFailure<()>> {
+//! let value = Retry::builder()
+//!     .strategy(FixedInterval::from_millis(100).take(5))
+//!     .notify(|_: &(), duration| println!("retrying after {duration:?}"))
+//!     .max_elapsed(Duration::from_secs(2))
+//!     .build(action)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Features
 //! `[jitter]`
 //! - `jitter` ranges between 50% and 150% of the strategy delay.
 //! - `jitter_range(min: f64, max: f64)` ranges between `min * Duration` and `max * Duration`.
+//! - `strategy.with_jitter(jitter)` applies any `Jitter`: `FullJitter` ranges between zero and
+//!   the strategy delay, `Proportional(0.2)` between 80% and 120% of it, and
+//!   `Additive(duration)` adds up to `duration` to it.
 //!
 //! To use jitter, add this to your Cargo.toml
 //!
@@ -120,6 +170,7 @@
 //! ## `jitter`
 //!
 //! ```rust,no_run
+//! # #[cfg(feature = "jitter")] {
 //! use tokio_retry2::Retry;
 //! use tokio_retry2::strategy::{ExponentialBackoff, jitter, MaxInterval};
 //!
@@ -127,11 +178,13 @@
 //!    .max_interval(10000) // set max interval to 10 seconds
 //!    .map(jitter) // add jitter to the retry interval
 //!    .take(3);    // limit to 3 retries
+//! # }
 //!````
 //!
 //! ## `jitter_range`
 //!
 //! ```rust,no_run
+//! # #[cfg(feature = "jitter")] {
 //! use tokio_retry2::Retry;
 //! use tokio_retry2::strategy::{ExponentialFactorBackoff, jitter_range, MaxInterval};
 //!
@@ -139,24 +192,298 @@
 //!    .max_interval(10000) // set max interval to 10 seconds
 //!    .map(jitter_range(0.5, 1.2)) // add jitter ranging between 50% and 120% to the retry interval
 //!    .take(3);    // limit to 3 retries
+//! # }
+//!````
+//!
+//! ## `with_jitter`
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "jitter")] {
+//! use tokio_retry2::strategy::{ExponentialBackoff, Proportional, WithJitter};
+//!
+//! let retry_strategy = ExponentialBackoff::from_millis(10)
+//!    .with_jitter(Proportional(0.2)) // keep every delay within 20% of the backoff
+//!    .take(3);    // limit to 3 retries
+//! # }
 //!````
 //!
+//! ## Static policies
+//!
+//! The constructors and builder methods of `FixedInterval`, `ExponentialBackoff`,
+//! `ExponentialFactorBackoff` and `FibonacciBackoff` are `const fn`, so a policy built from them
+//! can be declared once as `static` configuration and cloned wherever a retry is started:
+//!
+//! ```rust,no_run
+//! use tokio_retry2::Retry;
+//! use tokio_retry2::strategy::ExponentialBackoff;
+//!
+//! static RETRY_STRATEGY: ExponentialBackoff = ExponentialBackoff::from_millis(10)
+//!     .factor(1)
+//!     .max_delay_millis(100);
+//!
+//! let retry_strategy = RETRY_STRATEGY.clone().take(3);
+//! ```
+//!
+//! ## Inspecting a strategy
+//!
+//! Any cloneable strategy can be previewed without consuming it, which is handy for logging
+//! or unit-testing the delay plan of a policy:
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use tokio_retry2::strategy::{FibonacciBackoff, Preview};
+//!
+//! let retry_strategy = FibonacciBackoff::from_millis(10).take(4);
+//!
+//! assert_eq!(
+//!     retry_strategy.preview(10),
+//!     [10, 10, 20, 30].map(Duration::from_millis).to_vec()
+//! );
+//! ```
+//!
+//! ## Runtimes
+//!
+//! Retry futures only need a timer from their runtime, and the runtime features only add
+//! timers to pick from. The [`DefaultSleeper`] uses the tokio one inside a tokio runtime with
+//! the default `rt-tokio` feature, else the one of `rt-async-std`, `rt-smol` or
+//! `rt-futures-timer` if enabled, else a timer thread of this crate, so retries work on any
+//! executor whatever the features unified by cargo. Deadlines are [`std::time::Instant`]s
+//! either way, and [`SleeperClock`] pins a retry future to one timer:
+//!
+//! ```toml
+//! [dependencies]
+//! tokio-retry2 = { version = "0.5", default-features = false, features = ["rt-smol"] }
+//! ```
+//!
+//! On wasm32 targets, the `wasm` feature takes over the timer with the browser one, and the
+//! clock is `web_time` there.
+//!
+//! Without any feature, the crate is `no_std` and only provides the strategies with fixed
+//! parameters, the [`Action`], [`Condition`] and [`Notify`] traits, and [`retry_with_timer`],
+//! which sleeps with the timer it is given, e.g. the one of embassy in embedded firmware. The
+//! `alloc` feature adds the boxing adapters, like [`NotifyAsync`]:
+//!
+//! ```toml
+//! [dependencies]
+//! tokio-retry2 = { version = "0.5", default-features = false, features = ["alloc"] }
+//! ```
+//!
 //! ### NOTE:
 //! The time spent executing an action does not affect the intervals between
 //! retries. Therefore, for long-running functions it's a good idea to set up a deadline,
 //! to place an upper bound on the strategy execution time.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(warnings)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 mod action;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "std")]
+mod boxed;
+#[cfg(feature = "std")]
+mod breaker;
+#[cfg(feature = "std")]
+mod budget;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod classified;
+#[cfg(feature = "std")]
+mod classifier;
 mod condition;
+mod decide;
+#[cfg(feature = "detach")]
+mod detach;
+#[cfg(feature = "std")]
+mod driver;
 pub(crate) mod error;
+#[cfg(feature = "events")]
+mod events;
+#[cfg(feature = "std")]
+mod ext;
+#[cfg(feature = "std")]
+mod fallback;
+#[cfg(feature = "std")]
 mod future;
+#[cfg(feature = "group")]
+mod group;
+#[cfg(feature = "std")]
+mod handle;
+#[cfg(feature = "std")]
+mod handler;
+#[cfg(feature = "std")]
+mod hedge;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "std")]
+mod join;
+#[cfg(feature = "log")]
+mod log;
+#[cfg(feature = "std")]
+mod macros;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod notify;
+#[cfg(feature = "std")]
+mod output;
+#[cfg(feature = "persistence")]
+mod persistent;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+mod pool;
+#[cfg(feature = "std")]
+mod rate_limit;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "std")]
+mod runtime;
+#[cfg(feature = "scheduler")]
+mod scheduler;
+#[cfg(feature = "std")]
+mod shutdown;
+#[cfg(feature = "sink")]
+mod sink;
+#[cfg(feature = "std")]
+mod stats;
 /// Assorted retry strategies including fixed interval and exponential back-off.
 pub mod strategy;
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "std")]
+mod supervisor;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod timer;
+#[cfg(feature = "tonic")]
+mod tonic;
+#[cfg(feature = "tower")]
+mod tower;
+#[cfg(feature = "std")]
+mod transient;
 
-pub use action::Action;
-pub use condition::Condition;
-pub use error::{Error as RetryError, MapErr};
-pub use future::{Retry, RetryIf};
+#[cfg(feature = "std")]
+pub use action::ActionContext;
+pub use action::{
+    Action, ActionWith, AnyError, AnyErrorFuture, AttemptContext, BorrowingAction, Resumable,
+    WithInput,
+};
+#[cfg(feature = "std")]
+pub use boxed::{BoxedRetry, BoxedRetryPolicy};
+#[cfg(feature = "std")]
+pub use breaker::{CircuitBreaker, CircuitState};
+#[cfg(feature = "std")]
+pub use budget::RetryBudget;
+#[cfg(feature = "std")]
+pub use builder::{ActionBuilder, RetryBuilder};
+#[cfg(feature = "std")]
+pub use classified::ClassifiedCondition;
+#[cfg(feature = "std")]
+pub use classifier::{retry_if_is, Classifier, IsError};
+#[cfg(feature = "alloc")]
+pub use condition::ConditionAsync;
+pub use condition::{
+    always, never, Always, And, Condition, ConditionExt, ConditionState, Never, Not, Or,
+    RetryContext,
+};
+#[cfg(feature = "std")]
+pub use condition::{io_kinds, transient_io_errors, ConditionContext, IoKinds};
+#[cfg(feature = "detach")]
+pub use detach::DetachedRetry;
+#[cfg(feature = "std")]
+pub use driver::{NextAttempt, RetryDriver};
+#[cfg(feature = "std")]
+pub use error::RetryReport;
+pub use error::{
+    AttemptTimeout, Error as RetryError, FallbackError, GiveUpContext, MapErr, RetryFailure,
+    StopReason,
+};
+#[cfg(feature = "events")]
+pub use events::RetryEvent;
+#[cfg(feature = "std")]
+pub use ext::{RetryExt, Retryable};
+#[cfg(feature = "std")]
+pub use fallback::Fallback;
+#[cfg(feature = "std")]
+pub use future::{
+    retry, retry_any, retry_async, retry_borrowing, retry_handled, retry_if, retry_with,
+    retry_with_state, DeadLetter, GiveUp, Guarded, Reported, Reporting, Retried, Retry, RetryIf,
+    RetryParts, RetryPhase, SleepPastDeadline,
+};
+#[cfg(feature = "group")]
+pub use group::{Limited, Permitted, RetryGroup, RunAll};
+#[cfg(feature = "std")]
+pub use handle::RetryHandle;
+#[cfg(feature = "std")]
+pub use handler::{handled, ErrorHandler, Handled, HandledFuture, RetryDecision};
+#[cfg(feature = "std")]
+pub use hedge::Hedge;
+#[cfg(feature = "http")]
+pub use http::{parse_retry_after, retry_after_from_header};
+#[cfg(feature = "std")]
+pub use join::{retry_all, try_retry_all, RetryAll, TryRetryAll};
+#[cfg(feature = "log")]
+pub use log::LogNotify;
+#[cfg(feature = "metrics")]
+pub use metrics::RetryMetrics;
+#[cfg(feature = "alloc")]
+pub use notify::NotifyAsync;
+#[cfg(feature = "std")]
+pub use notify::NotifyContext;
+pub use notify::{DelayContext, NoNotify, Notify, NotifyAttempt, OnExhausted};
+#[cfg(feature = "std")]
+pub use output::{OutputAction, OutputFuture, RetryIfOutput};
+#[cfg(feature = "persistence")]
+pub use persistent::{
+    FileStore, MemoryStore, PersistentRetryQueue, Processed, RetryEntry, RetryStore,
+};
+#[cfg(feature = "std")]
+pub use policy::RetryPolicy;
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+pub use pool::RetryManager;
+#[cfg(feature = "std")]
+pub use rate_limit::RateLimiter;
+#[cfg(feature = "reqwest")]
+pub use reqwest::{is_retryable_reqwest_error, retry_request};
+#[cfg(feature = "rt-async-std")]
+pub use runtime::AsyncStdSleeper;
+#[cfg(feature = "rt-futures-timer")]
+pub use runtime::FuturesTimerSleeper;
+#[cfg(feature = "rt-smol")]
+pub use runtime::SmolSleeper;
+#[cfg(feature = "rt-tokio")]
+pub use runtime::TokioSleeper;
+#[cfg(feature = "wasm")]
+pub use runtime::WasmSleeper;
+#[cfg(feature = "std")]
+pub use runtime::{
+    Clock, ClockSleep, DefaultSleep, DefaultSleeper, Instant, Sleeper, SleeperClock, ThreadSleep,
+    ThreadSleeper,
+};
+#[cfg(feature = "scheduler")]
+pub use scheduler::{
+    DuplicateJob, JobEvent, JobEvents, JobHandle, JobOutcome, JobStatus, RetryScheduler,
+};
+#[cfg(feature = "std")]
+pub use shutdown::OnShutdown;
+#[cfg(feature = "sink")]
+pub use sink::RetrySink;
+#[cfg(feature = "std")]
+pub use stats::RetryStats;
+#[cfg(feature = "stream")]
+pub use stream::{AttemptOutcome, Resubscribe, RetryStream};
+#[cfg(feature = "std")]
+pub use supervisor::Supervisor;
+pub use timer::{retry_with_timer, retry_with_timer_notify};
+#[cfg(feature = "tonic")]
+pub use tonic::{grpc_retry_after, is_retryable_grpc_status, retry_unary};
+#[cfg(feature = "tower")]
+pub use tower::{OnError, RetryLayer, RetryService, ServiceAction, ServiceAttempt};
+#[cfg(feature = "std")]
+pub use transient::{
+    Classify, ClassifyFuture, MaybeRetryable, OnTransient, Retryability, TransientError,
+};
@@ -0,0 +1,148 @@
+use std::fmt;
+use std::time::Duration;
+
+use ::log::Level;
+
+use crate::condition::RetryContext;
+use crate::notify::{DelayContext, Notify};
+
+/// Notify hook logging every retry through the `log` crate, with the `log` feature.
+///
+/// Each retry is logged at warn level by default, with the error, the number of the failed
+/// attempt and the delay before the next one. Giving up is logged at the same level.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{LogNotify, Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let notify = LogNotify::new().target("billing_client");
+///
+/// Retry::spawn_notify(FixedInterval::from_millis(10).take(3), action, notify).await
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogNotify {
+    level: Level,
+    target: &'static str,
+}
+
+impl LogNotify {
+    /// Creates a hook logging at warn level with the `tokio_retry2` target.
+    pub const fn new() -> Self {
+        LogNotify {
+            level: Level::Warn,
+            target: "tokio_retry2",
+        }
+    }
+
+    /// Sets the level of the log records.
+    pub const fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets the target of the log records.
+    pub const fn target(mut self, target: &'static str) -> Self {
+        self.target = target;
+        self
+    }
+}
+
+impl Default for LogNotify {
+    fn default() -> Self {
+        LogNotify::new()
+    }
+}
+
+impl<E: fmt::Display> Notify<E> for LogNotify {
+    fn notify(&mut self, err: &E, duration: Duration) {
+        ::log::log!(
+            target: self.target,
+            self.level,
+            "retrying after transient error: {err}, delay: {duration:?}"
+        );
+    }
+
+    fn notify_with(&mut self, err: &E, context: &DelayContext) {
+        ::log::log!(
+            target: self.target,
+            self.level,
+            "attempt {} failed: {err}, retrying in {:?}",
+            context.attempt,
+            context.next_delay
+        );
+    }
+
+    fn exhausted(&mut self, err: &E, context: &RetryContext) {
+        ::log::log!(
+            target: self.target,
+            self.level,
+            "giving up after {} attempts: {err}",
+            context.attempts
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use ::log::{Log, Metadata, Record};
+
+    use super::*;
+
+    static RECORDS: Mutex<Vec<(Level, String, String)>> = Mutex::new(Vec::new());
+
+    struct Recorder;
+
+    impl Log for Recorder {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS.lock().unwrap().push((
+                record.level(),
+                record.target().to_owned(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn logs_retries_and_giving_up() {
+        ::log::set_logger(&Recorder).unwrap();
+        ::log::set_max_level(::log::LevelFilter::Trace);
+        let mut notify = LogNotify::new().target("billing");
+
+        let context =
+            DelayContext::new(2, Duration::from_millis(20), Duration::ZERO, Duration::ZERO);
+        notify.notify_with(&"timed out", &context);
+        notify.exhausted(&"timed out", &RetryContext::new(3, Duration::ZERO));
+
+        let records = RECORDS.lock().unwrap();
+        let records: Vec<_> = records
+            .iter()
+            .filter(|(_, target, _)| target == "billing")
+            .cloned()
+            .collect();
+        assert_eq!(
+            records,
+            [
+                (
+                    Level::Warn,
+                    "billing".to_owned(),
+                    "attempt 2 failed: timed out, retrying in 20ms".to_owned()
+                ),
+                (
+                    Level::Warn,
+                    "billing".to_owned(),
+                    "giving up after 3 attempts: timed out".to_owned()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,24 @@
+/// Retries a block of async code with a strategy, see [`Retry::spawn_async`](crate::Retry::spawn_async).
+///
+/// The block may borrow from the enclosing scope and evaluates to a
+/// `Result<T, RetryError<E>>`; the macro evaluates to a future of `Result<T, E>`.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # struct Client;
+/// # impl Client { async fn get(&self, url: &str) -> Result<u64, std::io::Error> { Ok(0) } }
+/// # async fn run(client: Client) -> Result<u64, std::io::Error> {
+/// let url = String::from("https://example.com");
+/// retry!(FixedInterval::from_millis(10).take(3), {
+///     client.get(&url).await.map_err(RetryError::transient)
+/// })
+/// .await
+/// # }
+/// ```
+#[macro_export]
+macro_rules! retry {
+    ($strategy:expr, $body:block) => {
+        $crate::Retry::spawn_async($strategy, async || $body)
+    };
+}
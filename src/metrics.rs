@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use ::metrics::{counter, histogram, Label};
+
+/// Metrics emitted by a retry future with the `metrics` feature, see
+/// [`RetryIf::with_metrics`](crate::RetryIf::with_metrics).
+///
+/// Every metric is named after a prefix, `retry` by default, and carries the configured labels:
+/// - `<prefix>_attempts_total`: counter of attempts, including the first one.
+/// - `<prefix>_retries_total`: counter of scheduled retries.
+/// - `<prefix>_exhausted_total`: counter of retry futures giving up on a transient error.
+/// - `<prefix>_delay_seconds`: histogram of the delay before each retry.
+/// - `<prefix>_attempt_duration_seconds`: histogram of how long each failed attempt took.
+/// - `<prefix>_duration_seconds`: histogram of the total duration of each retry future.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, RetryError, RetryMetrics};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let metrics = RetryMetrics::new()
+///     .prefix("billing_client")
+///     .label("endpoint", "/invoices");
+///
+/// Retry::spawn(FixedInterval::from_millis(10).take(3), action)
+///     .with_metrics(metrics)
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryMetrics {
+    attempts: String,
+    retries: String,
+    exhausted: String,
+    delay: String,
+    attempt_duration: String,
+    duration: String,
+    labels: Vec<Label>,
+}
+
+impl RetryMetrics {
+    /// Creates the metrics with the `retry` prefix and no labels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the prefix of the metric names.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.attempts = format!("{prefix}_attempts_total");
+        self.retries = format!("{prefix}_retries_total");
+        self.exhausted = format!("{prefix}_exhausted_total");
+        self.delay = format!("{prefix}_delay_seconds");
+        self.attempt_duration = format!("{prefix}_attempt_duration_seconds");
+        self.duration = format!("{prefix}_duration_seconds");
+        self
+    }
+
+    /// Adds a label to every metric.
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.push(Label::new(key.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn record_attempts(&self, attempts: usize) {
+        counter!(self.attempts.clone(), self.labels.clone()).increment(attempts as u64);
+    }
+
+    pub(crate) fn record_retry(&self, delay: Duration) {
+        counter!(self.retries.clone(), self.labels.clone()).increment(1);
+        histogram!(self.delay.clone(), self.labels.clone()).record(delay.as_secs_f64());
+    }
+
+    pub(crate) fn record_failed_attempt(&self, duration: Duration) {
+        histogram!(self.attempt_duration.clone(), self.labels.clone())
+            .record(duration.as_secs_f64());
+    }
+
+    pub(crate) fn record_exhausted(&self) {
+        counter!(self.exhausted.clone(), self.labels.clone()).increment(1);
+    }
+
+    pub(crate) fn record_finished(&self, elapsed: Duration) {
+        histogram!(self.duration.clone(), self.labels.clone()).record(elapsed.as_secs_f64());
+    }
+}
+
+impl Default for RetryMetrics {
+    fn default() -> Self {
+        RetryMetrics {
+            attempts: String::new(),
+            retries: String::new(),
+            exhausted: String::new(),
+            delay: String::new(),
+            attempt_duration: String::new(),
+            duration: String::new(),
+            labels: Vec::new(),
+        }
+        .prefix("retry")
+    }
+}
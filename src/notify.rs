@@ -1,7 +1,67 @@
-use std::time::Duration;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
 
+use crate::condition::RetryContext;
+#[cfg(feature = "std")]
+use crate::runtime::{self, Instant};
+
+/// Hook invoked every time a failed action is about to be retried.
+///
+/// Tuples of up to 4 hooks notify every hook in order, e.g. a metrics emitter and a logger:
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # fn record_retry() {}
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let log = |err: &std::io::Error, duration| println!("{err}, retrying after {duration:?}");
+/// let count = |_: &std::io::Error, _| record_retry();
+///
+/// Retry::spawn_notify(FixedInterval::from_millis(10).take(3), action, (log, count)).await
+/// # }
+/// ```
 pub trait Notify<E> {
+    /// Called with the error of the failed attempt and the current retry duration.
     fn notify(&mut self, err: &E, duration: Duration);
+
+    /// Called with the 1-based number of the attempt that just failed.
+    ///
+    /// Forwards to [`Notify::notify`] by default.
+    fn notify_attempt(&mut self, err: &E, attempt: usize, duration: Duration) {
+        let _ = attempt;
+        self.notify(err, duration)
+    }
+
+    /// Called by the retry futures with the [`DelayContext`] of the upcoming retry.
+    ///
+    /// Forwards to [`Notify::notify_attempt`] with [`DelayContext::duration`] by default.
+    fn notify_with(&mut self, err: &E, context: &DelayContext) {
+        self.notify_attempt(err, context.attempt, context.duration)
+    }
+
+    /// Polled by the retry futures after every notification, delaying the retry sleep until it
+    /// is ready. Lets hooks await asynchronous work, see [`NotifyAsync`].
+    ///
+    /// Always ready by default.
+    fn poll_notified(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let _ = cx;
+        Poll::Ready(())
+    }
+
+    /// Called once by the retry futures when they give up on a transient error, because the
+    /// strategy is exhausted, the condition rejected the error or `max_elapsed` passed.
+    /// Permanent errors don't trigger it. See [`OnExhausted`].
+    ///
+    /// Does nothing by default.
+    fn exhausted(&mut self, err: &E, context: &RetryContext) {
+        let _ = (err, context);
+    }
 }
 
 impl<E, F> Notify<E> for F
@@ -12,3 +72,319 @@ where
         self(err, duration)
     }
 }
+
+/// Context of the retry a [`Notify`] hook is called for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct DelayContext {
+    /// 1-based number of the attempt that just failed.
+    pub attempt: usize,
+    /// Delay before the next attempt, the strategy delay or the requested `retry_after`.
+    pub next_delay: Duration,
+    /// Time since the retry future was created, excluding `next_delay`.
+    pub total_elapsed: Duration,
+    /// Duration passed to [`Notify::notify`]: the requested `retry_after` if any, else the
+    /// sum of the previous delays.
+    pub duration: Duration,
+    /// How long the failed attempt itself took, from its start to its error.
+    pub attempt_duration: Duration,
+}
+
+impl DelayContext {
+    /// Creates a context, mostly useful to test notify hooks.
+    pub const fn new(
+        attempt: usize,
+        next_delay: Duration,
+        total_elapsed: Duration,
+        duration: Duration,
+    ) -> Self {
+        DelayContext {
+            attempt,
+            next_delay,
+            total_elapsed,
+            duration,
+            attempt_duration: Duration::ZERO,
+        }
+    }
+
+    /// Sets how long the failed attempt took, mostly useful to test notify hooks.
+    pub const fn with_attempt_duration(mut self, attempt_duration: Duration) -> Self {
+        self.attempt_duration = attempt_duration;
+        self
+    }
+}
+
+/// Notify hook that does nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoNotify;
+
+impl<E> Notify<E> for NoNotify {
+    fn notify(&mut self, _err: &E, _duration: Duration) {}
+}
+
+// tuples of up to 4 hooks, see `Notify`
+macro_rules! impl_notify_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<E, $($name: Notify<E>),+> Notify<E> for ($($name,)+) {
+            fn notify(&mut self, err: &E, duration: Duration) {
+                $(self.$index.notify(err, duration);)+
+            }
+
+            fn notify_attempt(&mut self, err: &E, attempt: usize, duration: Duration) {
+                $(self.$index.notify_attempt(err, attempt, duration);)+
+            }
+
+            fn notify_with(&mut self, err: &E, context: &DelayContext) {
+                $(self.$index.notify_with(err, context);)+
+            }
+
+            // ready once every hook is, hooks that are already ready stay ready
+            fn poll_notified(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+                let mut ready = true;
+                $(ready &= self.$index.poll_notified(cx).is_ready();)+
+                if ready {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }
+
+            fn exhausted(&mut self, err: &E, context: &RetryContext) {
+                $(self.$index.exhausted(err, context);)+
+            }
+        }
+    };
+}
+
+impl_notify_tuple!(A 0, B 1);
+impl_notify_tuple!(A 0, B 1, C 2);
+impl_notify_tuple!(A 0, B 1, C 2, D 3);
+
+/// Notify adapter adding a callback invoked once the retry future gives up,
+/// as in `FnMut(&E, &RetryContext)`, see [`Notify::exhausted`].
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{OnExhausted, Retry, RetryContext, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let notify = OnExhausted::new(
+///     |err: &std::io::Error, duration| println!("{err}, retrying after {duration:?}"),
+///     |err: &std::io::Error, context: &RetryContext| {
+///         eprintln!("giving up after {} attempts: {err}", context.attempts);
+///     },
+/// );
+///
+/// Retry::spawn_notify(FixedInterval::from_millis(10).take(3), action, notify).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OnExhausted<N, F> {
+    notify: N,
+    f: F,
+}
+
+impl<N, F> OnExhausted<N, F> {
+    /// Wraps `notify`, calling `f` with the final error once the retry future gives up.
+    pub const fn new(notify: N, f: F) -> Self {
+        OnExhausted { notify, f }
+    }
+}
+
+impl<E, N, F> Notify<E> for OnExhausted<N, F>
+where
+    N: Notify<E>,
+    F: FnMut(&E, &RetryContext),
+{
+    fn notify(&mut self, err: &E, duration: Duration) {
+        self.notify.notify(err, duration)
+    }
+
+    fn notify_attempt(&mut self, err: &E, attempt: usize, duration: Duration) {
+        self.notify.notify_attempt(err, attempt, duration)
+    }
+
+    fn notify_with(&mut self, err: &E, context: &DelayContext) {
+        self.notify.notify_with(err, context)
+    }
+
+    fn poll_notified(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.notify.poll_notified(cx)
+    }
+
+    fn exhausted(&mut self, err: &E, context: &RetryContext) {
+        self.notify.exhausted(err, context);
+        (self.f)(err, context)
+    }
+}
+
+/// Notify adapter for callbacks that also want the attempt number,
+/// as in `FnMut(&E, usize, Duration)`.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{NotifyAttempt, Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// let notify = NotifyAttempt::new(|err: &std::io::Error, attempt, duration| {
+///     println!("attempt {attempt} of 4 failed with {err}, retrying with {duration:?}");
+/// });
+///
+/// Retry::spawn_notify(strategy, action, notify).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotifyAttempt<F> {
+    f: F,
+    attempts: usize,
+}
+
+impl<F> NotifyAttempt<F> {
+    /// Wraps a callback receiving the error, the attempt number and the retry duration.
+    pub const fn new(f: F) -> Self {
+        NotifyAttempt { f, attempts: 0 }
+    }
+}
+
+impl<E, F> Notify<E> for NotifyAttempt<F>
+where
+    F: FnMut(&E, usize, Duration),
+{
+    fn notify(&mut self, err: &E, duration: Duration) {
+        // count attempts ourselves when not driven by a retry future
+        self.attempts += 1;
+        (self.f)(err, self.attempts, duration)
+    }
+
+    fn notify_attempt(&mut self, err: &E, attempt: usize, duration: Duration) {
+        self.attempts = attempt;
+        (self.f)(err, attempt, duration)
+    }
+}
+
+/// Notify adapter for callbacks that want the [`DelayContext`], as in
+/// `FnMut(&E, &DelayContext)`, to log the upcoming delay and the elapsed time separately.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{DelayContext, NotifyContext, Retry, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let notify = NotifyContext::new(|err: &std::io::Error, context: &DelayContext| {
+///     println!(
+///         "attempt {} failed with {err} after {:?}, retrying in {:?}",
+///         context.attempt, context.total_elapsed, context.next_delay
+///     );
+/// });
+///
+/// Retry::spawn_notify(ExponentialBackoff::from_millis(10).take(3), action, notify).await
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct NotifyContext<F> {
+    f: F,
+    attempts: usize,
+    started: Option<Instant>,
+}
+
+#[cfg(feature = "std")]
+impl<F> NotifyContext<F> {
+    /// Wraps a callback receiving the error and the context of the upcoming retry.
+    pub const fn new(f: F) -> Self {
+        NotifyContext {
+            f,
+            attempts: 0,
+            started: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, F> Notify<E> for NotifyContext<F>
+where
+    F: FnMut(&E, &DelayContext),
+{
+    fn notify(&mut self, err: &E, duration: Duration) {
+        // track the progress ourselves when not driven by a retry future
+        self.attempts += 1;
+        let started = *self.started.get_or_insert_with(runtime::now);
+        let context =
+            DelayContext::new(self.attempts, duration, runtime::elapsed(started), duration);
+        (self.f)(err, &context)
+    }
+
+    fn notify_with(&mut self, err: &E, context: &DelayContext) {
+        (self.f)(err, context)
+    }
+}
+
+/// Notify adapter for async callbacks, as in `FnMut(&E, Duration) -> impl Future<Output = ()>`.
+///
+/// The returned future is awaited before the retry future starts sleeping. It can't
+/// borrow the error, so copy out whatever it needs first:
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{NotifyAsync, Retry, RetryError};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let (audit, _records) = tokio::sync::mpsc::channel(16);
+/// let notify = NotifyAsync::new(move |err: &std::io::Error, duration| {
+///     let audit = audit.clone();
+///     let record = format!("{err} occurred, retrying with {duration:?}");
+///     async move {
+///         let _ = audit.send(record).await;
+///     }
+/// });
+///
+/// Retry::spawn_notify(FixedInterval::from_millis(10).take(3), action, notify).await
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+pub struct NotifyAsync<F, Fut> {
+    f: F,
+    pending: Option<Pin<Box<Fut>>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<F, Fut> NotifyAsync<F, Fut> {
+    /// Wraps a callback returning the future to await before each retry.
+    pub const fn new(f: F) -> Self {
+        NotifyAsync { f, pending: None }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F, Fut> fmt::Debug for NotifyAsync<F, Fut> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NotifyAsync")
+            .field("pending", &self.pending.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E, F, Fut> Notify<E> for NotifyAsync<F, Fut>
+where
+    F: FnMut(&E, Duration) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    fn notify(&mut self, err: &E, duration: Duration) {
+        self.pending = Some(Box::pin((self.f)(err, duration)));
+    }
+
+    fn poll_notified(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match self.pending.as_mut() {
+            None => Poll::Ready(()),
+            Some(future) => {
+                let poll = future.as_mut().poll(cx);
+                if poll.is_ready() {
+                    self.pending = None;
+                }
+                poll
+            }
+        }
+    }
+}
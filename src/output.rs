@@ -0,0 +1,143 @@
+use std::future::Future;
+use std::iter::{IntoIterator, Iterator};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::condition::Condition;
+use crate::error::Error as RetryError;
+use crate::future::RetryIf;
+use crate::notify::Notify;
+
+/// Action adapter presenting every output of an action as a transient error,
+/// so that the condition of a [`RetryIfOutput`] sees successful values too.
+pub struct OutputAction<A> {
+    action: A,
+}
+
+impl<A: Action> Action for OutputAction<A> {
+    type Future = OutputFuture<A::Future>;
+    type Item = A::Item;
+    type Error = Result<A::Item, A::Error>;
+
+    fn run(&mut self) -> Self::Future {
+        OutputFuture {
+            future: self.action.run(),
+        }
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        // only errors of the wrapped action can be handed over
+        let previous_error = context
+            .previous_error
+            .and_then(|output| output.as_ref().err());
+        let context = AttemptContext::new(context.attempt, previous_error, context.elapsed);
+        OutputFuture {
+            future: self.action.run_with(&context),
+        }
+    }
+}
+
+/// Future of an [`OutputAction`].
+#[pin_project]
+pub struct OutputFuture<F> {
+    #[pin]
+    future: F,
+}
+
+impl<F, T, E> Future for OutputFuture<F>
+where
+    F: Future<Output = Result<T, RetryError<E>>>,
+{
+    type Output = Result<T, RetryError<Result<T, E>>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project().future.poll(cx).map(|output| {
+            Err(match output {
+                Ok(ok) => RetryError::transient(Ok(ok)),
+                Err(RetryError::Permanent(err)) => RetryError::Permanent(Err(err)),
+                Err(RetryError::Transient { err, retry_after }) => RetryError::Transient {
+                    err: Err(err),
+                    retry_after,
+                },
+            })
+        })
+    }
+}
+
+/// Future that drives multiple attempts at an action via a retry strategy, like [`RetryIf`],
+/// with a condition inspecting successful values too, as in `FnMut(&Result<T, E>) -> bool`.
+///
+/// Useful for polling APIs that report progress as a successful value. Permanent errors are
+/// never retried, and once the strategy is exhausted, the last output is returned as is.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{RetryError, RetryIfOutput};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// #[derive(Debug, PartialEq)]
+/// enum Status {
+///     Pending,
+///     Done(u64),
+/// }
+///
+/// # async fn job_status() -> Result<Status, RetryError<std::io::Error>> { Ok(Status::Done(0)) }
+/// # async fn run() -> Result<Status, std::io::Error> {
+/// RetryIfOutput::spawn(
+///     FixedInterval::from_millis(100).take(50),
+///     job_status,
+///     |output: &Result<Status, std::io::Error>| matches!(output, Ok(Status::Pending) | Err(_)),
+///     |_: &Result<Status, std::io::Error>, _| {},
+/// )
+/// .await
+/// # }
+/// ```
+#[pin_project]
+pub struct RetryIfOutput<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<Result<A::Item, A::Error>>,
+    N: Notify<Result<A::Item, A::Error>>,
+{
+    #[pin]
+    retry_if: RetryIf<I, OutputAction<A>, C, N>,
+}
+
+impl<I, A, C, N> RetryIfOutput<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<Result<A::Item, A::Error>>,
+    N: Notify<Result<A::Item, A::Error>>,
+{
+    pub fn spawn<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        action: A,
+        condition: C,
+        notify: N,
+    ) -> RetryIfOutput<I, A, C, N> {
+        RetryIfOutput {
+            retry_if: RetryIf::spawn(strategy, OutputAction { action }, condition, notify),
+        }
+    }
+}
+
+impl<I, A, C, N> Future for RetryIfOutput<I, A, C, N>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+    C: Condition<Result<A::Item, A::Error>>,
+    N: Notify<Result<A::Item, A::Error>>,
+{
+    type Output = Result<A::Item, A::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project().retry_if.poll(cx).map(|result| match result {
+            Ok(ok) | Err(Ok(ok)) => Ok(ok),
+            Err(Err(err)) => Err(err),
+        })
+    }
+}
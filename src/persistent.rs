@@ -0,0 +1,384 @@
+use std::convert::Infallible;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::iter::IntoIterator;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error as RetryError;
+use crate::runtime::{Sleep, FAR_FUTURE};
+
+/// Pending retry of a [`PersistentRetryQueue`], as saved to its [`RetryStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryEntry<P> {
+    /// Id of the entry, unique within its queue.
+    pub id: u64,
+    /// Payload handed to the handler on every attempt.
+    pub payload: P,
+    /// Number of failed attempts so far.
+    pub attempts: usize,
+    /// Wall-clock time of the next attempt.
+    pub next_attempt_at: SystemTime,
+}
+
+/// Storage of the entries of a [`PersistentRetryQueue`].
+///
+/// The queue saves a snapshot of all its entries after every change, and loads them back
+/// when opened, so file, embedded database or key-value store backends fit behind it.
+pub trait RetryStore<P> {
+    /// Error of the store.
+    type Error;
+
+    /// Loads the saved entries, empty if nothing was saved yet.
+    fn load(&mut self) -> Result<Vec<RetryEntry<P>>, Self::Error>;
+
+    /// Replaces the saved entries with `entries`.
+    fn save(&mut self, entries: &[RetryEntry<P>]) -> Result<(), Self::Error>;
+}
+
+/// Store saving the entries as JSON to a file.
+///
+/// Snapshots are written to a temporary file next to it first and then renamed over it, so
+/// a crash while saving keeps the previous snapshot.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store saving to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileStore { path: path.into() }
+    }
+}
+
+impl<P> RetryStore<P> for FileStore
+where
+    P: Serialize + DeserializeOwned,
+{
+    type Error = io::Error;
+
+    fn load(&mut self) -> io::Result<Vec<RetryEntry<P>>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save(&mut self, entries: &[RetryEntry<P>]) -> io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_vec(entries)?)?;
+        fs::rename(tmp, &self.path)
+    }
+}
+
+/// Store keeping the entries in memory, shared between its clones.
+///
+/// Nothing survives the process, but a clone can reopen a queue, which helps testing.
+#[derive(Debug)]
+pub struct MemoryStore<P> {
+    entries: Arc<Mutex<Vec<RetryEntry<P>>>>,
+}
+
+impl<P> MemoryStore<P> {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        MemoryStore {
+            entries: Arc::default(),
+        }
+    }
+}
+
+impl<P> Default for MemoryStore<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Clone for MemoryStore<P> {
+    fn clone(&self) -> Self {
+        MemoryStore {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<P: Clone> RetryStore<P> for MemoryStore<P> {
+    type Error = Infallible;
+
+    fn load(&mut self) -> Result<Vec<RetryEntry<P>>, Infallible> {
+        Ok(self
+            .entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone())
+    }
+
+    fn save(&mut self, entries: &[RetryEntry<P>]) -> Result<(), Infallible> {
+        *self.entries.lock().unwrap_or_else(|err| err.into_inner()) = entries.to_vec();
+        Ok(())
+    }
+}
+
+/// What [`PersistentRetryQueue::process_next`] did with an entry.
+#[derive(Debug)]
+pub enum Processed<P, E> {
+    /// The attempt succeeded, the entry is removed.
+    Succeeded(RetryEntry<P>),
+    /// The attempt failed with a transient error, the entry is retried later.
+    Rescheduled {
+        /// Id of the entry.
+        id: u64,
+        /// Error of the attempt.
+        error: E,
+        /// Wall-clock time of the next attempt.
+        next_attempt_at: SystemTime,
+    },
+    /// The attempt failed with a permanent error or the strategy ran out, the entry is
+    /// removed.
+    Failed(RetryEntry<P>, E),
+}
+
+/// Retry queue surviving process restarts, saving its pending entries to a [`RetryStore`].
+///
+/// Each entry is retried with its own copy of the strategy, the delay after its `n`-th
+/// failed attempt being the `n`-th delay of the strategy, so the schedule resumes where it
+/// was from the saved attempt count and next attempt time. Entries are processed at least
+/// once: an attempt interrupted by a crash runs again after the restart.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{FileStore, PersistentRetryQueue, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn deliver(webhook: String) -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> std::io::Result<()> {
+/// let strategy = ExponentialBackoff::from_millis(1000).take(10);
+/// let mut queue = PersistentRetryQueue::open(FileStore::new("webhooks.json"), strategy)?;
+/// queue.push("https://example.com/hook".to_owned())?;
+///
+/// while let Some(processed) = queue.process_next(deliver).await? {
+///     println!("{processed:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PersistentRetryQueue<P, St, S> {
+    store: St,
+    strategy: S,
+    entries: Vec<RetryEntry<P>>,
+    next_id: u64,
+}
+
+impl<P, St, S> PersistentRetryQueue<P, St, S>
+where
+    P: Clone,
+    St: RetryStore<P>,
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Opens the queue saved in `store`, retrying its entries with `strategy`.
+    pub fn open(mut store: St, strategy: S) -> Result<Self, St::Error> {
+        let entries = store.load()?;
+        let next_id = entries.iter().map(|entry| entry.id + 1).max().unwrap_or(0);
+        Ok(PersistentRetryQueue {
+            store,
+            strategy,
+            entries,
+            next_id,
+        })
+    }
+
+    /// Adds `payload` to the queue for an attempt right away, returns the id of its entry.
+    pub fn push(&mut self, payload: P) -> Result<u64, St::Error> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(RetryEntry {
+            id,
+            payload,
+            attempts: 0,
+            next_attempt_at: SystemTime::now(),
+        });
+        self.store.save(&self.entries)?;
+        Ok(id)
+    }
+
+    /// Removes the entry `id` from the queue, returns its payload if it was pending.
+    pub fn remove(&mut self, id: u64) -> Result<Option<P>, St::Error> {
+        let Some(index) = self.entries.iter().position(|entry| entry.id == id) else {
+            return Ok(None);
+        };
+        let entry = self.entries.remove(index);
+        self.store.save(&self.entries)?;
+        Ok(Some(entry.payload))
+    }
+
+    /// Pending entries, in no particular order.
+    pub fn entries(&self) -> &[RetryEntry<P>] {
+        &self.entries
+    }
+
+    /// Number of pending entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no entry is pending.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Waits for the entry due first and runs `handler` with a copy of its payload.
+    ///
+    /// The entry is removed once it succeeds, fails for good or exhausts the strategy, else
+    /// rescheduled after the next delay of the strategy, or the delay of its
+    /// [`RetryError::Transient`] if given.
+    /// Returns `None` if the queue is empty.
+    pub async fn process_next<F, Fut, E>(
+        &mut self,
+        handler: F,
+    ) -> Result<Option<Processed<P, E>>, St::Error>
+    where
+        F: FnOnce(P) -> Fut,
+        Fut: Future<Output = Result<(), RetryError<E>>>,
+    {
+        let Some(index) = self
+            .entries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| entry.next_attempt_at)
+            .map(|(index, _)| index)
+        else {
+            return Ok(None);
+        };
+        let wait = self.entries[index]
+            .next_attempt_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            Sleep::after(wait).await;
+        }
+
+        let processed = match handler(self.entries[index].payload.clone()).await {
+            Ok(()) => Processed::Succeeded(self.entries.remove(index)),
+            Err(RetryError::Permanent(err)) => Processed::Failed(self.entries.remove(index), err),
+            Err(RetryError::Transient { err, retry_after }) => {
+                let entry = &mut self.entries[index];
+                entry.attempts += 1;
+                // a requested delay replaces the strategy delay, but doesn't extend the strategy
+                let delay = self.strategy.clone().into_iter().nth(entry.attempts - 1);
+                match delay.map(|delay| retry_after.unwrap_or(delay)) {
+                    Some(delay) => {
+                        let now = SystemTime::now();
+                        entry.next_attempt_at = now
+                            .checked_add(delay)
+                            .or_else(|| now.checked_add(FAR_FUTURE))
+                            .unwrap_or(now);
+                        Processed::Rescheduled {
+                            id: entry.id,
+                            error: err,
+                            next_attempt_at: entry.next_attempt_at,
+                        }
+                    }
+                    None => Processed::Failed(self.entries.remove(index), err),
+                }
+            }
+        };
+        self.store.save(&self.entries)?;
+        Ok(Some(processed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn resumes_the_schedule_after_reopening() {
+        let mut store = MemoryStore::new();
+        let strategy = [Duration::from_secs(60)];
+        let mut queue = PersistentRetryQueue::open(store.clone(), strategy).unwrap();
+        queue.push("hook").unwrap();
+
+        let processed = queue
+            .process_next(|_| future::ready(Err(RetryError::transient("down"))))
+            .await
+            .unwrap();
+        assert!(matches!(
+            processed,
+            Some(Processed::Rescheduled { id: 0, .. })
+        ));
+        drop(queue);
+
+        let mut queue = PersistentRetryQueue::open(store.clone(), strategy).unwrap();
+        assert_eq!(queue.entries()[0].attempts, 1);
+        assert_eq!(queue.push("other").unwrap(), 1);
+        assert_eq!(queue.remove(1).unwrap(), Some("other"));
+
+        let processed = queue
+            .process_next(|_| future::ready(Err(RetryError::transient("down"))))
+            .await
+            .unwrap();
+        assert!(matches!(processed, Some(Processed::Failed(entry, "down")) if entry.attempts == 2));
+        assert!(store.load().unwrap().is_empty());
+        assert!(queue
+            .process_next(|_| future::ready(Ok::<_, RetryError<()>>(())))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_does_not_extend_the_strategy() {
+        let strategy = [Duration::from_secs(60)];
+        let mut queue = PersistentRetryQueue::open(MemoryStore::new(), strategy).unwrap();
+        queue.push("hook").unwrap();
+        let busy = || future::ready(Err(RetryError::retry_after("busy", Duration::from_secs(1))));
+
+        let processed = queue.process_next(|_| busy()).await.unwrap();
+        assert!(matches!(processed, Some(Processed::Rescheduled { .. })));
+        let processed = queue.process_next(|_| busy()).await.unwrap();
+        assert!(matches!(processed, Some(Processed::Failed(entry, "busy")) if entry.attempts == 2));
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn huge_delays_reschedule_far_ahead() {
+        let mut queue = PersistentRetryQueue::open(MemoryStore::new(), [Duration::MAX]).unwrap();
+        queue.push("hook").unwrap();
+
+        let before = SystemTime::now();
+        let processed = queue
+            .process_next(|_| future::ready(Err(RetryError::transient("down"))))
+            .await
+            .unwrap();
+        assert!(matches!(processed, Some(Processed::Rescheduled { .. })));
+        assert!(queue.entries()[0].next_attempt_at >= before + FAR_FUTURE);
+    }
+
+    #[test]
+    fn file_store_round_trips_entries() {
+        let path = std::env::temp_dir().join(format!("tokio-retry2-{}.json", std::process::id()));
+        let mut store = FileStore::new(&path);
+        assert_eq!(RetryStore::<String>::load(&mut store).unwrap(), []);
+
+        let entries = [RetryEntry {
+            id: 3,
+            payload: "hook".to_owned(),
+            attempts: 2,
+            next_attempt_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1),
+        }];
+        store.save(&entries).unwrap();
+        let loaded: Vec<RetryEntry<String>> = store.load().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, entries);
+    }
+}
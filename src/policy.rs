@@ -0,0 +1,66 @@
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::builder::RetryBuilder;
+use crate::condition::{Always, Condition};
+use crate::future::Guarded;
+use crate::notify::{NoNotify, Notify};
+
+/// Reusable retry configuration, created by [`RetryBuilder::into_policy`].
+///
+/// A policy bundles the strategy, condition, notify hook and limits of a [`RetryBuilder`]
+/// once, so it can be shared between call sites and cloned freely. Every [`RetryPolicy::run`]
+/// starts from a fresh clone of the strategy and hooks.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use tokio_retry2::{Retry, RetryError, RetryPolicy};
+/// use tokio_retry2::strategy::{ExponentialBackoff, MaxInterval};
+///
+/// async fn fetch(id: u64) -> Result<u64, RetryError<std::io::Error>> {
+///     // do some real-world stuff here...
+/// #   Ok(id)
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), tokio_retry2::RetryFailure<std::io::Error>> {
+/// let policy = Retry::builder()
+///     .strategy(ExponentialBackoff::from_millis(10).max_interval(1_000).take(5))
+///     .condition(|err: &std::io::Error| err.kind() != std::io::ErrorKind::NotFound)
+///     .max_elapsed(Duration::from_secs(10))
+///     .into_policy();
+///
+/// let first = policy.run(|| fetch(1)).await?;
+/// let second = policy.run(|| fetch(2)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy<S, C = Always, N = NoNotify> {
+    builder: RetryBuilder<S, C, N>,
+}
+
+impl<S, C, N> RetryPolicy<S, C, N> {
+    /// Starts retrying `action` with this policy, see [`RetryBuilder::build`].
+    pub fn run<A>(&self, action: A) -> Guarded<S::IntoIter, A, C, N>
+    where
+        S: IntoIterator<Item = Duration> + Clone,
+        A: Action,
+        C: Condition<A::Error> + Clone,
+        N: Notify<A::Error> + Clone,
+    {
+        self.builder.clone().build(action)
+    }
+
+    /// Returns the builder of this policy, to derive a tweaked policy from it.
+    pub fn into_builder(self) -> RetryBuilder<S, C, N> {
+        self.builder
+    }
+}
+
+impl<S, C, N> From<RetryBuilder<S, C, N>> for RetryPolicy<S, C, N> {
+    fn from(builder: RetryBuilder<S, C, N>) -> Self {
+        RetryPolicy { builder }
+    }
+}
@@ -0,0 +1,309 @@
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use crate::condition::{Always, Condition, RetryContext};
+use crate::runtime::{Instant, Sleep, Time};
+
+/// Connection manager of a pool retrying the creation and recycling of its connections with a
+/// strategy of this crate, so acquiring connections to a flaky database backs off instead of
+/// failing or hammering it right away.
+///
+/// Wraps a `deadpool::managed::Manager` with the `deadpool` feature, retrying `create` and
+/// the backend errors of `recycle`, and a `bb8::ManageConnection` with the `bb8` feature,
+/// retrying `connect` and `is_valid`. Every error satisfying the condition is retried, all of
+/// them by default.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "deadpool")]
+/// # fn pool<M: deadpool::managed::Manager<Error = std::io::Error>>(manager: M) {
+/// use std::io::{Error, ErrorKind};
+/// use deadpool::managed::Pool;
+/// use tokio_retry2::RetryManager;
+/// use tokio_retry2::strategy::ExponentialBackoff;
+///
+/// let manager = RetryManager::new(manager, ExponentialBackoff::from_millis(10).take(5))
+///     .condition(|err: &Error| err.kind() == ErrorKind::ConnectionRefused);
+/// let pool: Pool<_> = Pool::builder(manager).build().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryManager<M, S, C = Always> {
+    manager: M,
+    strategy: S,
+    condition: C,
+}
+
+impl<M, S> RetryManager<M, S>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Wraps `manager`, retrying its errors according to `strategy`.
+    pub const fn new(manager: M, strategy: S) -> Self {
+        RetryManager {
+            manager,
+            strategy,
+            condition: Always,
+        }
+    }
+}
+
+impl<M, S, C> RetryManager<M, S, C> {
+    /// Sets the condition deciding which errors of the manager are retried.
+    pub fn condition<T>(self, condition: T) -> RetryManager<M, S, T> {
+        RetryManager {
+            manager: self.manager,
+            strategy: self.strategy,
+            condition,
+        }
+    }
+
+    /// Returns the wrapped manager.
+    pub const fn get_ref(&self) -> &M {
+        &self.manager
+    }
+
+    /// Returns the wrapped manager, dropping the strategy and condition.
+    pub fn into_inner(self) -> M {
+        self.manager
+    }
+
+    fn attempts(&self) -> Attempts<S::IntoIter, C>
+    where
+        S: IntoIterator<Item = Duration> + Clone,
+        C: Clone,
+    {
+        let time = Time::default();
+        Attempts {
+            strategy: self.strategy.clone().into_iter(),
+            condition: self.condition.clone(),
+            attempts: 0,
+            started: time.now(),
+            time,
+        }
+    }
+}
+
+/// Progress of retrying one operation of a manager.
+struct Attempts<I, C> {
+    strategy: I,
+    condition: C,
+    attempts: usize,
+    started: Instant,
+    time: Time,
+}
+
+impl<I, C> Attempts<I, C>
+where
+    I: Iterator<Item = Duration>,
+{
+    /// Returns the delay before retrying after `err`, `None` to give up.
+    fn retry<E>(&mut self, err: &E) -> Option<Duration>
+    where
+        C: Condition<E>,
+    {
+        self.attempts += 1;
+        let context = RetryContext::new(self.attempts, self.time.elapsed(self.started));
+        if !self.condition.should_retry_with(err, &context) {
+            return None;
+        }
+        let delay = self.strategy.next();
+        #[cfg(feature = "tracing")]
+        match delay {
+            Some(delay) => tracing::info!(
+                attempt = self.attempts,
+                delay_ms = delay.as_millis(),
+                "retrying connection manager after error"
+            ),
+            None => tracing::warn!("ending retry: strategy reached its limit"),
+        }
+        delay
+    }
+
+    fn sleep(&self, delay: Duration) -> Sleep {
+        self.time.sleep_until(self.time.after(delay))
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl<M, S, C> deadpool::managed::Manager for RetryManager<M, S, C>
+where
+    M: deadpool::managed::Manager,
+    S: IntoIterator<Item = Duration> + Clone + Send + Sync,
+    S::IntoIter: Send,
+    C: Condition<M::Error> + Clone + Send + Sync,
+{
+    type Type = M::Type;
+    type Error = M::Error;
+
+    async fn create(&self) -> Result<Self::Type, Self::Error> {
+        let mut attempts = self.attempts();
+        loop {
+            let err = match self.manager.create().await {
+                Ok(obj) => return Ok(obj),
+                Err(err) => err,
+            };
+            match attempts.retry(&err) {
+                Some(delay) => attempts.sleep(delay).await,
+                None => return Err(err),
+            }
+        }
+    }
+
+    async fn recycle(
+        &self,
+        obj: &mut Self::Type,
+        metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<Self::Error> {
+        let mut attempts = self.attempts();
+        loop {
+            let err = match self.manager.recycle(obj, metrics).await {
+                Err(deadpool::managed::RecycleError::Backend(err)) => err,
+                result => return result,
+            };
+            match attempts.retry(&err) {
+                Some(delay) => attempts.sleep(delay).await,
+                None => return Err(deadpool::managed::RecycleError::Backend(err)),
+            }
+        }
+    }
+
+    fn detach(&self, obj: &mut Self::Type) {
+        self.manager.detach(obj);
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl<M, S, C> bb8::ManageConnection for RetryManager<M, S, C>
+where
+    M: bb8::ManageConnection,
+    S: IntoIterator<Item = Duration> + Clone + Send + Sync + 'static,
+    S::IntoIter: Send,
+    C: Condition<M::Error> + Clone + Send + Sync + 'static,
+{
+    type Connection = M::Connection;
+    type Error = M::Error;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        let mut attempts = self.attempts();
+        loop {
+            let err = match self.manager.connect().await {
+                Ok(conn) => return Ok(conn),
+                Err(err) => err,
+            };
+            match attempts.retry(&err) {
+                Some(delay) => attempts.sleep(delay).await,
+                None => return Err(err),
+            }
+        }
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        let mut attempts = self.attempts();
+        loop {
+            let err = match self.manager.is_valid(conn).await {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            match attempts.retry(&err) {
+                Some(delay) => attempts.sleep(delay).await,
+                None => return Err(err),
+            }
+        }
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        self.manager.has_broken(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::strategy::FixedInterval;
+
+    /// Manager failing its first `failures` connections.
+    #[derive(Debug, Default)]
+    struct Flaky {
+        failures: usize,
+        calls: AtomicUsize,
+    }
+
+    impl Flaky {
+        fn call(&self) -> Result<usize, &'static str> {
+            match self.calls.fetch_add(1, Ordering::SeqCst) {
+                call if call < self.failures => Err("refused"),
+                call => Ok(call),
+            }
+        }
+    }
+
+    #[cfg(feature = "deadpool")]
+    impl deadpool::managed::Manager for Flaky {
+        type Type = usize;
+        type Error = &'static str;
+
+        async fn create(&self) -> Result<usize, &'static str> {
+            self.call()
+        }
+
+        async fn recycle(
+            &self,
+            _obj: &mut usize,
+            _metrics: &deadpool::managed::Metrics,
+        ) -> deadpool::managed::RecycleResult<&'static str> {
+            self.call().map(drop).map_err(Into::into)
+        }
+    }
+
+    #[cfg(feature = "bb8")]
+    impl bb8::ManageConnection for Flaky {
+        type Connection = usize;
+        type Error = &'static str;
+
+        async fn connect(&self) -> Result<usize, &'static str> {
+            self.call()
+        }
+
+        async fn is_valid(&self, _conn: &mut usize) -> Result<(), &'static str> {
+            self.call().map(drop)
+        }
+
+        fn has_broken(&self, _conn: &mut usize) -> bool {
+            false
+        }
+    }
+
+    fn manager(failures: usize) -> RetryManager<Flaky, std::iter::Take<FixedInterval>> {
+        let flaky = Flaky {
+            failures,
+            ..Flaky::default()
+        };
+        RetryManager::new(flaky, FixedInterval::from_millis(10).take(2))
+    }
+
+    #[cfg(feature = "deadpool")]
+    #[tokio::test(start_paused = true)]
+    async fn deadpool_creation_backs_off() {
+        use deadpool::managed::Manager;
+
+        assert_eq!(manager(2).create().await, Ok(2));
+        assert_eq!(manager(3).create().await, Err("refused"));
+        let rejecting = manager(1).condition(|err: &&str| *err != "refused");
+        assert_eq!(rejecting.create().await, Err("refused"));
+        assert_eq!(rejecting.get_ref().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "bb8")]
+    #[tokio::test(start_paused = true)]
+    async fn bb8_connection_backs_off() {
+        use bb8::ManageConnection;
+
+        let started = tokio::time::Instant::now();
+        let manager = manager(2);
+        assert_eq!(manager.connect().await, Ok(2));
+        assert_eq!(started.elapsed(), Duration::from_millis(20));
+        assert_eq!(manager.is_valid(&mut 0).await, Ok(()));
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::runtime::{self, deadline_after, Instant};
+
+/// Rate limiter for attempts, shared by retry futures, see
+/// [`RetryIf::with_rate_limit`](crate::RetryIf::with_rate_limit).
+///
+/// Allows bursts of up to `attempts` attempts, and `attempts` per `period` on average,
+/// as a leaky bucket. Cloning a limiter shares it.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use tokio_retry2::RateLimiter;
+///
+/// // at most 10 attempts per second
+/// let limiter = RateLimiter::new(10, Duration::from_secs(1));
+/// # let _ = limiter;
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<Limiter>,
+}
+
+#[derive(Debug)]
+struct Limiter {
+    interval: Duration,
+    burst: Duration,
+    // theoretical arrival time of the next attempt
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `attempts` attempts per `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `attempts` is zero.
+    pub fn new(attempts: u32, period: Duration) -> Self {
+        assert!(attempts > 0, "`attempts` must not be zero");
+        let interval = period / attempts;
+        RateLimiter {
+            inner: Arc::new(Limiter {
+                interval,
+                burst: period - interval,
+                next: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Reserves a slot for an attempt at `at` or later, returning the instant it may start.
+    pub fn reserve_at(&self, at: Instant) -> Instant {
+        let limiter = &self.inner;
+        let mut next = limiter.next.lock().unwrap_or_else(|err| err.into_inner());
+        let tat = next.map_or(at, |next| next.max(at));
+        let start = at.max(tat.checked_sub(limiter.burst).unwrap_or(at));
+        *next = Some(deadline_after(tat, limiter.interval));
+        start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_attempts_after_burst() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1));
+        let now = runtime::now();
+
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now + Duration::from_millis(500));
+        assert_eq!(limiter.reserve_at(now), now + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(1));
+        let now = runtime::now();
+
+        assert_eq!(limiter.reserve_at(now), now);
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve_at(later), later);
+    }
+}
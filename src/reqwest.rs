@@ -0,0 +1,85 @@
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use ::reqwest::{Client, RequestBuilder, Response, StatusCode};
+
+use crate::error::Error as RetryError;
+use crate::future::Retry;
+use crate::http::retry_after_from_header;
+use crate::transient::TransientError;
+
+/// Returns whether a request failing with `err` is worth retrying: connection failures,
+/// timeouts, `429 Too Many Requests` and server errors.
+///
+/// Usable as a condition of [`RetryIf`](crate::RetryIf) directly.
+pub fn is_retryable_reqwest_error(err: &::reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout() || err.status().is_some_and(is_retryable_status)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Sends the request of `builder` with `client`, retrying it according to `strategy` while
+/// it fails with [`is_retryable_reqwest_error`] or gets a retryable status.
+///
+/// Responses with a `Retry-After` header are retried after the delay it requests instead of
+/// the strategy delay. Once the strategy is exhausted, the last response is returned as is,
+/// so its status can still be inspected. Requests with a streaming body can't be copied, they
+/// are sent once.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::retry_request;
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn run() -> Result<(), reqwest::Error> {
+/// let client = reqwest::Client::new();
+/// let strategy = ExponentialBackoff::from_millis(100).take(3);
+/// let response = retry_request(&client, client.get("https://example.com/users"), strategy).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry_request<T>(
+    client: &Client,
+    builder: RequestBuilder,
+    strategy: T,
+) -> Result<Response, ::reqwest::Error>
+where
+    T: IntoIterator<Item = Duration>,
+{
+    let request = builder.build()?;
+    if request.try_clone().is_none() {
+        return client.execute(request).await;
+    }
+
+    let action = || {
+        let client = client.clone();
+        let request = request
+            .try_clone()
+            .expect("the request body is not a stream");
+        async move {
+            match client.execute(request).await {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    let retry_after = retry_after_from_header(response.headers());
+                    Err(RetryError::Transient {
+                        err: Ok(response),
+                        retry_after,
+                    })
+                }
+                Ok(response) => Ok(response),
+                Err(err) if is_retryable_reqwest_error(&err) => {
+                    Err(RetryError::transient(Err(err)))
+                }
+                Err(err) => Err(RetryError::permanent(Err(err))),
+            }
+        }
+    };
+    // an exhausted retryable status still is a response
+    Retry::spawn(strategy, action).await.or_else(|last| last)
+}
+
+impl TransientError for ::reqwest::Error {
+    /// Same as [`is_retryable_reqwest_error`].
+    fn is_transient(&self) -> bool {
+        is_retryable_reqwest_error(self)
+    }
+}
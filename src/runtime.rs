@@ -0,0 +1,637 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Once};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+/// Point in time used by the retry futures, [`std::time::Instant`] except on
+/// wasm32-unknown-unknown, where the standard one panics and `web_time::Instant` stands in.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+/// Point in time used by the retry futures, [`std::time::Instant`] except on
+/// wasm32-unknown-unknown, where the standard one panics and `web_time::Instant` stands in.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use web_time::Instant;
+
+/// Timer of an async runtime, used by the retry futures to sleep between attempts.
+///
+/// The retry futures sleep with the [`DefaultSleeper`], which picks one of the enabled timers
+/// for every sleep. A [`SleeperClock`] pins a future to another one through
+/// [`RetryIf::with_clock`](crate::RetryIf::with_clock).
+pub trait Sleeper {
+    /// Future resolving once the deadline has passed, its output is ignored.
+    type Sleep: Future;
+
+    /// Returns a future that sleeps until `deadline`.
+    fn sleep_until(deadline: Instant) -> Self::Sleep;
+
+    /// Moves the deadline of `sleep`, reusing it for the next attempt.
+    ///
+    /// Replaces it with a new sleep by default.
+    fn reset(mut sleep: Pin<&mut Self::Sleep>, deadline: Instant) {
+        sleep.set(Self::sleep_until(deadline));
+    }
+}
+
+/// Sleeper backed by the tokio timer, which panics outside a tokio runtime.
+#[cfg(feature = "rt-tokio")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokioSleeper;
+
+#[cfg(feature = "rt-tokio")]
+impl Sleeper for TokioSleeper {
+    type Sleep = tokio::time::Sleep;
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        tokio::time::sleep_until(deadline.into())
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn reset(sleep: Pin<&mut Self::Sleep>, deadline: Instant) {
+        sleep.reset(deadline.into());
+    }
+
+    // the clock is `web_time` there, tokio only gets the remaining time
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        tokio::time::sleep(deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Sleeper backed by the async-std timer.
+#[cfg(feature = "rt-async-std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AsyncStdSleeper;
+
+#[cfg(feature = "rt-async-std")]
+impl Sleeper for AsyncStdSleeper {
+    type Sleep = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        let duration = deadline.saturating_duration_since(Instant::now());
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// Sleeper backed by the `async-io` timer driving smol.
+#[cfg(feature = "rt-smol")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SmolSleeper;
+
+#[cfg(feature = "rt-smol")]
+impl Sleeper for SmolSleeper {
+    type Sleep = async_io::Timer;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        async_io::Timer::after(deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Sleeper backed by `futures-timer`, which runs its own timer thread and works with any
+/// executor, e.g. `futures::executor::block_on`.
+#[cfg(feature = "rt-futures-timer")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FuturesTimerSleeper;
+
+#[cfg(feature = "rt-futures-timer")]
+impl Sleeper for FuturesTimerSleeper {
+    type Sleep = futures_timer::Delay;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        futures_timer::Delay::new(deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Sleeper backed by the browser timers through `gloo-timers`, for wasm32-unknown-unknown.
+///
+/// Its sleep is not `Send`, like every other JavaScript value.
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WasmSleeper;
+
+#[cfg(feature = "wasm")]
+impl Sleeper for WasmSleeper {
+    type Sleep = gloo_timers::future::TimeoutFuture;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        let millis = deadline
+            .saturating_duration_since(Instant::now())
+            .as_millis();
+        gloo_timers::future::TimeoutFuture::new(u32::try_from(millis).unwrap_or(u32::MAX))
+    }
+}
+
+/// Sleeper backed by a timer thread of this crate, started on its first sleep. It needs no
+/// runtime feature and works with any executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThreadSleeper;
+
+impl Sleeper for ThreadSleeper {
+    type Sleep = ThreadSleep;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        ThreadSleep {
+            deadline,
+            key: None,
+        }
+    }
+}
+
+/// Sleep of the [`ThreadSleeper`].
+#[derive(Debug)]
+pub struct ThreadSleep {
+    deadline: Instant,
+    key: Option<u64>,
+}
+
+/// Wakers of the pending thread sleeps, by deadline.
+struct Timers {
+    queue: Mutex<BTreeMap<(Instant, u64), Waker>>,
+    changed: Condvar,
+}
+
+static TIMERS: Timers = Timers {
+    queue: Mutex::new(BTreeMap::new()),
+    changed: Condvar::new(),
+};
+static TIMER_THREAD: Once = Once::new();
+static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+
+impl Timers {
+    fn run(&self) {
+        let mut queue = self
+            .queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        loop {
+            let now = Instant::now();
+            let mut due = Vec::new();
+            while queue
+                .first_key_value()
+                .is_some_and(|(&(deadline, _), _)| deadline <= now)
+            {
+                due.extend(queue.pop_first().map(|(_, waker)| waker));
+            }
+            if !due.is_empty() {
+                drop(queue);
+                due.into_iter().for_each(Waker::wake);
+                queue = self
+                    .queue
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                continue;
+            }
+            queue = match queue.first_key_value() {
+                Some((&(deadline, _), _)) => {
+                    self.changed
+                        .wait_timeout(queue, deadline.saturating_duration_since(now))
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            };
+        }
+    }
+}
+
+impl Future for ThreadSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        TIMER_THREAD.call_once(|| {
+            thread::Builder::new()
+                .name("tokio-retry2-timer".into())
+                .spawn(|| TIMERS.run())
+                .expect("failed to spawn the timer thread");
+        });
+        let deadline = self.deadline;
+        let key = *self
+            .key
+            .get_or_insert_with(|| NEXT_KEY.fetch_add(1, Ordering::Relaxed));
+        let mut queue = TIMERS
+            .queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let first = queue
+            .first_key_value()
+            .is_none_or(|(&first, _)| (deadline, key) <= first);
+        queue.insert((deadline, key), cx.waker().clone());
+        if first {
+            TIMERS.changed.notify_one();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for ThreadSleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let mut queue = TIMERS
+                .queue
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            queue.remove(&(self.deadline, key));
+        }
+    }
+}
+
+/// Sleeper used by the retry futures, picking one of the enabled timers for every sleep, so
+/// enabling more runtime features never breaks the ones already in use.
+///
+/// The `wasm` timer is always used on wasm32 targets. Elsewhere the tokio timer is used inside
+/// a tokio runtime with `rt-tokio`, else the first enabled of `rt-async-std`, `rt-smol` and
+/// `rt-futures-timer`, else the [`ThreadSleeper`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DefaultSleeper;
+
+/// Sleep of the [`DefaultSleeper`], on the timer it picked.
+#[pin_project]
+pub struct DefaultSleep {
+    #[pin]
+    timer: Picked,
+}
+
+#[pin_project(project = PickedProj)]
+enum Picked {
+    #[cfg(feature = "rt-tokio")]
+    Tokio(#[pin] tokio::time::Sleep),
+    #[cfg(feature = "rt-async-std")]
+    AsyncStd(<AsyncStdSleeper as Sleeper>::Sleep),
+    #[cfg(feature = "rt-smol")]
+    Smol(async_io::Timer),
+    #[cfg(feature = "rt-futures-timer")]
+    FuturesTimer(#[pin] futures_timer::Delay),
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    Wasm(#[pin] gloo_timers::future::TimeoutFuture),
+    Thread(ThreadSleep),
+}
+
+/// Whether the current thread runs in a tokio runtime, whose clock and timer are used then.
+#[cfg(feature = "rt-tokio")]
+fn in_tokio() -> bool {
+    tokio::runtime::Handle::try_current().is_ok()
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn pick(deadline: Instant) -> Picked {
+    Picked::Wasm(WasmSleeper::sleep_until(deadline))
+}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+fn pick(deadline: Instant) -> Picked {
+    #[cfg(feature = "rt-tokio")]
+    if in_tokio() {
+        return Picked::Tokio(TokioSleeper::sleep_until(deadline));
+    }
+    pick_outside_tokio(deadline)
+}
+
+#[cfg(feature = "rt-async-std")]
+fn pick_outside_tokio(deadline: Instant) -> Picked {
+    Picked::AsyncStd(AsyncStdSleeper::sleep_until(deadline))
+}
+
+#[cfg(all(feature = "rt-smol", not(feature = "rt-async-std")))]
+fn pick_outside_tokio(deadline: Instant) -> Picked {
+    Picked::Smol(SmolSleeper::sleep_until(deadline))
+}
+
+#[cfg(all(
+    feature = "rt-futures-timer",
+    not(feature = "rt-async-std"),
+    not(feature = "rt-smol")
+))]
+fn pick_outside_tokio(deadline: Instant) -> Picked {
+    Picked::FuturesTimer(FuturesTimerSleeper::sleep_until(deadline))
+}
+
+#[cfg(not(any(
+    feature = "rt-async-std",
+    feature = "rt-smol",
+    feature = "rt-futures-timer"
+)))]
+fn pick_outside_tokio(deadline: Instant) -> Picked {
+    Picked::Thread(ThreadSleeper::sleep_until(deadline))
+}
+
+impl Sleeper for DefaultSleeper {
+    type Sleep = DefaultSleep;
+
+    fn sleep_until(deadline: Instant) -> Self::Sleep {
+        DefaultSleep {
+            timer: pick(deadline),
+        }
+    }
+
+    fn reset(sleep: Pin<&mut Self::Sleep>, deadline: Instant) {
+        let mut timer = sleep.project().timer;
+        match timer.as_mut().project() {
+            #[cfg(feature = "rt-tokio")]
+            PickedProj::Tokio(sleep) => TokioSleeper::reset(sleep, deadline),
+            _ => timer.set(pick(deadline)),
+        }
+    }
+}
+
+impl fmt::Debug for DefaultSleep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DefaultSleep").finish_non_exhaustive()
+    }
+}
+
+impl Future for DefaultSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match self.project().timer.project() {
+            #[cfg(feature = "rt-tokio")]
+            PickedProj::Tokio(sleep) => sleep.poll(cx),
+            #[cfg(feature = "rt-async-std")]
+            PickedProj::AsyncStd(sleep) => sleep.as_mut().poll(cx),
+            #[cfg(feature = "rt-smol")]
+            PickedProj::Smol(timer) => Pin::new(timer).poll(cx).map(drop),
+            #[cfg(feature = "rt-futures-timer")]
+            PickedProj::FuturesTimer(delay) => delay.poll(cx),
+            #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+            PickedProj::Wasm(timeout) => timeout.poll(cx),
+            PickedProj::Thread(sleep) => Pin::new(sleep).poll(cx),
+        }
+    }
+}
+
+/// Current instant of the default time source: the tokio clock inside a tokio runtime with
+/// `rt-tokio`, so a paused test clock applies, else the system clock.
+pub(crate) fn now() -> Instant {
+    #[cfg(all(
+        feature = "rt-tokio",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    if in_tokio() {
+        return tokio::time::Instant::now().into_std();
+    }
+    Instant::now()
+}
+
+/// Time passed since `since` on the default time source, see [`now`].
+pub(crate) fn elapsed(since: Instant) -> Duration {
+    now().saturating_duration_since(since)
+}
+
+/// Sleep of a [`Clock`], see [`Clock::sleep_until`].
+pub type ClockSleep = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
+
+/// Source of time replacing the [`DefaultSleeper`] and the system clock of a retry future,
+/// see [`RetryIf::with_clock`](crate::RetryIf::with_clock).
+///
+/// Lets tests drive delays and deadlines by hand, like
+/// [`MockClock`](crate::test_util::MockClock) with the `test-util` feature, without pausing
+/// the tokio clock of the whole runtime.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current instant of this clock.
+    fn now(&self) -> Instant;
+
+    /// Returns a future that sleeps until this clock reaches `deadline`.
+    fn sleep_until(&self, deadline: Instant) -> ClockSleep;
+}
+
+/// [`Clock`] sleeping with the sleeper `S`, which pins a retry future to one timer, e.g.
+/// `with_clock(SleeperClock::<SmolSleeper>::new())` with `rt-smol`.
+///
+/// Its time is the one of the retry futures, see [`DefaultSleeper`].
+pub struct SleeperClock<S>(PhantomData<fn() -> S>);
+
+impl<S> SleeperClock<S> {
+    /// Returns a clock sleeping with `S`.
+    pub const fn new() -> Self {
+        SleeperClock(PhantomData)
+    }
+}
+
+impl<S> Default for SleeperClock<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Clone for SleeperClock<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for SleeperClock<S> {}
+
+impl<S> fmt::Debug for SleeperClock<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SleeperClock")
+            .field(&std::any::type_name::<S>())
+            .finish()
+    }
+}
+
+impl<S> Clock for SleeperClock<S>
+where
+    S: Sleeper,
+    S::Sleep: Send + Sync + 'static,
+{
+    fn now(&self) -> Instant {
+        now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> ClockSleep {
+        let sleep = S::sleep_until(deadline);
+        Box::pin(async move {
+            sleep.await;
+        })
+    }
+}
+
+impl<T: Clock + ?Sized> Clock for Arc<T> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> ClockSleep {
+        (**self).sleep_until(deadline)
+    }
+}
+
+/// About 30 years, the latest deadline of a sleep.
+pub(crate) const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+/// Instant `delay` after `instant`, capped to a far future so huge delays don't overflow.
+pub(crate) fn deadline_after(instant: Instant, delay: Duration) -> Instant {
+    instant
+        .checked_add(delay)
+        .or_else(|| instant.checked_add(FAR_FUTURE))
+        .unwrap_or(instant)
+}
+
+/// Time of a retry future: its [`Clock`] if it has one, else the default time source, see
+/// [`now`] and the [`DefaultSleeper`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Time {
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl Time {
+    pub(crate) fn new(clock: Arc<dyn Clock>) -> Self {
+        Time { clock: Some(clock) }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self, since: Instant) -> Duration {
+        self.now().saturating_duration_since(since)
+    }
+
+    /// Instant `delay` from now, see [`deadline_after`].
+    pub(crate) fn after(&self, delay: Duration) -> Instant {
+        deadline_after(self.now(), delay)
+    }
+
+    pub(crate) fn sleep_until(&self, deadline: Instant) -> Sleep {
+        match &self.clock {
+            Some(clock) => Sleep {
+                deadline,
+                inner: Timer::Clock(clock.sleep_until(deadline), clock.clone()),
+            },
+            None => Sleep::until(deadline),
+        }
+    }
+}
+
+/// Sleep of the [`DefaultSleeper`] or of a [`Clock`] remembering its deadline, so it can be
+/// inspected and reset.
+#[pin_project]
+pub(crate) struct Sleep {
+    deadline: Instant,
+    #[pin]
+    inner: Timer,
+}
+
+#[pin_project(project = TimerProj)]
+enum Timer {
+    Runtime(#[pin] <DefaultSleeper as Sleeper>::Sleep),
+    Clock(ClockSleep, Arc<dyn Clock>),
+}
+
+impl Sleep {
+    pub(crate) fn until(deadline: Instant) -> Self {
+        Sleep {
+            deadline,
+            inner: Timer::Runtime(DefaultSleeper::sleep_until(deadline)),
+        }
+    }
+
+    pub(crate) fn after(duration: Duration) -> Self {
+        Self::until(deadline_after(now(), duration))
+    }
+
+    pub(crate) const fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub(crate) fn reset(self: Pin<&mut Self>, deadline: Instant) {
+        let this = self.project();
+        *this.deadline = deadline;
+        match this.inner.project() {
+            TimerProj::Runtime(sleep) => DefaultSleeper::reset(sleep, deadline),
+            TimerProj::Clock(sleep, clock) => *sleep = clock.sleep_until(deadline),
+        }
+    }
+}
+
+impl fmt::Debug for Sleep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sleep")
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match self.project().inner.project() {
+            TimerProj::Runtime(sleep) => sleep.poll(cx).map(drop),
+            TimerProj::Clock(sleep, _) => sleep.as_mut().poll(cx),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rt-tokio"))]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn reset_moves_the_deadline() {
+        let started = now();
+        let mut sleep = Box::pin(Sleep::after(Duration::from_secs(10)));
+        sleep.as_mut().reset(started + Duration::from_millis(10));
+        sleep.as_mut().await;
+
+        assert_eq!(sleep.deadline(), started + Duration::from_millis(10));
+        assert_eq!(elapsed(started), Duration::from_millis(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn huge_delays_sleep_until_a_far_future() {
+        let sleep = Sleep::after(Duration::MAX);
+        assert_eq!(sleep.deadline(), now() + FAR_FUTURE);
+        assert_eq!(Time::default().after(Duration::MAX), sleep.deadline());
+    }
+
+    #[test]
+    fn sleeps_outside_a_tokio_runtime() {
+        let started = Instant::now();
+        let sleep = Sleep::after(Duration::from_millis(20));
+        // no tokio timer in this runtime, the sleep was created outside of it anyway
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(sleep);
+
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn thread_sleeps_wake_before_later_ones() {
+        let started = Instant::now();
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let long = ThreadSleeper::sleep_until(started + Duration::from_secs(60));
+                let short = ThreadSleeper::sleep_until(started + Duration::from_millis(20));
+                tokio::select! {
+                    biased;
+                    () = long => unreachable!(),
+                    () = short => {}
+                }
+            });
+
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert!(started.elapsed() < Duration::from_secs(60));
+    }
+}
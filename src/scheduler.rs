@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+use crate::action::Action;
+use crate::boxed::BoxedRetryPolicy;
+use crate::handle::RetryHandle;
+use crate::stats::RetryStats;
+
+/// Registry of retry jobs running in the background on the tokio runtime.
+///
+/// Every job retries an action with a [`BoxedRetryPolicy`] under a unique key. Jobs can be
+/// inspected, paused, retried right away or cancelled by key through a [`JobHandle`], and
+/// report their outcome on the [`JobEvents`] feed returned with the scheduler.
+///
+/// Dropping the scheduler doesn't cancel its jobs, they keep running until done.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{BoxedRetryPolicy, JobOutcome, RetryError, RetryScheduler};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn deliver(webhook: u64) -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() {
+/// let (scheduler, mut events) = RetryScheduler::new();
+///
+/// for webhook in 0..10 {
+///     let policy = BoxedRetryPolicy::new(ExponentialBackoff::from_millis(100).take(8));
+///     scheduler
+///         .schedule(webhook, policy, move || deliver(webhook))
+///         .expect("unique webhook ids");
+/// }
+///
+/// // the feed ends once the scheduler is dropped and every job is done
+/// drop(scheduler);
+/// while let Some(event) = events.recv().await {
+///     if let JobOutcome::Failed(err) = event.outcome {
+///         eprintln!("giving up on webhook {}: {err}", event.key);
+///     }
+/// }
+/// # }
+/// ```
+pub struct RetryScheduler<K, T, E> {
+    jobs: Arc<Mutex<HashMap<K, Job>>>,
+    next_id: Arc<AtomicU64>,
+    events: mpsc::UnboundedSender<JobEvent<K, T, E>>,
+}
+
+struct Job {
+    id: u64,
+    handle: RetryHandle,
+    stats: Arc<RetryStats>,
+    abort: AbortHandle,
+}
+
+impl<K, T, E> RetryScheduler<K, T, E>
+where
+    K: Eq + Hash + Clone + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    /// Creates an empty scheduler and the feed of its job events.
+    pub fn new() -> (Self, JobEvents<K, T, E>) {
+        let (events, receiver) = mpsc::unbounded_channel();
+        let scheduler = RetryScheduler {
+            jobs: Arc::default(),
+            next_id: Arc::default(),
+            events,
+        };
+        (scheduler, JobEvents { receiver })
+    }
+
+    /// Starts retrying `action` with `policy` under `key` in a background task.
+    ///
+    /// Fails if a job with the same key is still running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn schedule<A>(
+        &self,
+        key: K,
+        policy: BoxedRetryPolicy<E>,
+        action: A,
+    ) -> Result<JobHandle<K, T, E>, DuplicateJob<K>>
+    where
+        A: Action<Item = T, Error = E> + Send + 'static,
+        A::Future: Send + 'static,
+    {
+        // held until the job is registered, so it can't finish before
+        let mut jobs = self.lock();
+        if jobs.contains_key(&key) {
+            return Err(DuplicateJob(key));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let retry = policy.retry(action);
+        let handle = retry.handle();
+        let stats = retry.stats();
+
+        let task = {
+            let scheduler = self.clone();
+            let key = key.clone();
+            tokio::spawn(async move {
+                let result = retry.await;
+                if let Some(job) = scheduler.remove(&key, Some(id)) {
+                    let outcome = match result {
+                        Ok(item) => JobOutcome::Succeeded(item),
+                        Err(err) => JobOutcome::Failed(err),
+                    };
+                    scheduler.send(key, &job, outcome);
+                }
+            })
+        };
+        jobs.insert(
+            key.clone(),
+            Job {
+                id,
+                handle,
+                stats,
+                abort: task.abort_handle(),
+            },
+        );
+        Ok(JobHandle {
+            scheduler: self.clone(),
+            key,
+            id,
+        })
+    }
+
+    /// Returns a handle to the running job scheduled under `key`, if any.
+    pub fn job(&self, key: &K) -> Option<JobHandle<K, T, E>> {
+        let id = self.lock().get(key)?.id;
+        Some(JobHandle {
+            scheduler: self.clone(),
+            key: key.clone(),
+            id,
+        })
+    }
+
+    /// Cancels the job scheduled under `key`, returns `false` if no job is running under it.
+    ///
+    /// Reports [`JobOutcome::Cancelled`] on the event feed.
+    pub fn cancel(&self, key: &K) -> bool {
+        self.cancel_job(key, None)
+    }
+
+    /// Number of running jobs.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no job is running.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the keys of the running jobs, in no particular order.
+    pub fn keys(&self) -> Vec<K> {
+        self.lock().keys().cloned().collect()
+    }
+
+    fn cancel_job(&self, key: &K, id: Option<u64>) -> bool {
+        let Some(job) = self.remove(key, id) else {
+            return false;
+        };
+        job.abort.abort();
+        self.send(key.clone(), &job, JobOutcome::Cancelled);
+        true
+    }
+
+    fn remove(&self, key: &K, id: Option<u64>) -> Option<Job> {
+        let mut jobs = self.lock();
+        match jobs.get(key) {
+            Some(job) if id.is_none_or(|id| job.id == id) => jobs.remove(key),
+            _ => None,
+        }
+    }
+
+    fn with_job<R>(&self, key: &K, id: u64, f: impl FnOnce(&Job) -> R) -> Option<R> {
+        self.lock().get(key).filter(|job| job.id == id).map(f)
+    }
+
+    fn send(&self, key: K, job: &Job, outcome: JobOutcome<T, E>) {
+        // nobody listens anymore when the feed is dropped
+        let _ = self.events.send(JobEvent {
+            key,
+            attempts: job.stats.attempts(),
+            outcome,
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, Job>> {
+        self.jobs.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<K, T, E> Clone for RetryScheduler<K, T, E> {
+    fn clone(&self) -> Self {
+        RetryScheduler {
+            jobs: self.jobs.clone(),
+            next_id: self.next_id.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<K, T, E> fmt::Debug for RetryScheduler<K, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let jobs = self.jobs.lock().map_or(0, |jobs| jobs.len());
+        f.debug_struct("RetryScheduler")
+            .field("jobs", &jobs)
+            .finish()
+    }
+}
+
+/// Handle to a job of a [`RetryScheduler`].
+///
+/// Once the job is done or cancelled, the handle is stale: its status is `None` and its
+/// controls have no effect, even if a new job reuses the key.
+pub struct JobHandle<K, T, E> {
+    scheduler: RetryScheduler<K, T, E>,
+    key: K,
+    id: u64,
+}
+
+impl<K, T, E> JobHandle<K, T, E>
+where
+    K: Eq + Hash + Clone + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    /// Key the job was scheduled under.
+    pub const fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the status of the job, `None` once it is done or cancelled.
+    pub fn status(&self) -> Option<JobStatus> {
+        self.scheduler
+            .with_job(&self.key, self.id, |job| JobStatus {
+                attempts: job.stats.attempts(),
+                backoff: job.stats.backoff(),
+                paused: job.handle.is_paused(),
+            })
+    }
+
+    /// Returns `true` once the job is done or cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.status().is_none()
+    }
+
+    /// Skips the sleep before the next attempt, see [`RetryHandle::retry_now`].
+    ///
+    /// Returns `false` if the job is done or cancelled.
+    pub fn retry_now(&self) -> bool {
+        self.control(RetryHandle::retry_now)
+    }
+
+    /// Halts further attempts of the job, see [`RetryHandle::pause`].
+    ///
+    /// Returns `false` if the job is done or cancelled.
+    pub fn pause(&self) -> bool {
+        self.control(RetryHandle::pause)
+    }
+
+    /// Lets a paused job continue, see [`RetryHandle::resume`].
+    ///
+    /// Returns `false` if the job is done or cancelled.
+    pub fn resume(&self) -> bool {
+        self.control(RetryHandle::resume)
+    }
+
+    /// Cancels the job, see [`RetryScheduler::cancel`].
+    ///
+    /// Returns `false` if the job is done or cancelled.
+    pub fn cancel(&self) -> bool {
+        self.scheduler.cancel_job(&self.key, Some(self.id))
+    }
+
+    fn control(&self, f: impl FnOnce(&RetryHandle)) -> bool {
+        self.scheduler
+            .with_job(&self.key, self.id, |job| f(&job.handle))
+            .is_some()
+    }
+}
+
+impl<K: Clone, T, E> Clone for JobHandle<K, T, E> {
+    fn clone(&self) -> Self {
+        JobHandle {
+            scheduler: self.scheduler.clone(),
+            key: self.key.clone(),
+            id: self.id,
+        }
+    }
+}
+
+impl<K: fmt::Debug, T, E> fmt::Debug for JobHandle<K, T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JobHandle")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Status of a running job, see [`JobHandle::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct JobStatus {
+    /// Number of attempts started so far, including a running one.
+    pub attempts: usize,
+    /// Delay of the sleep currently running before the next attempt, if any.
+    pub backoff: Option<Duration>,
+    /// Whether the job is paused.
+    pub paused: bool,
+}
+
+/// Feed of the events of a [`RetryScheduler`], one per finished or cancelled job.
+#[derive(Debug)]
+pub struct JobEvents<K, T, E> {
+    receiver: mpsc::UnboundedReceiver<JobEvent<K, T, E>>,
+}
+
+impl<K, T, E> JobEvents<K, T, E> {
+    /// Waits for the next event, `None` once every scheduler clone and [`JobHandle`] is dropped
+    /// and every job is done.
+    pub async fn recv(&mut self) -> Option<JobEvent<K, T, E>> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next event if one is ready.
+    pub fn try_recv(&mut self) -> Option<JobEvent<K, T, E>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Event of a job that finished or was cancelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobEvent<K, T, E> {
+    /// Key the job was scheduled under.
+    pub key: K,
+    /// Number of attempts the job started.
+    pub attempts: usize,
+    /// How the job ended.
+    pub outcome: JobOutcome<T, E>,
+}
+
+/// How a job of a [`RetryScheduler`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobOutcome<T, E> {
+    /// The action succeeded.
+    Succeeded(T),
+    /// The action failed with a permanent error, or its retries ran out.
+    Failed(E),
+    /// The job was cancelled.
+    Cancelled,
+}
+
+/// Error returned by [`RetryScheduler::schedule`] when a job already runs under the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateJob<K>(pub K);
+
+impl<K> fmt::Display for DuplicateJob<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a job is already scheduled under this key")
+    }
+}
+
+impl<K: fmt::Debug> error::Error for DuplicateJob<K> {}
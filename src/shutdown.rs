@@ -0,0 +1,90 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+type ShutdownFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Shutdown signal of retry futures, see [`RetryIf::until`](crate::RetryIf::until).
+///
+/// Clones share the future, so one signal can stop every retry built from a policy. Whoever
+/// polls it drives the future, and every waiting retry is woken once it resolves.
+#[derive(Clone)]
+pub(crate) struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    future: Mutex<Option<ShutdownFuture>>,
+    wakers: Arc<Wakers>,
+}
+
+#[derive(Default)]
+struct Wakers(Mutex<Vec<Waker>>);
+
+impl Wake for Wakers {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let wakers = std::mem::take(&mut *self.0.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Shutdown {
+    pub(crate) fn new(future: impl Future<Output = ()> + Send + 'static) -> Self {
+        Shutdown {
+            inner: Arc::new(Inner {
+                future: Mutex::new(Some(Box::pin(future))),
+                wakers: Arc::default(),
+            }),
+        }
+    }
+
+    /// Polls the shared future, resolving once it has resolved for any clone.
+    pub(crate) fn poll(&self, cx: &mut Context) -> Poll<()> {
+        let mut future = self.inner.future.lock().unwrap();
+        let Some(running) = future.as_mut() else {
+            return Poll::Ready(());
+        };
+        {
+            let mut wakers = self.inner.wakers.0.lock().unwrap();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        let waker = Waker::from(self.inner.wakers.clone());
+        match running.as_mut().poll(&mut Context::from_waker(&waker)) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(()) => {
+                *future = None;
+                self.inner.wakers.wake_by_ref();
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let signaled = self.inner.future.lock().unwrap().is_none();
+        f.debug_struct("Shutdown")
+            .field("signaled", &signaled)
+            .finish()
+    }
+}
+
+/// What a [`Guarded`](crate::Guarded) retry future does with a running attempt once its
+/// shutdown signal resolves, see [`Guarded::on_shutdown`](crate::Guarded::on_shutdown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnShutdown {
+    /// Lets the running attempt finish: its success or permanent error is returned as usual,
+    /// a transient error is not retried.
+    #[default]
+    FinishAttempt,
+    /// Drops the running attempt right away.
+    Abort,
+}
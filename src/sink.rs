@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::iter::IntoIterator;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_sink::Sink;
+use pin_project::pin_project;
+
+use crate::condition::{Always, Condition};
+use crate::error::Error as RetryError;
+use crate::runtime::Sleep;
+
+#[pin_project(project = SinkStateProj)]
+enum SinkState<Fut> {
+    Idle,
+    Sending(#[pin] Fut),
+    Sleeping(#[pin] Sleep),
+}
+
+/// [`Sink`] sending every item with a closure, retrying failed sends according to a strategy.
+///
+/// The pending item is kept until it is sent, so `send` is called with a copy of it on every
+/// attempt, and each item starts the strategy over. Transient errors rejected by the condition,
+/// permanent errors and exhausted strategies fail the sink with the last error.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{RetryError, RetrySink};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn publish(event: String) -> Result<(), std::io::Error> { Ok(()) }
+/// let events = RetrySink::new(ExponentialBackoff::from_millis(10).take(5), |event: String| async move {
+///     publish(event).await.map_err(RetryError::transient)
+/// })
+/// .when(|err: &std::io::Error| err.kind() != std::io::ErrorKind::PermissionDenied);
+/// ```
+#[pin_project]
+pub struct RetrySink<T, S, F, Fut, C = Always>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    policy: S,
+    send: F,
+    condition: C,
+    item: Option<T>,
+    strategy: Option<S::IntoIter>,
+    #[pin]
+    state: SinkState<Fut>,
+}
+
+impl<T, S, F, Fut, E> RetrySink<T, S, F, Fut>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<(), RetryError<E>>>,
+{
+    /// Creates a sink sending items with `send`, retrying every transient error.
+    pub fn new(strategy: S, send: F) -> Self {
+        RetrySink {
+            policy: strategy,
+            send,
+            condition: Always,
+            item: None,
+            strategy: None,
+            state: SinkState::Idle,
+        }
+    }
+}
+
+impl<T, S, F, Fut, C> RetrySink<T, S, F, Fut, C>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Only retries the transient errors satisfying `condition`.
+    pub fn when<D>(self, condition: D) -> RetrySink<T, S, F, Fut, D> {
+        RetrySink {
+            policy: self.policy,
+            send: self.send,
+            condition,
+            item: self.item,
+            strategy: self.strategy,
+            state: self.state,
+        }
+    }
+}
+
+impl<T, S, F, Fut, C, E> RetrySink<T, S, F, Fut, C>
+where
+    T: Clone,
+    S: IntoIterator<Item = Duration> + Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<(), RetryError<E>>>,
+    C: Condition<E>,
+{
+    /// Drives the pending item until it is sent or given up on.
+    fn drive(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        let mut this = self.project();
+        loop {
+            match this.state.as_mut().project() {
+                SinkStateProj::Idle => return Poll::Ready(Ok(())),
+                SinkStateProj::Sending(future) => {
+                    let (err, retry_after) = match future.poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Ok(())) => {
+                            *this.item = None;
+                            this.state.set(SinkState::Idle);
+                            return Poll::Ready(Ok(()));
+                        }
+                        Poll::Ready(Err(RetryError::Permanent(err))) => (err, None),
+                        Poll::Ready(Err(RetryError::Transient { err, retry_after })) => {
+                            (err, Some(retry_after))
+                        }
+                    };
+                    let delay = retry_after
+                        .filter(|_| this.condition.should_retry(&err))
+                        .and_then(|retry_after| {
+                            let strategy = this.strategy.as_mut()?;
+                            strategy.next().map(|delay| retry_after.unwrap_or(delay))
+                        });
+                    match delay {
+                        Some(delay) => this.state.set(SinkState::Sleeping(Sleep::after(delay))),
+                        None => {
+                            *this.item = None;
+                            this.state.set(SinkState::Idle);
+                            return Poll::Ready(Err(err));
+                        }
+                    }
+                }
+                SinkStateProj::Sleeping(sleep) => {
+                    if sleep.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    let item = this
+                        .item
+                        .clone()
+                        .expect("the pending item is kept while retrying");
+                    this.state.set(SinkState::Sending((this.send)(item)));
+                }
+            }
+        }
+    }
+}
+
+impl<T, S, F, Fut, C, E> Sink<T> for RetrySink<T, S, F, Fut, C>
+where
+    T: Clone,
+    S: IntoIterator<Item = Duration> + Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<(), RetryError<E>>>,
+    C: Condition<E>,
+{
+    type Error = E;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        self.drive(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), E> {
+        let mut this = self.project();
+        *this.strategy = Some(this.policy.clone().into_iter());
+        let future = (this.send)(item.clone());
+        *this.item = Some(item);
+        this.state.set(SinkState::Sending(future));
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        self.drive(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), E>> {
+        self.drive(cx)
+    }
+}
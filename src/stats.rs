@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::runtime::{self, Instant};
+
+const NONE: u64 = u64::MAX;
+
+/// Live statistics of a retry future, shared through the handle returned by
+/// [`RetryIf::stats`](crate::RetryIf::stats) and updated while the future runs.
+#[derive(Debug)]
+pub struct RetryStats {
+    started: Instant,
+    attempts: AtomicUsize,
+    last_error: AtomicU64,
+    slept: AtomicU64,
+    backoff: AtomicU64,
+}
+
+impl RetryStats {
+    pub(crate) fn new(started: Instant, attempts: usize) -> Self {
+        RetryStats {
+            started,
+            attempts: AtomicUsize::new(attempts),
+            last_error: AtomicU64::new(NONE),
+            slept: AtomicU64::new(0),
+            backoff: AtomicU64::new(NONE),
+        }
+    }
+
+    /// Number of attempts started so far, including a running one.
+    pub fn attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Instant the last attempt failed at, if any attempt failed.
+    pub fn last_error_at(&self) -> Option<Instant> {
+        load_duration(&self.last_error).map(|elapsed| self.started + elapsed)
+    }
+
+    /// Total time slept between attempts, excluding a running sleep.
+    pub fn total_slept(&self) -> Duration {
+        Duration::from_nanos(self.slept.load(Ordering::Relaxed))
+    }
+
+    /// Delay of the sleep currently running before the next attempt, if any.
+    pub fn backoff(&self) -> Option<Duration> {
+        load_duration(&self.backoff)
+    }
+
+    pub(crate) fn record_attempt(&self, attempt: usize) {
+        self.attempts.store(attempt, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_error(&self) {
+        store_duration(&self.last_error, runtime::elapsed(self.started));
+    }
+
+    pub(crate) fn record_sleep(&self, delay: Duration) {
+        store_duration(&self.backoff, delay);
+    }
+
+    pub(crate) fn record_wake(&self) {
+        let delay = self.backoff.swap(NONE, Ordering::Relaxed);
+        if delay != NONE {
+            self.slept.fetch_add(delay, Ordering::Relaxed);
+        }
+    }
+}
+
+fn load_duration(value: &AtomicU64) -> Option<Duration> {
+    match value.load(Ordering::Relaxed) {
+        NONE => None,
+        nanos => Some(Duration::from_nanos(nanos)),
+    }
+}
+
+fn store_duration(value: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(NONE - 1);
+    value.store(nanos.min(NONE - 1), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_sleeps() {
+        let stats = RetryStats::new(Instant::now(), 1);
+        assert_eq!(stats.backoff(), None);
+        assert_eq!(stats.last_error_at(), None);
+
+        stats.record_error();
+        stats.record_sleep(Duration::from_millis(30));
+        assert_eq!(stats.backoff(), Some(Duration::from_millis(30)));
+        assert!(stats.last_error_at().is_some());
+
+        stats.record_wake();
+        stats.record_attempt(2);
+        assert_eq!(stats.backoff(), None);
+        assert_eq!(stats.total_slept(), Duration::from_millis(30));
+        assert_eq!(stats.attempts(), 2);
+    }
+
+    #[test]
+    fn saturates_long_delays() {
+        let stats = RetryStats::new(Instant::now(), 1);
+        stats.record_sleep(Duration::MAX);
+        assert!(stats.backoff().is_some());
+    }
+}
@@ -0,0 +1,236 @@
+use std::future::Future;
+use std::iter::Iterator;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::error::Error as RetryError;
+
+/// A retry strategy sharing one delay between many retry futures, adapted to the observed
+/// failures and successes: every retry multiplies the delay by a factor, every success
+/// shortens it by a fixed step (AIMD).
+///
+/// The delay grows quickly while a dependency keeps failing, and shrinks back once it
+/// recovers, instead of each retry future restarting its own curve. Cloning an adaptive
+/// backoff shares its delay. Successes are recorded by the actions wrapped with
+/// [`AdaptiveBackoff::track`], or by hand with [`AdaptiveBackoff::record_success`].
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::AdaptiveBackoff;
+/// # use std::time::Duration;
+/// # async fn call() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let backoff = AdaptiveBackoff::new(Duration::from_millis(10), Duration::from_secs(10));
+///
+/// // every call shares the delay of `backoff`
+/// Retry::spawn(backoff.clone().take(5), backoff.track(call)).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdaptiveBackoff {
+    inner: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    delay: AtomicU64,
+    min: u64,
+    max: u64,
+    factor: f64,
+    decrease: u64,
+}
+
+impl AdaptiveBackoff {
+    /// Constructs an adaptive backoff starting at `min`, doubling up to `max` on every retry and
+    /// shrinking by `min` on every success.
+    pub fn new(min: Duration, max: Duration) -> AdaptiveBackoff {
+        let (min, max) = (nanos(min), nanos(max).max(nanos(min)));
+        AdaptiveBackoff {
+            inner: Arc::new(Shared {
+                delay: AtomicU64::new(min),
+                min,
+                max,
+                factor: 2.,
+                decrease: min,
+            }),
+        }
+    }
+
+    /// Sets the factor the delay is multiplied by on every retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is lower than 1 or not finite, or if the backoff is shared already.
+    pub fn factor(mut self, factor: f64) -> AdaptiveBackoff {
+        assert!(
+            factor.is_finite() && factor >= 1.,
+            "`factor` must be a finite number of at least 1"
+        );
+        self.shared_mut().factor = factor;
+        self
+    }
+
+    /// Sets the step the delay shrinks by on every success.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backoff is shared already.
+    pub fn decrease(mut self, decrease: Duration) -> AdaptiveBackoff {
+        self.shared_mut().decrease = nanos(decrease);
+        self
+    }
+
+    /// Current delay, the one the next retry sleeps for.
+    pub fn current(&self) -> Duration {
+        Duration::from_nanos(self.inner.delay.load(Ordering::Relaxed))
+    }
+
+    /// Records a success, shrinking the delay by its step down to the minimum.
+    pub fn record_success(&self) {
+        let shared = &self.inner;
+        let _ = shared
+            .delay
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |delay| {
+                Some(delay.saturating_sub(shared.decrease).max(shared.min))
+            });
+    }
+
+    /// Wraps `action`, recording a success whenever one of its attempts succeeds.
+    pub fn track<A: Action>(&self, action: A) -> Tracked<A> {
+        Tracked {
+            action,
+            backoff: self.clone(),
+        }
+    }
+
+    fn shared_mut(&mut self) -> &mut Shared {
+        Arc::get_mut(&mut self.inner).expect("the adaptive backoff is configured before sharing")
+    }
+}
+
+impl Iterator for AdaptiveBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let shared = &self.inner;
+        let previous = shared
+            .delay
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |delay| {
+                let grown = (delay as f64 * shared.factor).min(shared.max as f64) as u64;
+                Some(grown.clamp(shared.min, shared.max))
+            })
+            .unwrap_or_else(|delay| delay);
+        Some(Duration::from_nanos(previous))
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Action adapter recording its successes on an [`AdaptiveBackoff`], see
+/// [`AdaptiveBackoff::track`].
+#[derive(Debug, Clone)]
+pub struct Tracked<A> {
+    action: A,
+    backoff: AdaptiveBackoff,
+}
+
+impl<A: Action> Action for Tracked<A> {
+    type Future = TrackedFuture<A::Future>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        TrackedFuture {
+            future: self.action.run(),
+            backoff: self.backoff.clone(),
+        }
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        TrackedFuture {
+            future: self.action.run_with(context),
+            backoff: self.backoff.clone(),
+        }
+    }
+}
+
+/// Future of a [`Tracked`] attempt.
+#[pin_project]
+#[derive(Debug)]
+pub struct TrackedFuture<F> {
+    #[pin]
+    future: F,
+    backoff: AdaptiveBackoff,
+}
+
+impl<F, T, E> Future for TrackedFuture<F>
+where
+    F: Future<Output = Result<T, RetryError<E>>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        let poll = this.future.poll(cx);
+        if let Poll::Ready(Ok(_)) = &poll {
+            this.backoff.record_success();
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn grows_multiplicatively_up_to_max() {
+        let mut s = AdaptiveBackoff::new(millis(10), millis(50));
+
+        assert_eq!(s.next(), Some(millis(10)));
+        assert_eq!(s.next(), Some(millis(20)));
+        assert_eq!(s.next(), Some(millis(40)));
+        assert_eq!(s.next(), Some(millis(50)));
+        assert_eq!(s.next(), Some(millis(50)));
+    }
+
+    #[test]
+    fn shrinks_additively_on_success_down_to_min() {
+        let s = AdaptiveBackoff::new(millis(10), millis(1000))
+            .factor(4.)
+            .decrease(millis(15));
+        let mut shared = s.clone();
+        shared.next();
+        shared.next();
+        assert_eq!(s.current(), millis(160));
+
+        s.record_success();
+        assert_eq!(s.current(), millis(145));
+        for _ in 0..20 {
+            s.record_success();
+        }
+        assert_eq!(shared.next(), Some(millis(10)));
+    }
+
+    #[tokio::test]
+    async fn tracked_actions_record_successes() {
+        let mut s = AdaptiveBackoff::new(millis(10), millis(1000));
+        s.next();
+        let mut action = s.track(|| std::future::ready(Ok::<_, RetryError<()>>(())));
+
+        action.run().await.unwrap();
+        assert_eq!(s.current(), millis(10));
+    }
+}
@@ -0,0 +1,131 @@
+use std::iter::Iterator;
+use std::time::Duration;
+
+use backoff::backoff::Backoff;
+
+/// A retry strategy following a policy of the `backoff` crate, like a tuned
+/// `backoff::ExponentialBackoff`, so it can drive these retry futures.
+///
+/// The strategy ends once the policy returns `None`, e.g. after its `max_elapsed_time`.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::BackoffStrategy;
+/// # async fn action() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let policy = backoff::ExponentialBackoffBuilder::new()
+///     .with_initial_interval(Duration::from_millis(50))
+///     .with_max_elapsed_time(Some(Duration::from_secs(30)))
+///     .build();
+/// Retry::spawn(BackoffStrategy::new(policy), action).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BackoffStrategy<B> {
+    policy: B,
+}
+
+impl<B: Backoff> BackoffStrategy<B> {
+    /// Constructs a strategy following `policy`.
+    pub const fn new(policy: B) -> BackoffStrategy<B> {
+        BackoffStrategy { policy }
+    }
+
+    /// Resets the policy to its initial state.
+    pub fn reset(&mut self) {
+        self.policy.reset();
+    }
+
+    /// Returns the wrapped policy.
+    pub fn into_inner(self) -> B {
+        self.policy
+    }
+}
+
+impl<B: Backoff> Iterator for BackoffStrategy<B> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        self.policy.next_backoff()
+    }
+}
+
+/// A policy of the `backoff` crate following a retry strategy of this crate, for code still
+/// retrying with the `backoff` crate.
+///
+/// Resetting the policy starts over from a clone of the strategy it was created with.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// use backoff::backoff::Backoff;
+/// use tokio_retry2::strategy::{FibonacciBackoff, StrategyBackoff};
+///
+/// let mut policy = StrategyBackoff::new(FibonacciBackoff::from_millis(10).take(2));
+/// assert_eq!(policy.next_backoff(), Some(Duration::from_millis(10)));
+/// assert_eq!(policy.next_backoff(), Some(Duration::from_millis(10)));
+/// assert_eq!(policy.next_backoff(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StrategyBackoff<S> {
+    initial: S,
+    strategy: S,
+}
+
+impl<S> StrategyBackoff<S>
+where
+    S: Iterator<Item = Duration> + Clone,
+{
+    /// Constructs a policy following `strategy`.
+    pub fn new(strategy: S) -> StrategyBackoff<S> {
+        StrategyBackoff {
+            initial: strategy.clone(),
+            strategy,
+        }
+    }
+}
+
+impl<S> Backoff for StrategyBackoff<S>
+where
+    S: Iterator<Item = Duration> + Clone,
+{
+    fn reset(&mut self) {
+        self.strategy = self.initial.clone();
+    }
+
+    fn next_backoff(&mut self) -> Option<Duration> {
+        self.strategy.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::ExponentialBackoff;
+
+    #[test]
+    fn follows_the_backoff_policy() {
+        let policy = backoff::ExponentialBackoffBuilder::new()
+            .with_initial_interval(Duration::from_millis(100))
+            .with_multiplier(2.)
+            .with_randomization_factor(0.)
+            .build();
+        let mut s = BackoffStrategy::new(policy);
+
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        s.reset();
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn reset_starts_the_strategy_over() {
+        let mut policy = StrategyBackoff::new(ExponentialBackoff::from_millis(2).take(2));
+
+        assert_eq!(policy.next_backoff(), Some(Duration::from_millis(2)));
+        assert_eq!(policy.next_backoff(), Some(Duration::from_millis(4)));
+        assert_eq!(policy.next_backoff(), None);
+        policy.reset();
+        assert_eq!(policy.next_backoff(), Some(Duration::from_millis(2)));
+    }
+}
@@ -1,15 +1,21 @@
-use std::iter::Iterator;
-use tokio::time::Duration;
+use core::error;
+use core::fmt;
+use core::iter::Iterator;
+use core::time::Duration;
 
 /// A retry strategy driven by exponential back-off.
 ///
-/// The power corresponds to the number of past attempts.
+/// The power corresponds to the number of past attempts. Delays saturate at the maximum delay
+/// if any, else at [`Duration::MAX`], instead of overflowing.
 #[derive(Debug, Clone)]
 pub struct ExponentialBackoff {
-    current: u64,
+    // `None` once the power overflowed
+    current: Option<u64>,
+    exponent: u32,
     base: u64,
     factor: u64,
     max_delay: Option<Duration>,
+    max_exponent: Option<u32>,
 }
 
 impl ExponentialBackoff {
@@ -20,10 +26,12 @@ impl ExponentialBackoff {
     /// where `n` denotes the number of past attempts.
     pub const fn from_millis(base: u64) -> Self {
         ExponentialBackoff {
-            current: base,
+            current: Some(base),
+            exponent: 1,
             base,
             factor: 1u64,
             max_delay: None,
+            max_exponent: None,
         }
     }
 
@@ -48,18 +56,164 @@ impl ExponentialBackoff {
         self.max_delay = Some(Duration::from_millis(duration));
         self
     }
+
+    /// Apply a maximum exponent. Delays stop growing once the base is raised to this power,
+    /// so that later attempts keep retrying at `base^max_exponent * factor` milliseconds.
+    pub const fn max_exponent(mut self, exponent: u32) -> ExponentialBackoff {
+        self.max_exponent = Some(exponent);
+        self
+    }
+
+    /// Returns a builder validating the parameters of the strategy, for strategies built
+    /// from user-supplied configuration.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// use tokio_retry2::strategy::{ExponentialBackoff, InvalidPolicy};
+    ///
+    /// let strategy = ExponentialBackoff::builder()
+    ///     .base(2)
+    ///     .factor(1000)
+    ///     .max_delay(Duration::from_secs(30))
+    ///     .build();
+    /// assert!(strategy.is_ok());
+    ///
+    /// let strategy = ExponentialBackoff::builder().base(2).factor(0).build();
+    /// assert_eq!(strategy.unwrap_err(), InvalidPolicy::ZeroFactor);
+    /// ```
+    pub const fn builder() -> ExponentialBackoffBuilder {
+        ExponentialBackoffBuilder {
+            base: 0,
+            factor: 1,
+            max_delay: None,
+            max_exponent: None,
+        }
+    }
+}
+
+/// Builder of an [`ExponentialBackoff`] rejecting parameters giving zero, constant or
+/// overflowing delays, see [`ExponentialBackoff::builder`].
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoffBuilder {
+    base: u64,
+    factor: u64,
+    max_delay: Option<Duration>,
+    max_exponent: Option<u32>,
+}
+
+impl ExponentialBackoffBuilder {
+    /// Sets the base in milliseconds, see [`ExponentialBackoff::from_millis`]. Must be at
+    /// least 2.
+    pub const fn base(mut self, base: u64) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Sets the multiplicative factor, see [`ExponentialBackoff::factor`]. Must not be zero.
+    pub const fn factor(mut self, factor: u64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    /// Sets the maximum delay, see [`ExponentialBackoff::max_delay`]. Must not be below the
+    /// first delay.
+    pub const fn max_delay(mut self, duration: Duration) -> Self {
+        self.max_delay = Some(duration);
+        self
+    }
+
+    /// Sets the maximum exponent, see [`ExponentialBackoff::max_exponent`]. Must be at least
+    /// 2, as the first delay already raises the base to the power of 1.
+    pub const fn max_exponent(mut self, exponent: u32) -> Self {
+        self.max_exponent = Some(exponent);
+        self
+    }
+
+    /// Validates the parameters and builds the strategy.
+    pub fn build(self) -> Result<ExponentialBackoff, InvalidPolicy> {
+        if self.base < 2 {
+            return Err(InvalidPolicy::BaseTooSmall(self.base));
+        }
+        if self.factor == 0 {
+            return Err(InvalidPolicy::ZeroFactor);
+        }
+        let first_delay = self
+            .base
+            .checked_mul(self.factor)
+            .ok_or(InvalidPolicy::Overflow)?;
+        let first_delay = Duration::from_millis(first_delay);
+        if let Some(max_delay) = self.max_delay {
+            if max_delay < first_delay {
+                return Err(InvalidPolicy::MaxDelayBelowBase {
+                    max_delay,
+                    first_delay,
+                });
+            }
+        }
+        if let Some(exponent) = self.max_exponent {
+            if exponent < 2 {
+                return Err(InvalidPolicy::MaxExponentTooSmall(exponent));
+            }
+        }
+        let mut strategy = ExponentialBackoff::from_millis(self.base).factor(self.factor);
+        strategy.max_delay = self.max_delay;
+        strategy.max_exponent = self.max_exponent;
+        Ok(strategy)
+    }
+}
+
+/// Invalid parameters of a strategy, returned by [`ExponentialBackoffBuilder::build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidPolicy {
+    /// The base is below 2, giving zero or constant delays.
+    BaseTooSmall(u64),
+    /// The factor is zero, giving zero delays.
+    ZeroFactor,
+    /// The first delay, `base * factor` milliseconds, overflows.
+    Overflow,
+    /// The maximum delay is below the first delay, so every delay is the maximum.
+    MaxDelayBelowBase {
+        /// The maximum delay.
+        max_delay: Duration,
+        /// The first delay of the strategy.
+        first_delay: Duration,
+    },
+    /// The maximum exponent is below 2, giving constant delays.
+    MaxExponentTooSmall(u32),
+}
+
+impl fmt::Display for InvalidPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidPolicy::BaseTooSmall(base) => write!(f, "base {base} is below 2"),
+            InvalidPolicy::ZeroFactor => f.write_str("factor is zero"),
+            InvalidPolicy::Overflow => f.write_str("first delay overflows"),
+            InvalidPolicy::MaxDelayBelowBase {
+                max_delay,
+                first_delay,
+            } => write!(
+                f,
+                "max delay {max_delay:?} is below the first delay {first_delay:?}"
+            ),
+            InvalidPolicy::MaxExponentTooSmall(exponent) => {
+                write!(f, "max exponent {exponent} is below 2")
+            }
+        }
+    }
 }
 
+impl error::Error for InvalidPolicy {}
+
 impl Iterator for ExponentialBackoff {
     type Item = Duration;
 
     fn next(&mut self) -> Option<Duration> {
-        // set delay duration by applying factor
-        let duration = if let Some(duration) = self.current.checked_mul(self.factor) {
-            Duration::from_millis(duration)
-        } else {
-            Duration::from_millis(u64::MAX)
-        };
+        // set delay duration by applying factor, saturating on overflow
+        let duration = self
+            .current
+            .and_then(|current| current.checked_mul(self.factor))
+            .map_or(Duration::MAX, Duration::from_millis);
 
         // check if we reached max delay
         if let Some(ref max_delay) = self.max_delay {
@@ -70,10 +224,14 @@ impl Iterator for ExponentialBackoff {
             }
         }
 
-        if let Some(next) = self.current.checked_mul(self.base) {
-            self.current = next;
-        } else {
-            self.current = u64::MAX;
+        if self
+            .max_exponent
+            .is_none_or(|max_exponent| self.exponent < max_exponent)
+        {
+            self.current = self
+                .current
+                .and_then(|current| current.checked_mul(self.base));
+            self.exponent = self.exponent.saturating_add(1);
         }
 
         Some(duration)
@@ -107,8 +265,37 @@ mod tests {
         let mut s = ExponentialBackoff::from_millis(u64::MAX - 1);
 
         assert_eq!(s.next(), Some(Duration::from_millis(u64::MAX - 1)));
-        assert_eq!(s.next(), Some(Duration::from_millis(u64::MAX)));
-        assert_eq!(s.next(), Some(Duration::from_millis(u64::MAX)));
+        assert_eq!(s.next(), Some(Duration::MAX));
+        assert_eq!(s.next(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn saturates_when_the_factor_overflows() {
+        let mut s = ExponentialBackoff::from_millis(u64::MAX / 2).factor(3);
+
+        assert_eq!(s.next(), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn saturates_at_max_delay_after_many_attempts() {
+        let max_delay = Duration::from_secs(30);
+        let s = ExponentialBackoff::from_millis(2)
+            .factor(1000)
+            .max_delay(max_delay);
+
+        let delays: Vec<_> = s.take(10_000).collect();
+        assert!(delays.iter().all(|delay| *delay <= max_delay));
+        assert_eq!(delays[9_999], max_delay);
+    }
+
+    #[test]
+    fn stops_growing_at_max_exponent() {
+        let mut s = ExponentialBackoff::from_millis(2).max_exponent(3);
+
+        assert_eq!(s.next(), Some(Duration::from_millis(2)));
+        assert_eq!(s.next(), Some(Duration::from_millis(4)));
+        assert_eq!(s.next(), Some(Duration::from_millis(8)));
+        assert_eq!(s.nth(10_000), Some(Duration::from_millis(8)));
     }
 
     #[test]
@@ -137,4 +324,68 @@ mod tests {
         assert_eq!(s.next(), Some(Duration::from_millis(10)));
         assert_eq!(s.next(), Some(Duration::from_millis(10)));
     }
+
+    #[test]
+    fn builder_rejects_degenerate_parameters() {
+        let builder = || ExponentialBackoff::builder().base(2).factor(1000);
+
+        assert_eq!(
+            ExponentialBackoff::builder().build().unwrap_err(),
+            InvalidPolicy::BaseTooSmall(0)
+        );
+        assert_eq!(
+            builder().base(1).build().unwrap_err(),
+            InvalidPolicy::BaseTooSmall(1)
+        );
+        assert_eq!(
+            builder().factor(0).build().unwrap_err(),
+            InvalidPolicy::ZeroFactor
+        );
+        assert_eq!(
+            builder().factor(u64::MAX).build().unwrap_err(),
+            InvalidPolicy::Overflow
+        );
+        assert_eq!(
+            builder()
+                .max_delay(Duration::from_secs(1))
+                .build()
+                .unwrap_err(),
+            InvalidPolicy::MaxDelayBelowBase {
+                max_delay: Duration::from_secs(1),
+                first_delay: Duration::from_secs(2),
+            }
+        );
+        assert_eq!(
+            builder().max_exponent(1).build().unwrap_err(),
+            InvalidPolicy::MaxExponentTooSmall(1)
+        );
+    }
+
+    #[test]
+    fn builder_builds_valid_parameters() {
+        let mut s = ExponentialBackoff::builder()
+            .base(2)
+            .factor(1000)
+            .max_delay(Duration::from_secs(6))
+            .max_exponent(5)
+            .build()
+            .unwrap();
+
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+        assert_eq!(s.next(), Some(Duration::from_secs(4)));
+        assert_eq!(s.next(), Some(Duration::from_secs(6)));
+    }
+
+    static STRATEGY: ExponentialBackoff = ExponentialBackoff::from_millis(2)
+        .factor(1000)
+        .max_delay_millis(6000);
+
+    #[test]
+    fn can_be_declared_as_static() {
+        let mut s = STRATEGY.clone();
+
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+        assert_eq!(s.next(), Some(Duration::from_secs(4)));
+        assert_eq!(s.next(), Some(Duration::from_secs(6)));
+    }
 }
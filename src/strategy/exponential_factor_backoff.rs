@@ -1,5 +1,5 @@
-use std::iter::Iterator;
-use tokio::time::Duration;
+use core::iter::Iterator;
+use core::time::Duration;
 
 /// A retry strategy driven by exponential factor back-off.
 /// Duration is capped at a maximum value of `u32::MAX millis = 4294967295 ms` ~49 days.
@@ -175,4 +175,17 @@ mod tests {
         assert_eq!(s.next(), Some(Duration::from_millis(2000)));
         assert_eq!(s.next(), Some(Duration::from_millis(4000)));
     }
+
+    static STRATEGY: ExponentialFactorBackoff = ExponentialFactorBackoff::from_factor(2.)
+        .initial_delay(100)
+        .max_delay_millis(300);
+
+    #[test]
+    fn can_be_declared_as_static() {
+        let mut s = STRATEGY.clone();
+
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(200)));
+        assert_eq!(s.next(), Some(Duration::from_millis(300)));
+    }
 }
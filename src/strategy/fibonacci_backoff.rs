@@ -1,5 +1,5 @@
-use std::iter::Iterator;
-use tokio::time::Duration;
+use core::iter::Iterator;
+use core::time::Duration;
 
 /// A retry strategy driven by the fibonacci series.
 ///
@@ -135,4 +135,16 @@ mod tests {
         assert_eq!(s.next(), Some(Duration::from_secs(1)));
         assert_eq!(s.next(), Some(Duration::from_secs(2)));
     }
+
+    static STRATEGY: FibonacciBackoff = FibonacciBackoff::from_millis(10).max_delay_millis(20);
+
+    #[test]
+    fn can_be_declared_as_static() {
+        let mut s = STRATEGY.clone();
+
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+        assert_eq!(s.next(), Some(Duration::from_millis(20)));
+        assert_eq!(s.next(), Some(Duration::from_millis(20)));
+    }
 }
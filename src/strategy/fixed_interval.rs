@@ -1,5 +1,5 @@
-use std::iter::Iterator;
-use tokio::time::Duration;
+use core::iter::Iterator;
+use core::time::Duration;
 
 /// A retry strategy driven by a fixed interval.
 #[derive(Debug, Clone)]
@@ -42,4 +42,14 @@ mod tests {
         assert_eq!(s.next(), Some(Duration::from_millis(123)));
         assert_eq!(s.next(), Some(Duration::from_millis(123)));
     }
+
+    static STRATEGY: FixedInterval = FixedInterval::from_millis(123);
+
+    #[test]
+    fn can_be_declared_as_static() {
+        let mut s = STRATEGY.clone();
+
+        assert_eq!(s.next(), Some(Duration::from_millis(123)));
+        assert_eq!(s.next(), Some(Duration::from_millis(123)));
+    }
 }
@@ -0,0 +1,66 @@
+use core::iter::Iterator;
+use core::time::Duration;
+
+/// Wraps a strategy, retrying right away a few times before its delays kick in.
+pub trait ImmediateRetries: Iterator<Item = Duration> {
+    /// Makes the first `retries` retries happen without delay, then follows the strategy from
+    /// its first delay.
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// use tokio_retry2::strategy::{ExponentialBackoff, ImmediateRetries};
+    ///
+    /// let mut s = ExponentialBackoff::from_millis(10).immediate_retries(1);
+    /// assert_eq!(s.next(), Some(Duration::ZERO));
+    /// assert_eq!(s.next(), Some(Duration::from_millis(10)));
+    /// ```
+    fn immediate_retries(self, retries: usize) -> ImmediateRetriesIterator<Self>
+    where
+        Self: Sized,
+    {
+        ImmediateRetriesIterator {
+            iter: self,
+            remaining: retries,
+        }
+    }
+}
+
+impl<I> ImmediateRetries for I where I: Iterator<Item = Duration> {}
+
+/// A strategy wrapper with immediate retries first,
+/// created by [`ImmediateRetries::immediate_retries`] function.
+#[derive(Debug, Clone)]
+pub struct ImmediateRetriesIterator<I> {
+    iter: I,
+    remaining: usize,
+}
+
+impl<I: Iterator<Item = Duration>> Iterator for ImmediateRetriesIterator<I> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            Some(Duration::ZERO)
+        } else {
+            self.iter.next()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::FixedInterval;
+
+    #[test]
+    fn retries_immediately_before_the_strategy() {
+        let mut s = FixedInterval::from_millis(100).take(2).immediate_retries(2);
+
+        assert_eq!(s.next(), Some(Duration::ZERO));
+        assert_eq!(s.next(), Some(Duration::ZERO));
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+        assert_eq!(s.next(), None);
+    }
+}
@@ -1,4 +1,5 @@
-use tokio::time::Duration;
+use std::iter::Iterator;
+use std::time::Duration;
 
 pub fn jitter(duration: Duration) -> Duration {
     duration.mul_f64(rand::random::<f64>() + 0.5)
@@ -8,6 +9,90 @@ pub fn jitter_range(min: f64, max: f64) -> impl Fn(Duration) -> Duration {
     move |x| x.mul_f64(rand::random::<f64>() * (max - min) + min)
 }
 
+/// Randomizes the delays of a strategy, see [`WithJitter::with_jitter`].
+///
+/// Implemented by [`FullJitter`], [`Proportional`] and [`Additive`], and by every
+/// `Fn(Duration) -> Duration`, like [`jitter`] and [`jitter_range`].
+pub trait Jitter {
+    /// Returns a randomized `delay`.
+    fn jitter(&self, delay: Duration) -> Duration;
+}
+
+impl<F: Fn(Duration) -> Duration> Jitter for F {
+    fn jitter(&self, delay: Duration) -> Duration {
+        self(delay)
+    }
+}
+
+/// Jitter picking a delay between zero and the strategy delay.
+///
+/// Spreads retries the most, but may retry right away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FullJitter;
+
+impl Jitter for FullJitter {
+    fn jitter(&self, delay: Duration) -> Duration {
+        scale(delay, rand::random::<f64>())
+    }
+}
+
+/// Jitter picking a delay within a ratio of the strategy delay, e.g. `Proportional(0.2)`
+/// for ±20%.
+///
+/// Ratios are clamped between 0 and 1, so delays never shrink below `1 - ratio` times the
+/// strategy delay.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Proportional(pub f64);
+
+impl Jitter for Proportional {
+    fn jitter(&self, delay: Duration) -> Duration {
+        let ratio = self.0.clamp(0., 1.);
+        scale(delay, 1. + ratio * (rand::random::<f64>() * 2. - 1.))
+    }
+}
+
+/// Jitter adding up to the given duration to the strategy delay, which stays the minimum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Additive(pub Duration);
+
+impl Jitter for Additive {
+    fn jitter(&self, delay: Duration) -> Duration {
+        delay.saturating_add(scale(self.0, rand::random::<f64>()))
+    }
+}
+
+fn scale(delay: Duration, ratio: f64) -> Duration {
+    Duration::try_from_secs_f64(delay.as_secs_f64() * ratio).unwrap_or(Duration::MAX)
+}
+
+/// Wraps a strategy, randomizing its delays with a [`Jitter`].
+pub trait WithJitter: Iterator<Item = Duration> {
+    /// Randomizes every delay of the strategy with `jitter`.
+    fn with_jitter<J: Jitter>(self, jitter: J) -> Jittered<Self, J>
+    where
+        Self: Sized,
+    {
+        Jittered { iter: self, jitter }
+    }
+}
+
+impl<I> WithJitter for I where I: Iterator<Item = Duration> {}
+
+/// A strategy wrapper with randomized delays, created by [`WithJitter::with_jitter`].
+#[derive(Debug, Clone)]
+pub struct Jittered<I, J> {
+    iter: I,
+    jitter: J,
+}
+
+impl<I: Iterator<Item = Duration>, J: Jitter> Iterator for Jittered<I, J> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        self.iter.next().map(|delay| self.jitter.jitter(delay))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -37,4 +122,41 @@ mod tests {
         assert!(jitter.as_millis() <= 60);
         assert!(jitter.as_millis() != 100);
     }
+
+    #[test]
+    fn built_in_jitters_stay_in_range() {
+        let delay = Duration::from_millis(100);
+        for _ in 0..100 {
+            assert!(FullJitter.jitter(delay) <= delay);
+
+            let proportional = Proportional(0.2).jitter(delay);
+            assert!(proportional >= Duration::from_millis(80));
+            assert!(proportional <= Duration::from_millis(120));
+
+            let additive = Additive(Duration::from_millis(10)).jitter(delay);
+            assert!(additive >= delay);
+            assert!(additive <= Duration::from_millis(110));
+        }
+        assert_eq!(Proportional(0.).jitter(delay), delay);
+        assert_eq!(
+            Additive(Duration::from_secs(1)).jitter(Duration::MAX),
+            Duration::MAX
+        );
+    }
+
+    #[test]
+    fn with_jitter_randomizes_every_delay() {
+        let delays: Vec<_> = std::iter::repeat(Duration::from_secs(1))
+            .with_jitter(Proportional(0.5))
+            .take(3)
+            .collect();
+        assert_eq!(delays.len(), 3);
+        assert!(delays
+            .iter()
+            .all(|delay| delay.as_millis() >= 500 && delay.as_millis() <= 1500));
+
+        let mut s = std::iter::once(Duration::from_millis(100)).with_jitter(jitter);
+        assert!(s.next().unwrap().as_millis() >= 50);
+        assert_eq!(s.next(), None);
+    }
 }
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::iter::Iterator;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::error::Error as RetryError;
+
+/// Registry of retry strategies shared per key, e.g. per host or endpoint, so every retry
+/// future targeting one key advances the same backoff.
+///
+/// Failures against a key raise the delay for all its callers, instead of hundreds of tasks
+/// each restarting their own backoff against a host known to be down. A success of an action
+/// wrapped with [`KeyedStrategy::track`] resets the backoff of its key to a fresh clone of
+/// the strategy. Cloning a keyed backoff shares its registry.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::{ExponentialBackoff, KeyedBackoff};
+/// # async fn fetch(host: &str) -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let backoff = KeyedBackoff::new(ExponentialBackoff::from_millis(10).max_delay_millis(10_000));
+///
+/// let host = "a.example.com";
+/// let strategy = backoff.key(host);
+/// Retry::spawn(strategy.clone().take(5), strategy.track(|| fetch(host))).await
+/// # }
+/// ```
+pub struct KeyedBackoff<K, S> {
+    strategy: S,
+    keys: Arc<Mutex<HashMap<K, Arc<Mutex<S>>>>>,
+}
+
+impl<K, S> KeyedBackoff<K, S>
+where
+    K: Hash + Eq,
+    S: Iterator<Item = Duration> + Clone,
+{
+    /// Creates a registry giving every key its own clone of `strategy`.
+    pub fn new(strategy: S) -> Self {
+        KeyedBackoff {
+            strategy,
+            keys: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the strategy of `key`, shared with every other caller of the same key.
+    pub fn key(&self, key: K) -> KeyedStrategy<S> {
+        let position = lock(&self.keys)
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(self.strategy.clone())))
+            .clone();
+        KeyedStrategy {
+            strategy: self.strategy.clone(),
+            position,
+        }
+    }
+
+    /// Resets the backoff of `key`, as a success would.
+    pub fn reset(&self, key: &K) {
+        if let Some(position) = lock(&self.keys).get(key) {
+            *lock(position) = self.strategy.clone();
+        }
+    }
+
+    /// Forgets `key`, the next caller of it starts a fresh backoff.
+    pub fn remove(&self, key: &K) {
+        lock(&self.keys).remove(key);
+    }
+
+    /// Number of keys with a backoff.
+    pub fn len(&self) -> usize {
+        lock(&self.keys).len()
+    }
+
+    /// Returns `true` if no key has a backoff yet.
+    pub fn is_empty(&self) -> bool {
+        lock(&self.keys).is_empty()
+    }
+}
+
+impl<K, S: Clone> Clone for KeyedBackoff<K, S> {
+    fn clone(&self) -> Self {
+        KeyedBackoff {
+            strategy: self.strategy.clone(),
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+impl<K, S> fmt::Debug for KeyedBackoff<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyedBackoff")
+            .field("keys", &lock(&self.keys).len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Strategy of one key of a [`KeyedBackoff`], see [`KeyedBackoff::key`].
+///
+/// Cloning it shares the backoff of the key.
+#[derive(Debug, Clone)]
+pub struct KeyedStrategy<S> {
+    strategy: S,
+    position: Arc<Mutex<S>>,
+}
+
+impl<S> KeyedStrategy<S>
+where
+    S: Iterator<Item = Duration> + Clone,
+{
+    /// Resets the backoff of the key to a fresh clone of the strategy.
+    pub fn reset(&self) {
+        *lock(&self.position) = self.strategy.clone();
+    }
+
+    /// Wraps `action`, resetting the backoff of the key whenever one of its attempts succeeds.
+    pub fn track<A: Action>(&self, action: A) -> KeyTracked<A, S> {
+        KeyTracked {
+            action,
+            strategy: self.clone(),
+        }
+    }
+}
+
+impl<S> Iterator for KeyedStrategy<S>
+where
+    S: Iterator<Item = Duration>,
+{
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        lock(&self.position).next()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Action adapter resetting the backoff of a key on success, see [`KeyedStrategy::track`].
+#[derive(Debug, Clone)]
+pub struct KeyTracked<A, S> {
+    action: A,
+    strategy: KeyedStrategy<S>,
+}
+
+impl<A, S> Action for KeyTracked<A, S>
+where
+    A: Action,
+    S: Iterator<Item = Duration> + Clone,
+{
+    type Future = KeyTrackedFuture<A::Future, S>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        KeyTrackedFuture {
+            future: self.action.run(),
+            strategy: self.strategy.clone(),
+        }
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        KeyTrackedFuture {
+            future: self.action.run_with(context),
+            strategy: self.strategy.clone(),
+        }
+    }
+}
+
+/// Future of a [`KeyTracked`] attempt.
+#[pin_project]
+#[derive(Debug)]
+pub struct KeyTrackedFuture<F, S> {
+    #[pin]
+    future: F,
+    strategy: KeyedStrategy<S>,
+}
+
+impl<F, S, T, E> Future for KeyTrackedFuture<F, S>
+where
+    F: Future<Output = Result<T, RetryError<E>>>,
+    S: Iterator<Item = Duration> + Clone,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        let poll = this.future.poll(cx);
+        if let Poll::Ready(Ok(_)) = &poll {
+            this.strategy.reset();
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::ExponentialBackoff;
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn callers_of_a_key_share_its_backoff() {
+        let backoff = KeyedBackoff::new(ExponentialBackoff::from_millis(2));
+        let (mut first, mut second) = (backoff.key("a"), backoff.key("a"));
+        let mut other = backoff.key("b");
+
+        assert_eq!(first.next(), Some(millis(2)));
+        assert_eq!(second.next(), Some(millis(4)));
+        assert_eq!(first.next(), Some(millis(8)));
+        assert_eq!(other.next(), Some(millis(2)));
+        assert_eq!(backoff.len(), 2);
+    }
+
+    #[test]
+    fn reset_and_remove_start_over() {
+        let backoff = KeyedBackoff::new(ExponentialBackoff::from_millis(2));
+        let mut strategy = backoff.key("a");
+        strategy.next();
+
+        backoff.reset(&"a");
+        assert_eq!(strategy.next(), Some(millis(2)));
+        backoff.remove(&"a");
+        assert_eq!(backoff.key("a").next(), Some(millis(2)));
+        assert_eq!(strategy.next(), Some(millis(4)));
+    }
+
+    #[tokio::test]
+    async fn tracked_successes_reset_the_key() {
+        let backoff = KeyedBackoff::new(ExponentialBackoff::from_millis(2));
+        let mut strategy = backoff.key("a");
+        strategy.next();
+        strategy.next();
+        let mut action = strategy.track(|| std::future::ready(Ok::<_, RetryError<()>>(())));
+
+        action.run().await.unwrap();
+        assert_eq!(backoff.key("a").next(), Some(millis(2)));
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt;
+use std::future::Future;
+use std::iter::{IntoIterator, Iterator};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::{Action, AttemptContext};
+use crate::error::Error as RetryError;
+
+type BoxedStrategy = Box<dyn Iterator<Item = Duration> + Send>;
+
+/// Retry strategies picked by the kind of each transient error, e.g. a slow backoff for rate
+/// limits next to quick retries of timeouts.
+///
+/// `classify` maps an error to its kind, and every kind advances its own strategy. The map is
+/// applied to an action with [`StrategyMap::apply`]: its transient errors get the next delay
+/// of their kind as `retry_after`, which replaces the delay of the retry strategy. The retry
+/// strategy wrapped with [`Dispatched::strategy`] ends once the strategy of a kind runs out,
+/// so the retry gives up as exhausted. Errors of unmapped kinds, or already carrying a
+/// `retry_after`, keep the retry strategy, which still bounds the overall retries.
+///
+/// ```rust,no_run
+/// # use std::iter;
+/// # use std::time::Duration;
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::{ExponentialBackoff, FixedInterval, StrategyMap};
+/// #[derive(Debug, PartialEq)]
+/// enum ErrorKind {
+///     RateLimited,
+///     Timeout,
+///     Other,
+/// }
+///
+/// # #[derive(Debug)]
+/// # struct ApiError;
+/// # impl ApiError { fn kind(&self) -> ErrorKind { ErrorKind::Other } }
+/// # async fn call() -> Result<(), RetryError<ApiError>> { Ok(()) }
+/// # async fn run() -> Result<(), ApiError> {
+/// let strategies = StrategyMap::new(|err: &ApiError| err.kind())
+///     .on(ErrorKind::RateLimited, ExponentialBackoff::from_millis(2).factor(500).take(5))
+///     .on(ErrorKind::Timeout, FixedInterval::from_millis(100).take(3));
+///
+/// // other errors retry after 10ms, at most 10 retries in total
+/// let action = strategies.apply(call);
+/// let strategy = action.strategy(FixedInterval::from_millis(10).take(10));
+/// Retry::spawn(strategy, action).await
+/// # }
+/// ```
+pub struct StrategyMap<K, F> {
+    classify: F,
+    strategies: Vec<(K, BoxedStrategy)>,
+    exhausted: Arc<AtomicBool>,
+}
+
+impl<K, F> StrategyMap<K, F> {
+    /// Creates a map classifying errors with `classify`, without strategies.
+    pub fn new(classify: F) -> Self {
+        StrategyMap {
+            classify,
+            strategies: Vec::new(),
+            exhausted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Retries the errors of `kind` according to `strategy`, replacing any strategy mapped to
+    /// `kind` before.
+    pub fn on<S>(mut self, kind: K, strategy: S) -> Self
+    where
+        K: PartialEq,
+        S: IntoIterator<Item = Duration>,
+        S::IntoIter: Send + 'static,
+    {
+        self.strategies.retain(|(mapped, _)| *mapped != kind);
+        self.strategies.push((kind, Box::new(strategy.into_iter())));
+        self
+    }
+
+    /// Dispatches the transient errors of `action` to the strategies of their kind.
+    pub fn apply<A>(self, action: A) -> Dispatched<A, K, F> {
+        Dispatched {
+            action,
+            exhausted: self.exhausted.clone(),
+            map: Arc::new(Mutex::new(self)),
+        }
+    }
+
+    fn dispatch<E>(&mut self, error: RetryError<E>) -> RetryError<E>
+    where
+        K: PartialEq,
+        F: Fn(&E) -> K,
+    {
+        let RetryError::Transient {
+            err,
+            retry_after: None,
+        } = error
+        else {
+            return error;
+        };
+        let kind = (self.classify)(&err);
+        match self
+            .strategies
+            .iter_mut()
+            .find(|(mapped, _)| *mapped == kind)
+        {
+            Some((_, strategy)) => match strategy.next() {
+                Some(delay) => RetryError::retry_after(err, delay),
+                None => {
+                    self.exhausted.store(true, Ordering::Relaxed);
+                    RetryError::transient(err)
+                }
+            },
+            None => RetryError::transient(err),
+        }
+    }
+}
+
+impl<K, F> fmt::Debug for StrategyMap<K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StrategyMap")
+            .field("kinds", &self.strategies.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Action adapter dispatching its transient errors to the strategies of a [`StrategyMap`],
+/// see [`StrategyMap::apply`].
+#[derive(Debug)]
+pub struct Dispatched<A, K, F> {
+    action: A,
+    map: Arc<Mutex<StrategyMap<K, F>>>,
+    exhausted: Arc<AtomicBool>,
+}
+
+impl<A, K, F> Dispatched<A, K, F> {
+    /// Wraps the retry strategy, ending it once the strategy of a kind runs out.
+    pub fn strategy<S: IntoIterator<Item = Duration>>(&self, strategy: S) -> Mapped<S::IntoIter> {
+        Mapped {
+            strategy: strategy.into_iter(),
+            exhausted: self.exhausted.clone(),
+        }
+    }
+}
+
+/// Retry strategy ending once the strategy of a kind of a [`StrategyMap`] runs out, see
+/// [`Dispatched::strategy`].
+#[derive(Debug)]
+pub struct Mapped<I> {
+    strategy: I,
+    exhausted: Arc<AtomicBool>,
+}
+
+impl<I: Iterator<Item = Duration>> Iterator for Mapped<I> {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.exhausted.swap(false, Ordering::Relaxed) {
+            return None;
+        }
+        self.strategy.next()
+    }
+}
+
+impl<A, K, F> Action for Dispatched<A, K, F>
+where
+    A: Action,
+    K: PartialEq,
+    F: Fn(&A::Error) -> K,
+{
+    type Future = DispatchedFuture<A::Future, K, F>;
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn run(&mut self) -> Self::Future {
+        DispatchedFuture {
+            future: self.action.run(),
+            map: self.map.clone(),
+        }
+    }
+
+    fn run_with(&mut self, context: &AttemptContext<'_, Self::Error>) -> Self::Future {
+        DispatchedFuture {
+            future: self.action.run_with(context),
+            map: self.map.clone(),
+        }
+    }
+}
+
+/// Future of a [`Dispatched`] attempt.
+#[pin_project]
+#[derive(Debug)]
+pub struct DispatchedFuture<Fut, K, F> {
+    #[pin]
+    future: Fut,
+    map: Arc<Mutex<StrategyMap<K, F>>>,
+}
+
+impl<Fut, K, F, T, E> Future for DispatchedFuture<Fut, K, F>
+where
+    Fut: Future<Output = Result<T, RetryError<E>>>,
+    K: PartialEq,
+    F: Fn(&E) -> K,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.project();
+        this.future.poll(cx).map_err(|error| {
+            let mut map = this
+                .map
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            map.dispatch(error)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{ExponentialBackoff, FixedInterval};
+
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        RateLimited,
+        Timeout,
+        Other,
+    }
+
+    fn map() -> StrategyMap<Kind, fn(&Kind) -> Kind> {
+        let classify: fn(&Kind) -> Kind = |kind| match kind {
+            Kind::RateLimited => Kind::RateLimited,
+            Kind::Timeout => Kind::Timeout,
+            Kind::Other => Kind::Other,
+        };
+        StrategyMap::new(classify)
+            .on(Kind::RateLimited, ExponentialBackoff::from_millis(10))
+            .on(Kind::Timeout, FixedInterval::from_millis(100).take(1))
+    }
+
+    #[test]
+    fn every_kind_advances_its_own_strategy() {
+        let mut map = map();
+        let delay = |error: RetryError<Kind>| error.retry_after_delay();
+
+        assert_eq!(
+            delay(map.dispatch(RetryError::transient(Kind::RateLimited))),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            delay(map.dispatch(RetryError::transient(Kind::Timeout))),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            delay(map.dispatch(RetryError::transient(Kind::RateLimited))),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn exhausted_kinds_end_the_strategy_and_unmapped_kinds_pass_through() {
+        let dispatched = map().apply(());
+        let mut strategy = dispatched.strategy(FixedInterval::from_millis(10));
+        let mut map = dispatched.map.lock().unwrap();
+        map.dispatch(RetryError::transient(Kind::Timeout));
+        assert_eq!(strategy.next(), Some(Duration::from_millis(10)));
+
+        let exhausted = map.dispatch(RetryError::transient(Kind::Timeout));
+        assert!(exhausted.is_transient());
+        assert_eq!(exhausted.retry_after_delay(), None);
+        assert_eq!(strategy.next(), None);
+        let other = map.dispatch(RetryError::transient(Kind::Other));
+        assert!(other.is_transient());
+        assert_eq!(other.retry_after_delay(), None);
+        let requested = map.dispatch(RetryError::retry_after(
+            Kind::RateLimited,
+            Duration::from_secs(5),
+        ));
+        assert_eq!(requested.retry_after_delay(), Some(Duration::from_secs(5)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn exhausted_kinds_stop_the_retry_as_exhausted() {
+        let action =
+            map().apply(|| std::future::ready(Err::<(), _>(RetryError::transient(Kind::Timeout))));
+        let strategy = action.strategy(FixedInterval::from_millis(10));
+        let report = crate::Retry::spawn_with_report(strategy, action)
+            .await
+            .unwrap_err();
+
+        assert_eq!(report.attempts, 2);
+        assert_eq!(report.reason, crate::StopReason::Exhausted);
+    }
+}
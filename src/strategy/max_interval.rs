@@ -1,5 +1,5 @@
+use std::time::Duration;
 use std::time::Instant;
-use tokio::time::Duration;
 
 /// Wraps a strategy, applying `max_interval``, after which strategy will
 /// stop retrying.
@@ -35,7 +35,7 @@ impl<I> MaxInterval for I where I: Iterator<Item = Duration> {}
 
 /// A strategy wrapper with applied max_interval,
 /// created by [`MaxInterval::max_interval`] function.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MaxIntervalIterator<I> {
     iter: I,
     start: Instant,
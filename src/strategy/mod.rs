@@ -1,16 +1,50 @@
+#[cfg(feature = "std")]
+mod adaptive;
+#[cfg(feature = "backoff")]
+mod backoff_compat;
 mod exponential_backoff;
 mod exponential_factor_backoff;
 mod fibonacci_backoff;
 mod fixed_interval;
+mod immediate_retries;
 #[cfg(feature = "jitter")]
 mod jitter;
+#[cfg(feature = "std")]
+mod keyed;
+#[cfg(feature = "std")]
+mod map;
+#[cfg(feature = "std")]
 mod max_interval;
+#[cfg(feature = "std")]
+mod preview;
+#[cfg(feature = "chrono")]
+mod wall_clock;
+#[cfg(feature = "watch")]
+mod watch;
 
-pub use self::exponential_backoff::ExponentialBackoff;
+#[cfg(feature = "std")]
+pub use self::adaptive::{AdaptiveBackoff, Tracked, TrackedFuture};
+#[cfg(feature = "backoff")]
+pub use self::backoff_compat::{BackoffStrategy, StrategyBackoff};
+pub use self::exponential_backoff::{ExponentialBackoff, ExponentialBackoffBuilder, InvalidPolicy};
 pub use self::exponential_factor_backoff::ExponentialFactorBackoff;
 pub use self::fibonacci_backoff::FibonacciBackoff;
 pub use self::fixed_interval::FixedInterval;
+pub use self::immediate_retries::{ImmediateRetries, ImmediateRetriesIterator};
+#[cfg(feature = "std")]
+pub use self::keyed::{KeyTracked, KeyTrackedFuture, KeyedBackoff, KeyedStrategy};
+#[cfg(feature = "std")]
+pub use self::map::{Dispatched, DispatchedFuture, Mapped, StrategyMap};
+#[cfg(feature = "std")]
 pub use self::max_interval::{MaxInterval, MaxIntervalIterator};
+#[cfg(feature = "std")]
+pub use self::preview::Preview;
+#[cfg(feature = "chrono")]
+pub use self::wall_clock::WallClock;
+#[cfg(feature = "watch")]
+pub use self::watch::{RetryConfig, WatchStrategy};
 
 #[cfg(feature = "jitter")]
-pub use self::jitter::{jitter, jitter_range};
+pub use self::jitter::{
+    jitter, jitter_range, Additive, FullJitter, Jitter, Jittered, Proportional, WithJitter,
+};
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+/// Inspects the delays a strategy would produce without consuming it.
+///
+/// Implemented for every cloneable strategy, so the delay plan of a policy
+/// can be logged or asserted on before it is handed to a retry future.
+pub trait Preview: Iterator<Item = Duration> + Clone {
+    /// Returns the next `n` delays of the strategy, leaving the strategy untouched.
+    ///
+    /// Fewer than `n` delays are returned if the strategy is exhausted earlier.
+    fn preview(&self, n: usize) -> Vec<Duration> {
+        self.delays().take(n).collect()
+    }
+
+    /// Returns an iterator over the planned delays, backed by a clone of the strategy.
+    fn delays(&self) -> Self {
+        self.clone()
+    }
+}
+
+impl<I> Preview for I where I: Iterator<Item = Duration> + Clone {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategy::{ExponentialBackoff, FibonacciBackoff, FixedInterval, MaxInterval};
+
+    #[test]
+    fn preview_does_not_consume_strategy() {
+        let mut s = ExponentialBackoff::from_millis(10);
+
+        assert_eq!(
+            s.preview(3),
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(100),
+                Duration::from_millis(1000)
+            ]
+        );
+        assert_eq!(s.next(), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn preview_stops_at_exhaustion() {
+        let s = FixedInterval::from_millis(5).take(2);
+
+        assert_eq!(
+            s.preview(10),
+            vec![Duration::from_millis(5), Duration::from_millis(5)]
+        );
+    }
+
+    #[test]
+    fn preview_wrapped_strategy() {
+        let s = FibonacciBackoff::from_millis(10).max_interval(10_000);
+
+        assert_eq!(
+            s.delays().take(4).collect::<Vec<_>>(),
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+                Duration::from_millis(30)
+            ]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::iter::Iterator;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+
+type NextTarget = Box<dyn FnMut(DateTime<Utc>) -> Option<DateTime<Utc>> + Send>;
+
+/// A retry strategy waiting until wall-clock instants, e.g. the top of the next minute or
+/// the opening of the next quota window, rather than for relative delays.
+///
+/// Every delay runs from now until the next target, computed from the current UTC time when
+/// the delay is requested. Targets already past give a zero delay, and the strategy ends once
+/// there's no next target. Cron-like schedules fit [`WallClock::new`], e.g. with the `cron`
+/// crate: `WallClock::new(move |now| schedule.after(&now).next())`.
+///
+/// ```rust,no_run
+/// # use chrono::NaiveTime;
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::WallClock;
+/// # async fn export() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// // retry the nightly export at 02:00 UTC, for three nights at most
+/// let strategy = WallClock::daily_at(NaiveTime::from_hms_opt(2, 0, 0).unwrap()).take(3);
+/// Retry::spawn(strategy, export).await
+/// # }
+/// ```
+pub struct WallClock {
+    next_target: NextTarget,
+}
+
+impl WallClock {
+    /// Constructs a strategy waiting until the instant `next_target` returns for the current
+    /// time, ending once it returns `None`.
+    pub fn new<F>(next_target: F) -> WallClock
+    where
+        F: FnMut(DateTime<Utc>) -> Option<DateTime<Utc>> + Send + 'static,
+    {
+        WallClock {
+            next_target: Box::new(next_target),
+        }
+    }
+
+    /// Constructs a strategy waiting until the next multiple of `period` since the Unix
+    /// epoch, e.g. the top of the next minute for a period of 60 seconds.
+    ///
+    /// Never ends, and waits a whole period when now is exactly on a multiple.
+    pub fn every(period: Duration) -> WallClock {
+        let period = TimeDelta::from_std(period)
+            .unwrap_or(TimeDelta::MAX)
+            .max(TimeDelta::milliseconds(1));
+        WallClock::new(move |now| {
+            let since_epoch = now - DateTime::UNIX_EPOCH;
+            let periods = since_epoch.num_milliseconds() / period.num_milliseconds();
+            (periods + 1)
+                .checked_mul(period.num_milliseconds())
+                .and_then(DateTime::from_timestamp_millis)
+        })
+    }
+
+    /// Constructs a strategy waiting until `time` of day, in UTC, today if it is still
+    /// ahead, else tomorrow.
+    pub fn daily_at(time: NaiveTime) -> WallClock {
+        WallClock::new(move |now| {
+            let today = now.date_naive().and_time(time).and_utc();
+            if today > now {
+                Some(today)
+            } else {
+                today.checked_add_signed(TimeDelta::days(1))
+            }
+        })
+    }
+
+    /// Constructs a strategy waiting until each of `targets` in turn, skipping the ones
+    /// already past.
+    pub fn at<T>(targets: T) -> WallClock
+    where
+        T: IntoIterator<Item = DateTime<Utc>>,
+        T::IntoIter: Send + 'static,
+    {
+        let mut targets = targets.into_iter();
+        WallClock::new(move |now| targets.find(|target| *target > now))
+    }
+
+    fn delay_at(&mut self, now: DateTime<Utc>) -> Option<Duration> {
+        let target = (self.next_target)(now)?;
+        Some((target - now).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+impl fmt::Debug for WallClock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WallClock").finish_non_exhaustive()
+    }
+}
+
+impl Iterator for WallClock {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        self.delay_at(Utc::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instant(hour: u32, min: u32, sec: u32) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(hour, min, sec)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn every_waits_for_the_next_multiple_of_the_period() {
+        let mut s = WallClock::every(Duration::from_secs(60));
+
+        assert_eq!(
+            s.delay_at(instant(10, 15, 20)),
+            Some(Duration::from_secs(40))
+        );
+        assert_eq!(
+            s.delay_at(instant(10, 16, 0)),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn daily_at_waits_until_today_or_tomorrow() {
+        let mut s = WallClock::daily_at(NaiveTime::from_hms_opt(2, 0, 0).unwrap());
+
+        assert_eq!(
+            s.delay_at(instant(1, 0, 0)),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            s.delay_at(instant(3, 0, 0)),
+            Some(Duration::from_secs(23 * 3600))
+        );
+    }
+
+    #[test]
+    fn at_skips_past_targets_and_ends() {
+        let mut s = WallClock::at([instant(1, 0, 0), instant(2, 0, 0), instant(3, 0, 0)]);
+
+        assert_eq!(
+            s.delay_at(instant(1, 30, 0)),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(s.delay_at(instant(2, 59, 0)), Some(Duration::from_secs(60)));
+        assert_eq!(s.delay_at(instant(4, 0, 0)), None);
+    }
+}
@@ -0,0 +1,123 @@
+use std::iter::Iterator;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+/// Parameters of an exponential backoff that can change while retrying, see
+/// [`WatchStrategy`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Factor each delay is multiplied by for the next retry.
+    pub factor: f64,
+    /// Longest delay between attempts.
+    pub max_delay: Duration,
+    /// Number of retries after the first attempt.
+    pub max_retries: usize,
+}
+
+impl RetryConfig {
+    /// Delay before the `retry`-th retry, counting from 0, `None` once the retries ran out.
+    pub fn delay(&self, retry: usize) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        let exponent = i32::try_from(retry).unwrap_or(i32::MAX);
+        let delay = self.initial_delay.as_secs_f64() * self.factor.powi(exponent);
+        Some(
+            Duration::try_from_secs_f64(delay)
+                .unwrap_or(self.max_delay)
+                .min(self.max_delay),
+        )
+    }
+}
+
+impl Default for RetryConfig {
+    /// Retries 5 times from 100ms, doubling the delay up to 30s.
+    fn default() -> Self {
+        RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            factor: 2.,
+            max_delay: Duration::from_secs(30),
+            max_retries: 5,
+        }
+    }
+}
+
+/// A retry strategy reading its [`RetryConfig`] from a `tokio::sync::watch` channel on every
+/// retry, so that running retries pick up new delays and retry limits as soon as they are
+/// sent, e.g. to dial long-lived reconnect loops up or down during an incident.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use tokio_retry2::{Retry, RetryError};
+/// # use tokio_retry2::strategy::{RetryConfig, WatchStrategy};
+/// # async fn connect() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let (config, receiver) = tokio::sync::watch::channel(RetryConfig::default());
+///
+/// // later, from an admin endpoint
+/// config.send_modify(|config| config.max_delay = Duration::from_secs(300));
+///
+/// Retry::spawn(WatchStrategy::new(receiver), connect).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchStrategy {
+    config: watch::Receiver<RetryConfig>,
+    retries: usize,
+}
+
+impl WatchStrategy {
+    /// Constructs a strategy following the configs sent to `config`.
+    ///
+    /// Once the sender is dropped, the last config stays in use.
+    pub const fn new(config: watch::Receiver<RetryConfig>) -> WatchStrategy {
+        WatchStrategy { config, retries: 0 }
+    }
+}
+
+impl Iterator for WatchStrategy {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = self.config.borrow_and_update().delay(self.retries)?;
+        self.retries += 1;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_up_to_max_delay_and_retries() {
+        let config = RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            factor: 3.,
+            max_delay: Duration::from_millis(500),
+            max_retries: 3,
+        };
+
+        assert_eq!(config.delay(0), Some(Duration::from_millis(100)));
+        assert_eq!(config.delay(1), Some(Duration::from_millis(300)));
+        assert_eq!(config.delay(2), Some(Duration::from_millis(500)));
+        assert_eq!(config.delay(3), None);
+    }
+
+    #[test]
+    fn follows_config_updates() {
+        let (sender, receiver) = watch::channel(RetryConfig::default());
+        let mut s = WatchStrategy::new(receiver);
+        assert_eq!(s.next(), Some(Duration::from_millis(100)));
+
+        sender.send_modify(|config| config.initial_delay = Duration::from_secs(1));
+        assert_eq!(s.next(), Some(Duration::from_secs(2)));
+
+        sender.send_modify(|config| config.max_retries = 2);
+        drop(sender);
+        assert_eq!(s.next(), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::future::Future;
+use std::iter::{IntoIterator, Iterator};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_core::{Stream, TryStream};
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::error::Error as RetryError;
+use crate::runtime::{self, Instant, Sleep};
+
+/// Outcome of a single attempt, yielded by [`RetryStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttemptOutcome<T, E> {
+    /// The attempt failed with a transient error and will be retried after `delay`.
+    Retrying {
+        /// 1-based number of the failed attempt.
+        attempt: usize,
+        /// Error of the failed attempt.
+        error: E,
+        /// Delay before the next attempt.
+        delay: Duration,
+    },
+    /// The attempt succeeded, ending the stream.
+    Succeeded(T),
+    /// The attempt failed with a permanent error or the strategy is exhausted, ending the stream.
+    Failed(E),
+}
+
+impl<T, E> AttemptOutcome<T, E> {
+    /// Returns `true` if no attempt follows this outcome.
+    pub const fn is_final(&self) -> bool {
+        !matches!(self, AttemptOutcome::Retrying { .. })
+    }
+}
+
+#[pin_project(project = StreamStateProj)]
+enum StreamState<A>
+where
+    A: Action,
+{
+    Running(#[pin] A::Future),
+    Sleeping(#[pin] Sleep),
+    Done,
+}
+
+/// Stream that drives multiple attempts at an action via a retry strategy, yielding the
+/// outcome of every attempt.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{AttemptOutcome, RetryError, RetryStream};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # use futures_core::Stream;
+/// # async fn action() -> Result<u64, RetryError<std::io::Error>> { Ok(0) }
+/// # async fn run() {
+/// let mut attempts = std::pin::pin!(RetryStream::spawn(FixedInterval::from_millis(10).take(3), action));
+/// while let Some(outcome) = std::future::poll_fn(|cx| attempts.as_mut().poll_next(cx)).await {
+///     match outcome {
+///         AttemptOutcome::Retrying { attempt, error, delay } => {
+///             println!("attempt {attempt} failed with {error}, retrying in {delay:?}")
+///         }
+///         AttemptOutcome::Succeeded(value) => println!("got {value}"),
+///         AttemptOutcome::Failed(error) => println!("gave up: {error}"),
+///     }
+/// }
+/// # }
+/// ```
+#[pin_project]
+pub struct RetryStream<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    strategy: I,
+    #[pin]
+    state: StreamState<A>,
+    action: A,
+    attempts: usize,
+}
+
+impl<I, A> RetryStream<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    /// Creates the stream, starting the first attempt of `action`.
+    pub fn spawn<T: IntoIterator<IntoIter = I, Item = Duration>>(
+        strategy: T,
+        mut action: A,
+    ) -> RetryStream<I, A> {
+        RetryStream {
+            strategy: strategy.into_iter(),
+            state: StreamState::Running(action.run()),
+            action,
+            attempts: 1,
+        }
+    }
+}
+
+impl<I, A> Stream for RetryStream<I, A>
+where
+    I: Iterator<Item = Duration>,
+    A: Action,
+{
+    type Item = AttemptOutcome<A::Item, A::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            match this.state.as_mut().project() {
+                StreamStateProj::Running(future) => {
+                    let outcome = match future.poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Ok(item)) => AttemptOutcome::Succeeded(item),
+                        Poll::Ready(Err(RetryError::Permanent(err))) => AttemptOutcome::Failed(err),
+                        Poll::Ready(Err(RetryError::Transient { err, retry_after })) => {
+                            match this.strategy.next() {
+                                Some(duration) => {
+                                    let delay = retry_after.unwrap_or(duration);
+                                    this.state.set(StreamState::Sleeping(Sleep::after(delay)));
+                                    return Poll::Ready(Some(AttemptOutcome::Retrying {
+                                        attempt: *this.attempts,
+                                        error: err,
+                                        delay,
+                                    }));
+                                }
+                                None => AttemptOutcome::Failed(err),
+                            }
+                        }
+                    };
+                    this.state.set(StreamState::Done);
+                    return Poll::Ready(Some(outcome));
+                }
+                StreamStateProj::Sleeping(sleep) => {
+                    if sleep.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    *this.attempts += 1;
+                    let future = this.action.run();
+                    this.state.set(StreamState::Running(future));
+                }
+                StreamStateProj::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[pin_project(project = ResubscribeStateProj)]
+enum ResubscribeState<A>
+where
+    A: Action,
+{
+    Connecting(#[pin] A::Future),
+    Streaming {
+        #[pin]
+        stream: A::Item,
+        since: Instant,
+    },
+    Sleeping(#[pin] Sleep),
+    Done,
+}
+
+/// Stream that keeps a subscription alive, connecting again with backoff whenever the
+/// subscribed stream fails or ends. Created by [`Resubscribe::new`].
+///
+/// `connect` is an [`Action`] resolving with a stream of `Result`s. Items are forwarded,
+/// while an error or the end of the stream triggers the next connection after the strategy
+/// delay. Once the subscription has been healthy for `reset_after`, the strategy starts over.
+///
+/// The stream yields the last error and ends once the strategy is exhausted or `connect`
+/// fails with a permanent error.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Resubscribe, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # use futures_core::stream::BoxStream;
+/// # type Events = BoxStream<'static, Result<String, std::io::Error>>;
+/// # async fn subscribe() -> Result<Events, RetryError<std::io::Error>> { unimplemented!() }
+/// # async fn run() {
+/// let strategy = ExponentialBackoff::from_millis(100).max_delay_millis(10_000).take(10);
+/// let events = Resubscribe::new(strategy, subscribe, std::time::Duration::from_secs(60));
+/// # }
+/// ```
+#[pin_project]
+pub struct Resubscribe<T, A>
+where
+    T: IntoIterator<Item = Duration> + Clone,
+    A: Action,
+{
+    policy: T,
+    strategy: T::IntoIter,
+    #[pin]
+    state: ResubscribeState<A>,
+    connect: A,
+    reset_after: Duration,
+    last_error: Option<A::Error>,
+}
+
+impl<T, A> Resubscribe<T, A>
+where
+    T: IntoIterator<Item = Duration> + Clone,
+    A: Action,
+{
+    /// Creates the stream, starting the first connection.
+    pub fn new(strategy: T, mut connect: A, reset_after: Duration) -> Self {
+        Resubscribe {
+            strategy: strategy.clone().into_iter(),
+            policy: strategy,
+            state: ResubscribeState::Connecting(connect.run()),
+            connect,
+            reset_after,
+            last_error: None,
+        }
+    }
+}
+
+impl<T, A> Stream for Resubscribe<T, A>
+where
+    T: IntoIterator<Item = Duration> + Clone,
+    A: Action,
+    A::Item: TryStream<Error = A::Error>,
+{
+    type Item = Result<<A::Item as TryStream>::Ok, A::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let retry_after = match this.state.as_mut().project() {
+                ResubscribeStateProj::Connecting(future) => match future.poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Ok(stream)) => {
+                        this.state.set(ResubscribeState::Streaming {
+                            stream,
+                            since: runtime::now(),
+                        });
+                        continue;
+                    }
+                    Poll::Ready(Err(RetryError::Permanent(err))) => {
+                        this.state.set(ResubscribeState::Done);
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    Poll::Ready(Err(RetryError::Transient { err, retry_after })) => {
+                        *this.last_error = Some(err);
+                        retry_after
+                    }
+                },
+                ResubscribeStateProj::Streaming { stream, since } => {
+                    match stream.try_poll_next(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(Some(Ok(item))) => return Poll::Ready(Some(Ok(item))),
+                        Poll::Ready(Some(Err(err))) => *this.last_error = Some(err),
+                        // an ended subscription is reconnected too
+                        Poll::Ready(None) => {}
+                    }
+                    if runtime::elapsed(*since) >= *this.reset_after {
+                        *this.strategy = this.policy.clone().into_iter();
+                    }
+                    None
+                }
+                ResubscribeStateProj::Sleeping(sleep) => {
+                    if sleep.poll(cx).is_pending() {
+                        return Poll::Pending;
+                    }
+                    let future = this.connect.run();
+                    this.state.set(ResubscribeState::Connecting(future));
+                    continue;
+                }
+                ResubscribeStateProj::Done => return Poll::Ready(None),
+            };
+
+            match this.strategy.next() {
+                Some(delay) => {
+                    let delay = retry_after.unwrap_or(delay);
+                    this.state
+                        .set(ResubscribeState::Sleeping(Sleep::after(delay)));
+                }
+                None => {
+                    this.state.set(ResubscribeState::Done);
+                    return Poll::Ready(this.last_error.take().map(Err));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::error::Error as RetryError;
+use crate::runtime::Time;
+
+/// Restarts a long-running action, like a connection manager or a consumer loop, whenever
+/// it exits, backing off between restarts.
+///
+/// The action is restarted after every success and transient error, and stops the
+/// supervisor with a permanent error. Once a run has lasted [`Supervisor::healthy_after`],
+/// the backoff starts over from a fresh clone of the strategy, so a task crashing after days
+/// of healthy runtime restarts quickly. A `retry_after` of a transient error replaces the
+/// strategy delay.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use tokio_retry2::{RetryError, Supervisor};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn consume() -> Result<(), RetryError<std::io::Error>> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let strategy = ExponentialBackoff::from_millis(10).max_delay_millis(30_000);
+/// Supervisor::new(strategy)
+///     .healthy_after(Duration::from_secs(300))
+///     .run(consume)
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Supervisor<S> {
+    strategy: S,
+    healthy_after: Duration,
+}
+
+impl<S> Supervisor<S>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Creates a supervisor backing off between restarts according to `strategy`, reset
+    /// after a minute of healthy runtime.
+    pub const fn new(strategy: S) -> Self {
+        Supervisor {
+            strategy,
+            healthy_after: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets how long a run must last to reset the backoff.
+    pub const fn healthy_after(mut self, healthy_after: Duration) -> Self {
+        self.healthy_after = healthy_after;
+        self
+    }
+
+    /// Runs `action` and restarts it whenever it exits.
+    ///
+    /// Resolves with the permanent error of the action, or with the outcome of its last run
+    /// once the strategy runs out.
+    pub async fn run<A: Action>(&self, mut action: A) -> Result<A::Item, A::Error> {
+        let time = Time::default();
+        let mut strategy = self.strategy.clone().into_iter();
+        loop {
+            let started = time.now();
+            let (outcome, retry_after) = match action.run().await {
+                Ok(item) => (Ok(item), None),
+                Err(RetryError::Permanent(err)) => return Err(err),
+                Err(RetryError::Transient { err, retry_after }) => (Err(err), retry_after),
+            };
+            if time.elapsed(started) >= self.healthy_after {
+                strategy = self.strategy.clone().into_iter();
+            }
+            let Some(delay) = strategy.next() else {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("ending supervision: strategy reached its limit");
+                return outcome;
+            };
+            let delay = retry_after.unwrap_or(delay);
+            #[cfg(feature = "tracing")]
+            tracing::info!(delay_ms = delay.as_millis(), "restarting supervised action");
+            time.sleep_until(time.after(delay)).await;
+        }
+    }
+}
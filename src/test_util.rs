@@ -0,0 +1,298 @@
+//! Helpers to test code using retry futures, enabled by the `test-util` feature.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::error::Error as RetryError;
+use crate::runtime::{Clock, ClockSleep, Instant, Time};
+
+/// [`Clock`] only moving forward when advanced by hand, to test delays and deadlines of retry
+/// futures deterministically, without a runtime or `tokio::time::pause`.
+///
+/// Clones share the same time. Pass a clone to [`RetryIf::with_clock`](crate::RetryIf::with_clock),
+/// poll the retry future, then [`MockClock::advance`] past its delays.
+///
+/// ```rust
+/// use std::future::Future;
+/// use std::task::{Context, Poll, Waker};
+/// use std::time::Duration;
+/// use tokio_retry2::{Retry, RetryError};
+/// use tokio_retry2::strategy::FixedInterval;
+/// use tokio_retry2::test_util::MockClock;
+///
+/// let clock = MockClock::new();
+/// let retry = Retry::spawn(FixedInterval::from_millis(100).take(1), || {
+///     std::future::ready(Err::<(), _>(RetryError::transient("unavailable")))
+/// })
+/// .with_clock(clock.clone());
+/// let mut retry = std::pin::pin!(retry);
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// assert!(retry.as_mut().poll(&mut cx).is_pending());
+/// clock.advance(Duration::from_millis(100));
+/// assert_eq!(retry.as_mut().poll(&mut cx), Poll::Ready(Err("unavailable")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    now: Instant,
+    wakers: Vec<Waker>,
+}
+
+impl MockClock {
+    /// Creates a clock standing still at the current instant.
+    pub fn new() -> Self {
+        MockClock {
+            state: Arc::new(Mutex::new(State {
+                now: Instant::now(),
+                wakers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Moves the clock forward by `duration`, waking the sleeps it completes.
+    pub fn advance(&self, duration: Duration) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.now += duration;
+            std::mem::take(&mut state.wakers)
+        };
+        // the sleeps still pending register again when polled
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        MockClock::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.state.lock().unwrap().now
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> ClockSleep {
+        Box::pin(MockSleep {
+            clock: self.clone(),
+            deadline,
+        })
+    }
+}
+
+struct MockSleep {
+    clock: MockClock,
+    deadline: Instant,
+}
+
+impl Future for MockSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.clock.state.lock().unwrap();
+        if state.now >= self.deadline {
+            return Poll::Ready(());
+        }
+        state.wakers.push(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// [`Action`] following a script of outcomes and recording when it is called, to test retry
+/// configurations without writing the fixture by hand.
+///
+/// Clones share the script and the calls: pass a clone to the retry future and check the
+/// calls on the original. Calls are timestamped with the runtime clock, or with the clock set
+/// by [`MockAction::with_clock`], so the observed delays are exact with a [`MockClock`] or a
+/// paused tokio clock.
+///
+/// ```rust
+/// use std::future::Future;
+/// use std::task::{Context, Poll, Waker};
+/// use std::time::Duration;
+/// use tokio_retry2::{Retry, RetryError};
+/// use tokio_retry2::strategy::ExponentialBackoff;
+/// use tokio_retry2::test_util::{MockAction, MockClock};
+///
+/// let clock = MockClock::new();
+/// let action = MockAction::fail_times(2, "unavailable", 42).with_clock(clock.clone());
+/// let retry = Retry::spawn(ExponentialBackoff::from_millis(10), action.clone())
+///     .with_clock(clock.clone());
+/// let mut retry = std::pin::pin!(retry);
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// assert!(retry.as_mut().poll(&mut cx).is_pending());
+/// clock.advance(Duration::from_millis(10));
+/// assert!(retry.as_mut().poll(&mut cx).is_pending());
+/// clock.advance(Duration::from_millis(100));
+/// assert_eq!(retry.as_mut().poll(&mut cx), Poll::Ready(Ok(42)));
+///
+/// assert_eq!(action.calls(), 3);
+/// action.assert_delays([Duration::from_millis(10), Duration::from_millis(100)]);
+/// ```
+pub struct MockAction<T, E> {
+    script: Arc<Mutex<Script<T, E>>>,
+    time: Time,
+}
+
+struct Script<T, E> {
+    outcomes: VecDeque<Result<T, RetryError<E>>>,
+    calls: Vec<Instant>,
+}
+
+impl<T, E> MockAction<T, E> {
+    /// Creates an action resolving with `outcomes` in order, one per call.
+    ///
+    /// Calling it once the outcomes ran out panics.
+    pub fn new(outcomes: impl IntoIterator<Item = Result<T, RetryError<E>>>) -> Self {
+        MockAction {
+            script: Arc::new(Mutex::new(Script {
+                outcomes: outcomes.into_iter().collect(),
+                calls: Vec::new(),
+            })),
+            time: Time::default(),
+        }
+    }
+
+    /// Creates an action failing `times` times with the transient `error`, then succeeding
+    /// with `value`.
+    pub fn fail_times(times: usize, error: E, value: T) -> Self
+    where
+        E: Clone,
+    {
+        let failures = std::iter::repeat_n(error, times).map(|err| Err(RetryError::transient(err)));
+        MockAction::new(failures.chain(std::iter::once(Ok(value))))
+    }
+
+    /// Timestamps the calls with `clock`, e.g. the [`MockClock`] of the retry future.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.time = Time::new(Arc::new(clock));
+        self
+    }
+
+    /// Number of calls so far.
+    pub fn calls(&self) -> usize {
+        self.script.lock().unwrap().calls.len()
+    }
+
+    /// Instants of the calls so far, oldest first.
+    pub fn call_times(&self) -> Vec<Instant> {
+        self.script.lock().unwrap().calls.clone()
+    }
+
+    /// Time between every call and the previous one.
+    pub fn delays(&self) -> Vec<Duration> {
+        let calls = self.call_times();
+        calls
+            .windows(2)
+            .map(|calls| calls[1].saturating_duration_since(calls[0]))
+            .collect()
+    }
+
+    /// Asserts that the [`delays`](MockAction::delays) between the calls are `expected`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delays differ.
+    #[track_caller]
+    pub fn assert_delays(&self, expected: impl IntoIterator<Item = Duration>) {
+        let expected: Vec<_> = expected.into_iter().collect();
+        assert_eq!(
+            self.delays(),
+            expected,
+            "unexpected delays between the calls"
+        );
+    }
+}
+
+impl<T, E> Clone for MockAction<T, E> {
+    fn clone(&self) -> Self {
+        MockAction {
+            script: self.script.clone(),
+            time: self.time.clone(),
+        }
+    }
+}
+
+impl<T, E> fmt::Debug for MockAction<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let script = self.script.lock().unwrap();
+        f.debug_struct("MockAction")
+            .field("calls", &script.calls.len())
+            .field("remaining", &script.outcomes.len())
+            .finish()
+    }
+}
+
+impl<T, E> Action for MockAction<T, E> {
+    type Future = future::Ready<Result<T, RetryError<E>>>;
+    type Item = T;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        let mut script = self.script.lock().unwrap();
+        script.calls.push(self.time.now());
+        let outcome = script
+            .outcomes
+            .pop_front()
+            .expect("`MockAction` called more often than scripted");
+        future::ready(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleeps_complete_once_advanced_past_their_deadline() {
+        let clock = MockClock::new();
+        let mut sleep = clock.sleep_until(clock.now() + Duration::from_millis(100));
+        let mut cx = Context::from_waker(Waker::noop());
+
+        assert!(sleep.as_mut().poll(&mut cx).is_pending());
+        clock.advance(Duration::from_millis(99));
+        assert!(sleep.as_mut().poll(&mut cx).is_pending());
+        clock.advance(Duration::from_millis(1));
+        assert!(sleep.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn mock_action_follows_its_script() {
+        use crate::strategy::FixedInterval;
+        use crate::Retry;
+
+        let action = MockAction::new([
+            Err(RetryError::transient("timeout")),
+            Err(RetryError::retry_after(
+                "throttled",
+                Duration::from_millis(50),
+            )),
+            Err(RetryError::permanent("denied")),
+        ]);
+        let res = Retry::spawn(FixedInterval::from_millis(10), action.clone()).await;
+
+        assert_eq!(res, Err::<(), _>("denied"));
+        assert_eq!(action.calls(), 3);
+        action.assert_delays([Duration::from_millis(10), Duration::from_millis(50)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "called more often than scripted")]
+    fn mock_action_panics_past_its_script() {
+        let mut action = MockAction::<(), ()>::new([]);
+        drop(action.run());
+    }
+}
@@ -0,0 +1,166 @@
+use core::future::{poll_fn, Future};
+use core::iter::{IntoIterator, Iterator};
+use core::time::Duration;
+
+use crate::action::{Action, AttemptContext};
+use crate::condition::{Condition, RetryContext};
+use crate::decide::Decider;
+use crate::error::Error as RetryError;
+use crate::notify::{NoNotify, Notify};
+
+/// Retries `action` according to `strategy` while `condition` holds, sleeping with the futures
+/// returned by `sleep` instead of a runtime timer.
+///
+/// Available without the `std` feature, so the strategies of this crate can drive retries on
+/// targets without tokio, e.g. in embedded firmware with `embassy_time::Timer::after`. Delays,
+/// `retry_after` requests and async conditions work as in [`RetryIf`](crate::RetryIf), which
+/// shares its decisions, but without a clock the elapsed time seen by the condition and the
+/// action is the sum of the delays slept so far.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use tokio_retry2::{retry_with_timer, RetryError};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn read_sensor() -> Result<u16, RetryError<()>> { Ok(0) }
+/// # async fn timer_after(delay: Duration) {}
+/// # async fn run() -> Result<u16, ()> {
+/// let strategy = ExponentialBackoff::from_millis(2).factor(10).take(5);
+/// retry_with_timer(strategy, read_sensor, |_: &()| true, timer_after).await
+/// # }
+/// ```
+pub async fn retry_with_timer<T, A, C, S, F>(
+    strategy: T,
+    action: A,
+    condition: C,
+    sleep: S,
+) -> Result<A::Item, A::Error>
+where
+    T: IntoIterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    S: FnMut(Duration) -> F,
+    F: Future<Output = ()>,
+{
+    retry_with_timer_notify(strategy, action, condition, NoNotify, sleep).await
+}
+
+/// Same as [`retry_with_timer`], notifying `notify` before every retry and once it gives up
+/// on a transient error, as [`RetryIf`](crate::RetryIf) does.
+///
+/// Without a clock, the [`DelayContext`](crate::DelayContext) of the hook has a zero
+/// `attempt_duration`.
+pub async fn retry_with_timer_notify<T, A, C, N, S, F>(
+    strategy: T,
+    mut action: A,
+    mut condition: C,
+    mut notify: N,
+    mut sleep: S,
+) -> Result<A::Item, A::Error>
+where
+    T: IntoIterator<Item = Duration>,
+    A: Action,
+    C: Condition<A::Error>,
+    N: Notify<A::Error>,
+    S: FnMut(Duration) -> F,
+    F: Future<Output = ()>,
+{
+    let mut strategy = strategy.into_iter();
+    let mut decider = Decider::default();
+    let mut attempts = 0;
+    let mut slept = Duration::ZERO;
+    let mut last_error = None;
+    loop {
+        attempts += 1;
+        let context = AttemptContext::new(attempts, last_error.as_ref(), slept);
+        let (err, retry_after) = match action.run_with(&context).await {
+            Ok(item) => return Ok(item),
+            Err(RetryError::Permanent(err)) => return Err(err),
+            Err(RetryError::Transient { err, retry_after }) => (err, retry_after),
+        };
+        let context = RetryContext::new(attempts, slept);
+        let next = match poll_fn(|cx| condition.poll_should_retry(&err, &context, cx)).await {
+            true => decider.next_delay(&mut strategy, retry_after),
+            false => None,
+        };
+        let Some(next) = next else {
+            notify.exhausted(&err, &context);
+            return Err(err);
+        };
+        decider.notify(&mut notify, &err, next, attempts, slept, Duration::ZERO);
+        poll_fn(|cx| notify.poll_notified(cx)).await;
+        sleep(next.delay).await;
+        slept = slept.saturating_add(next.delay);
+        last_error = Some(err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::future::ready;
+
+    use super::*;
+    use crate::notify::OnExhausted;
+    use crate::strategy::FixedInterval;
+
+    #[tokio::test]
+    async fn sleeps_with_the_given_timer() {
+        let slept = RefCell::new(Vec::new());
+        let mut outcomes = vec![
+            Ok(7),
+            Err(RetryError::retry_after("busy", Duration::from_secs(1))),
+            Err(RetryError::transient("busy")),
+        ];
+        let res = retry_with_timer(
+            FixedInterval::from_millis(10).take(3),
+            move || ready(outcomes.pop().unwrap()),
+            |_: &&str| true,
+            |delay| {
+                slept.borrow_mut().push(delay);
+                ready(())
+            },
+        )
+        .await;
+
+        assert_eq!(res, Ok(7));
+        assert_eq!(
+            *slept.borrow(),
+            [Duration::from_millis(10), Duration::from_secs(1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_once_the_condition_rejects_the_error() {
+        let res = retry_with_timer(
+            FixedInterval::from_millis(10),
+            || ready(Err::<(), _>(RetryError::transient("rejected"))),
+            |err: &&str| *err != "rejected",
+            |_| -> std::future::Ready<()> { panic!("no retry expected") },
+        )
+        .await;
+
+        assert_eq!(res, Err("rejected"));
+    }
+
+    #[tokio::test]
+    async fn notifies_before_every_retry_and_once_exhausted() {
+        let notified = RefCell::new(Vec::new());
+        let res = retry_with_timer_notify(
+            FixedInterval::from_millis(10).take(2),
+            || ready(Err::<(), _>(RetryError::transient("busy"))),
+            |_: &&str| true,
+            OnExhausted::new(
+                |_: &&str, duration| notified.borrow_mut().push(Some(duration)),
+                |_: &&str, _: &RetryContext| notified.borrow_mut().push(None),
+            ),
+            |_| ready(()),
+        )
+        .await;
+
+        assert_eq!(res, Err("busy"));
+        assert_eq!(
+            *notified.borrow(),
+            [Some(Duration::ZERO), Some(Duration::from_millis(10)), None]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::iter::IntoIterator;
+use std::time::Duration;
+
+use ::tonic::{Code, Request, Response, Status};
+
+use crate::error::Error as RetryError;
+use crate::future::Retry;
+use crate::http::parse_retry_after;
+use crate::transient::TransientError;
+
+/// Returns whether a call failing with `status` is worth retrying: `UNAVAILABLE`,
+/// `RESOURCE_EXHAUSTED` and `DEADLINE_EXCEEDED`, unless the server pushes back with a
+/// negative `grpc-retry-pushback-ms`.
+///
+/// Usable as a condition of [`RetryIf`](crate::RetryIf) directly.
+pub fn is_retryable_grpc_status(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable | Code::ResourceExhausted | Code::DeadlineExceeded
+    ) && pushback(status).is_none_or(|millis| millis >= 0)
+}
+
+/// Reads the delay requested by the `grpc-retry-pushback-ms` or `retry-after` metadata of
+/// `status`, if present and valid.
+pub fn grpc_retry_after(status: &Status) -> Option<Duration> {
+    match pushback(status) {
+        Some(millis) => u64::try_from(millis).ok().map(Duration::from_millis),
+        None => parse_retry_after(status.metadata().get("retry-after")?.to_str().ok()?),
+    }
+}
+
+fn pushback(status: &Status) -> Option<i64> {
+    let value = status.metadata().get("grpc-retry-pushback-ms")?;
+    value.to_str().ok()?.trim().parse().ok()
+}
+
+/// Sends a unary gRPC call with a clone of `client` and of `message` on every attempt,
+/// retrying it according to `strategy` while it fails with [`is_retryable_grpc_status`].
+///
+/// Statuses requesting a delay through [`grpc_retry_after`] are retried after it instead of
+/// the strategy delay.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::retry_unary;
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # use tonic::{Request, Response, Status};
+/// # #[derive(Clone)]
+/// # struct GreeterClient;
+/// # impl GreeterClient {
+/// #     async fn say_hello(&mut self, _: Request<String>) -> Result<Response<String>, Status> { todo!() }
+/// # }
+/// # async fn run(client: GreeterClient) -> Result<(), Status> {
+/// let strategy = ExponentialBackoff::from_millis(100).take(3);
+/// let response = retry_unary(strategy, &client, "world".to_owned(), |mut client, request| async move {
+///     client.say_hello(request).await
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry_unary<S, C, M, F, Fut, T>(
+    strategy: S,
+    client: &C,
+    message: M,
+    mut call: F,
+) -> Result<Response<T>, Status>
+where
+    S: IntoIterator<Item = Duration>,
+    C: Clone,
+    M: Clone,
+    F: FnMut(C, Request<M>) -> Fut,
+    Fut: Future<Output = Result<Response<T>, Status>>,
+{
+    let action = || {
+        let call = call(client.clone(), Request::new(message.clone()));
+        async move { call.await.map_err(RetryError::classify) }
+    };
+    Retry::spawn(strategy, action).await
+}
+
+impl TransientError for Status {
+    /// Same as [`is_retryable_grpc_status`].
+    fn is_transient(&self) -> bool {
+        is_retryable_grpc_status(self)
+    }
+
+    /// Same as [`grpc_retry_after`].
+    fn retry_after(&self) -> Option<Duration> {
+        grpc_retry_after(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::tonic::metadata::MetadataValue;
+
+    use super::*;
+
+    fn status(code: Code, metadata: &[(&'static str, &'static str)]) -> Status {
+        let mut status = Status::new(code, "failed");
+        for (key, value) in metadata {
+            status
+                .metadata_mut()
+                .insert(*key, MetadataValue::from_static(value));
+        }
+        status
+    }
+
+    #[test]
+    fn classifies_status_codes() {
+        assert!(is_retryable_grpc_status(&status(Code::Unavailable, &[])));
+        assert!(is_retryable_grpc_status(&status(
+            Code::ResourceExhausted,
+            &[]
+        )));
+        assert!(is_retryable_grpc_status(&status(
+            Code::DeadlineExceeded,
+            &[]
+        )));
+        assert!(!is_retryable_grpc_status(&status(
+            Code::InvalidArgument,
+            &[]
+        )));
+        assert!(!is_retryable_grpc_status(&status(
+            Code::Unavailable,
+            &[("grpc-retry-pushback-ms", "-1")]
+        )));
+    }
+
+    #[test]
+    fn reads_pushback_and_retry_after() {
+        let pushback = status(Code::Unavailable, &[("grpc-retry-pushback-ms", "250")]);
+        let retry_after = status(Code::Unavailable, &[("retry-after", "2")]);
+
+        assert_eq!(
+            grpc_retry_after(&pushback),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(grpc_retry_after(&retry_after), Some(Duration::from_secs(2)));
+        assert_eq!(grpc_retry_after(&status(Code::Unavailable, &[])), None);
+        assert!(matches!(
+            RetryError::classify(pushback),
+            RetryError::Transient { retry_after: Some(delay), .. } if delay == Duration::from_millis(250)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_unary_calls() {
+        let mut attempts = 0;
+        let response = retry_unary(
+            [Duration::from_millis(10); 3],
+            &(),
+            "ping",
+            |(), request: Request<&str>| {
+                attempts += 1;
+                let result = if attempts < 3 {
+                    Err(Status::unavailable("down"))
+                } else {
+                    Ok(Response::new(request.into_inner().len()))
+                };
+                async move { result }
+            },
+        )
+        .await;
+
+        assert_eq!(response.unwrap().into_inner(), 4);
+        assert_eq!(attempts, 3);
+    }
+}
@@ -0,0 +1,225 @@
+use std::future::Future;
+use std::iter::IntoIterator;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use ::tower::util::{Oneshot, ServiceExt};
+use ::tower::{Layer, Service};
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::condition::Condition;
+use crate::error::Error as RetryError;
+use crate::notify::{NoNotify, Notify};
+use crate::output::RetryIfOutput;
+
+/// Condition retrying every error of a service, and none of its responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnError;
+
+impl<T, E> Condition<Result<T, E>> for OnError {
+    fn should_retry(&mut self, output: &Result<T, E>) -> bool {
+        output.is_err()
+    }
+}
+
+/// [`Layer`] retrying the requests of a service with a strategy of this crate.
+///
+/// The condition and the notify hook receive the `Result` of every call, so responses can be
+/// retried too. Requests must be `Clone`, as every attempt sends a copy.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::RetryLayer;
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # fn layer() -> impl tower::Layer<tower::util::BoxCloneService<String, u16, std::io::Error>> {
+/// RetryLayer::new(ExponentialBackoff::from_millis(10).take(3))
+///     // also retry server errors
+///     .condition(|output: &Result<u16, std::io::Error>| !matches!(output, Ok(status) if *status < 500))
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryLayer<S, C = OnError, N = NoNotify> {
+    strategy: S,
+    condition: C,
+    notify: N,
+}
+
+impl<S> RetryLayer<S>
+where
+    S: IntoIterator<Item = Duration> + Clone,
+{
+    /// Creates a layer retrying errors with `strategy`.
+    pub const fn new(strategy: S) -> Self {
+        RetryLayer {
+            strategy,
+            condition: OnError,
+            notify: NoNotify,
+        }
+    }
+}
+
+impl<S, C, N> RetryLayer<S, C, N> {
+    /// Sets the condition deciding which outputs are retried.
+    pub fn condition<T>(self, condition: T) -> RetryLayer<S, T, N> {
+        RetryLayer {
+            strategy: self.strategy,
+            condition,
+            notify: self.notify,
+        }
+    }
+
+    /// Sets the hook notified before every retry.
+    pub fn notify<T>(self, notify: T) -> RetryLayer<S, C, T> {
+        RetryLayer {
+            strategy: self.strategy,
+            condition: self.condition,
+            notify,
+        }
+    }
+}
+
+impl<Svc, S, C, N> Layer<Svc> for RetryLayer<S, C, N>
+where
+    S: Clone,
+    C: Clone,
+    N: Clone,
+{
+    type Service = RetryService<Svc, S, C, N>;
+
+    fn layer(&self, inner: Svc) -> Self::Service {
+        RetryService {
+            inner,
+            strategy: self.strategy.clone(),
+            condition: self.condition.clone(),
+            notify: self.notify.clone(),
+        }
+    }
+}
+
+/// [`Service`] retrying the requests of its inner service, created by [`RetryLayer`].
+#[derive(Debug, Clone)]
+pub struct RetryService<Svc, S, C, N> {
+    inner: Svc,
+    strategy: S,
+    condition: C,
+    notify: N,
+}
+
+impl<Svc, Req, S, C, N> Service<Req> for RetryService<Svc, S, C, N>
+where
+    Svc: Service<Req> + Clone,
+    Req: Clone,
+    S: IntoIterator<Item = Duration> + Clone,
+    C: Condition<Result<Svc::Response, Svc::Error>> + Clone,
+    N: Notify<Result<Svc::Response, Svc::Error>> + Clone,
+{
+    type Response = Svc::Response;
+    type Error = Svc::Error;
+    type Future = RetryIfOutput<S::IntoIter, ServiceAction<Svc, Req>, C, N>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        // the service polled ready runs the first attempt, the others wait for a clone
+        let clone = self.inner.clone();
+        let ready = std::mem::replace(&mut self.inner, clone);
+        RetryIfOutput::spawn(
+            self.strategy.clone(),
+            ServiceAction {
+                svc: self.inner.clone(),
+                req,
+                ready: Some(ready),
+            },
+            self.condition.clone(),
+            self.notify.clone(),
+        )
+    }
+}
+
+/// Action calling a service with a copy of the request, see [`RetryService`].
+///
+/// Also retries a single call without a [`RetryLayer`]: every attempt waits for a clone of
+/// the service to be ready and calls it with a clone of the request. Its errors are transient.
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Retry, ServiceAction};
+/// # use tokio_retry2::strategy::ExponentialBackoff;
+/// # async fn run(client: tower::util::BoxCloneService<String, u16, std::io::Error>) -> Result<(), std::io::Error> {
+/// let action = ServiceAction::new(client, "/health".to_owned());
+/// let status = Retry::spawn(ExponentialBackoff::from_millis(10).take(3), action).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServiceAction<Svc, Req> {
+    svc: Svc,
+    req: Req,
+    // already polled ready, called directly by the next attempt
+    ready: Option<Svc>,
+}
+
+impl<Svc, Req> ServiceAction<Svc, Req> {
+    /// Creates an action calling `svc` with `req`.
+    pub const fn new(svc: Svc, req: Req) -> Self {
+        ServiceAction {
+            svc,
+            req,
+            ready: None,
+        }
+    }
+}
+
+impl<Svc, Req> Action for ServiceAction<Svc, Req>
+where
+    Svc: Service<Req> + Clone,
+    Req: Clone,
+{
+    type Future = ServiceAttempt<Svc, Req>;
+    type Item = Svc::Response;
+    type Error = Svc::Error;
+
+    fn run(&mut self) -> Self::Future {
+        let call = match self.ready.take() {
+            Some(mut svc) => Call::Ready(svc.call(self.req.clone())),
+            None => Call::Oneshot(self.svc.clone().oneshot(self.req.clone())),
+        };
+        ServiceAttempt { call }
+    }
+}
+
+/// Future of a single [`ServiceAction`] attempt.
+#[pin_project]
+pub struct ServiceAttempt<Svc, Req>
+where
+    Svc: Service<Req>,
+{
+    #[pin]
+    call: Call<Svc, Req>,
+}
+
+#[pin_project(project = CallProj)]
+enum Call<Svc, Req>
+where
+    Svc: Service<Req>,
+{
+    Ready(#[pin] Svc::Future),
+    Oneshot(#[pin] Oneshot<Svc, Req>),
+}
+
+impl<Svc, Req> Future for ServiceAttempt<Svc, Req>
+where
+    Svc: Service<Req>,
+{
+    type Output = Result<Svc::Response, RetryError<Svc::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let poll = match self.project().call.project() {
+            CallProj::Ready(call) => call.poll(cx),
+            CallProj::Oneshot(call) => call.poll(cx),
+        };
+        poll.map_err(RetryError::transient)
+    }
+}
@@ -0,0 +1,288 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use pin_project::pin_project;
+
+use crate::action::Action;
+use crate::condition::{Condition, TRANSIENT_IO_KINDS};
+use crate::error::Error as RetryError;
+
+/// Errors that know whether they are worth retrying.
+///
+/// Implemented for [`std::io::Error`], and for the errors of `hyper`, `sqlx`, `redis`,
+/// `reqwest` and `tonic` with their features. Use [`OnTransient`] as the condition of a
+/// [`RetryIf`](crate::RetryIf), or [`RetryError::classify`] in the action.
+pub trait TransientError {
+    /// Returns whether the failed operation may succeed if attempted again.
+    fn is_transient(&self) -> bool;
+
+    /// Delay requested by the error before the next attempt, if any.
+    ///
+    /// `None` by default.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Condition retrying the errors classified as transient by [`TransientError`].
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{MapErr, OnTransient, RetryError, RetryIf};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn connect() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// let action = || async { connect().await.map_transient_err() };
+/// RetryIf::spawn(strategy, action, OnTransient, |_: &std::io::Error, _| {}).await
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnTransient;
+
+impl<E: TransientError> Condition<E> for OnTransient {
+    fn should_retry(&mut self, error: &E) -> bool {
+        error.is_transient()
+    }
+}
+
+/// Whether an error is worth retrying, returned by [`MaybeRetryable::classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retryability {
+    /// The error is retried, after the given delay or according to the strategy.
+    Transient(Option<Duration>),
+    /// The error ends the retry.
+    Permanent,
+}
+
+/// Errors classifying themselves as transient or permanent, so actions can return them as is
+/// instead of wrapping them into a [`RetryError`], see [`Classify`].
+///
+/// Implemented for every [`TransientError`].
+pub trait MaybeRetryable {
+    /// Returns whether the failed operation may succeed if attempted again, and when.
+    fn classify(&self) -> Retryability;
+}
+
+impl<E: TransientError> MaybeRetryable for E {
+    fn classify(&self) -> Retryability {
+        if self.is_transient() {
+            Retryability::Transient(self.retry_after())
+        } else {
+            Retryability::Permanent
+        }
+    }
+}
+
+impl<E: MaybeRetryable> RetryError<E> {
+    /// Wraps `err` into a transient error with its `retry_after` delay, or into a permanent
+    /// error, according to [`MaybeRetryable`].
+    pub fn classify(err: E) -> Self {
+        match err.classify() {
+            Retryability::Transient(retry_after) => RetryError::Transient { err, retry_after },
+            Retryability::Permanent => RetryError::Permanent(err),
+        }
+    }
+}
+
+/// Action adapter for closures returning plain `Result<T, E>` futures, wrapping their
+/// [`MaybeRetryable`] errors with [`RetryError::classify`].
+///
+/// ```rust,no_run
+/// # use tokio_retry2::{Classify, Retry};
+/// # use tokio_retry2::strategy::FixedInterval;
+/// # async fn connect() -> Result<(), std::io::Error> { Ok(()) }
+/// # async fn run() -> Result<(), std::io::Error> {
+/// let strategy = FixedInterval::from_millis(10).take(3);
+/// Retry::spawn(strategy, Classify::new(connect)).await
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Classify<F> {
+    action: F,
+}
+
+impl<F> Classify<F> {
+    /// Wraps `action`, a closure returning a future of `Result<T, E>`.
+    pub const fn new(action: F) -> Self {
+        Classify { action }
+    }
+}
+
+impl<F, Fut, T, E> Action for Classify<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: MaybeRetryable,
+{
+    type Future = ClassifyFuture<Fut>;
+    type Item = T;
+    type Error = E;
+
+    fn run(&mut self) -> Self::Future {
+        ClassifyFuture {
+            future: (self.action)(),
+        }
+    }
+}
+
+/// Future of a [`Classify`] action.
+#[pin_project]
+#[derive(Debug)]
+pub struct ClassifyFuture<F> {
+    #[pin]
+    future: F,
+}
+
+impl<F> ClassifyFuture<F> {
+    /// Wraps `future`, a future of `Result<T, E>`, wrapping its error with
+    /// [`RetryError::classify`].
+    pub const fn new(future: F) -> Self {
+        ClassifyFuture { future }
+    }
+}
+
+impl<F, T, E> Future for ClassifyFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: MaybeRetryable,
+{
+    type Output = Result<T, RetryError<E>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        self.project()
+            .future
+            .poll(cx)
+            .map(|result| result.map_err(RetryError::classify))
+    }
+}
+
+impl TransientError for io::Error {
+    /// Connection failures, timeouts, interruptions and truncated streams are transient.
+    fn is_transient(&self) -> bool {
+        TRANSIENT_IO_KINDS.contains(&self.kind())
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl TransientError for hyper::Error {
+    /// Timeouts, closed connections, incomplete messages and canceled requests are transient.
+    fn is_transient(&self) -> bool {
+        self.is_timeout() || self.is_closed() || self.is_incomplete_message() || self.is_canceled()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl TransientError for sqlx::Error {
+    /// Transient I/O errors, pool timeouts, crashed workers, as well as serialization failures
+    /// and deadlocks of PostgreSQL and MySQL are transient.
+    fn is_transient(&self) -> bool {
+        match self {
+            sqlx::Error::Io(err) => err.is_transient(),
+            sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+            sqlx::Error::Database(err) => matches!(
+                err.code().as_deref(),
+                // PostgreSQL serialization failure and deadlock, MySQL deadlock and lock timeout
+                Some("40001" | "40P01" | "1213" | "1205")
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl TransientError for redis::RedisError {
+    /// Timeouts, dropped or refused connections, and servers busy loading, failing over or
+    /// asking to try again are transient.
+    fn is_transient(&self) -> bool {
+        self.is_timeout()
+            || self.is_connection_dropped()
+            || self.is_connection_refusal()
+            || matches!(
+                self.kind(),
+                redis::ErrorKind::TryAgain
+                    | redis::ErrorKind::BusyLoadingError
+                    | redis::ErrorKind::ClusterDown
+                    | redis::ErrorKind::MasterDown
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_io_errors() {
+        let timed_out = io::Error::from(io::ErrorKind::TimedOut);
+        let not_found = io::Error::from(io::ErrorKind::NotFound);
+
+        assert!(OnTransient.should_retry(&timed_out));
+        assert!(!OnTransient.should_retry(&not_found));
+        assert!(matches!(
+            RetryError::classify(timed_out),
+            RetryError::Transient {
+                retry_after: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            RetryError::classify(not_found),
+            RetryError::Permanent(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn classify_wraps_plain_errors() {
+        #[derive(Debug, PartialEq)]
+        enum Failure {
+            Busy,
+            Invalid,
+        }
+
+        impl MaybeRetryable for Failure {
+            fn classify(&self) -> Retryability {
+                match self {
+                    Failure::Busy => Retryability::Transient(None),
+                    Failure::Invalid => Retryability::Permanent,
+                }
+            }
+        }
+
+        let mut action = Classify::new(|| async { Err::<(), _>(Failure::Busy) });
+        assert_eq!(
+            action.run().await,
+            Err(RetryError::transient(Failure::Busy))
+        );
+        let mut action = Classify::new(|| async { Err::<(), _>(Failure::Invalid) });
+        assert_eq!(
+            action.run().await,
+            Err(RetryError::permanent(Failure::Invalid))
+        );
+    }
+
+    #[test]
+    fn classify_keeps_retry_after() {
+        struct Throttled;
+
+        impl TransientError for Throttled {
+            fn is_transient(&self) -> bool {
+                true
+            }
+
+            fn retry_after(&self) -> Option<Duration> {
+                Some(Duration::from_secs(1))
+            }
+        }
+
+        assert!(matches!(
+            RetryError::classify(Throttled),
+            RetryError::Transient {
+                retry_after: Some(delay),
+                ..
+            } if delay == Duration::from_secs(1)
+        ));
+    }
+}
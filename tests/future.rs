@@ -5,7 +5,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tokio_retry2::strategy::ExponentialBackoff;
-use tokio_retry2::{Retry, RetryError, RetryIf};
+use tokio_retry2::{Retry, RetryBuilder, RetryError, RetryIf, StopReason};
 
 #[tokio::test]
 async fn attempts_just_once() {
@@ -58,6 +58,58 @@ async fn attempts_until_success() {
     assert_eq!(counter.load(Ordering::SeqCst), 4);
 }
 
+#[tokio::test]
+async fn spawn_any_retries_plain_errors() {
+    use tokio_retry2::strategy::FixedInterval;
+    let s = FixedInterval::from_millis(10).take(5);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = Retry::spawn_any(s, move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 2 {
+            Err(42u64)
+        } else {
+            Ok(previous)
+        })
+    });
+    let res = future.await;
+
+    assert_eq!(res, Ok(2));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn free_functions_retry_like_the_structs() {
+    use tokio_retry2::strategy::FixedInterval;
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let action = move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    };
+
+    let res = tokio_retry2::retry(FixedInterval::from_millis(1).take(2), action.clone()).await;
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+
+    let res = tokio_retry2::retry_if(FixedInterval::from_millis(1), action, |_: &u64| false).await;
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn huge_delays_do_not_overflow_the_deadline() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = Retry::spawn([Duration::MAX], move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    });
+
+    assert_eq!(future.await, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
 #[tokio::test]
 async fn compatible_with_tokio_core() {
     use tokio_retry2::strategy::FixedInterval;
@@ -178,3 +230,2819 @@ fn message(err: &u64, duration: Duration) {
     let msg = format!("err: {}, duration: {:?}", err, duration);
     assert_eq!(msg, "err: 42, duration: 0ns");
 }
+
+#[tokio::test]
+async fn notify_retry_with_attempt_number() {
+    use std::sync::Mutex;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::NotifyAttempt;
+
+    let s = FixedInterval::from_millis(10).take(5);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let attempts = Arc::new(Mutex::new(Vec::new()));
+    let cloned_attempts = attempts.clone();
+    let future = Retry::spawn_notify(
+        s,
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            if previous < 3 {
+                future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+            } else {
+                future::ready(Ok::<(), RetryError<u64>>(()))
+            }
+        },
+        NotifyAttempt::new(move |err: &u64, attempt, _duration| {
+            assert_eq!(*err, 42);
+            cloned_attempts.lock().unwrap().push(attempt);
+        }),
+    );
+    let res = future.await;
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(*attempts.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn notify_async_is_awaited_before_retrying() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::NotifyAsync;
+
+    let s = FixedInterval::from_millis(10).take(5);
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let future = Retry::spawn_notify(
+        s,
+        move || {
+            let notified = notified.load(Ordering::SeqCst);
+            if notified < 2 {
+                future::ready(Err::<usize, RetryError<usize>>(RetryError::transient(
+                    notified,
+                )))
+            } else {
+                future::ready(Ok::<usize, RetryError<usize>>(notified))
+            }
+        },
+        NotifyAsync::new(move |_err: &usize, _duration| {
+            let notified = cloned_notified.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                notified.fetch_add(1, Ordering::SeqCst);
+            }
+        }),
+    );
+    let res = future.await;
+
+    assert_eq!(res, Ok(2));
+}
+
+#[tokio::test]
+async fn attempts_retry_only_if_async_condition_is_true() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::ConditionAsync;
+
+    let s = FixedInterval::from_millis(10).take(5);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = RetryIf::spawn(
+        s,
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<usize>>(RetryError::transient(
+                previous + 1,
+            )))
+        },
+        ConditionAsync::new(|e: &usize| {
+            let retry = *e < 3;
+            async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                retry
+            }
+        }),
+        |_: &usize, _: Duration| {},
+    );
+    let res = future.await;
+
+    assert_eq!(res, Err(3));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn condition_receives_retry_context() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{ConditionContext, RetryContext};
+
+    let s = FixedInterval::from_millis(10).take(10);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = RetryIf::spawn(
+        s,
+        move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        },
+        ConditionContext::new(|_: &u64, context: &RetryContext| {
+            assert!(context.elapsed >= Duration::from_millis(10) * (context.attempts as u32 - 1));
+            context.attempts < 4
+        }),
+        |_: &u64, _: Duration| {},
+    );
+    let res = future.await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn deadline_interrupts_sleep_with_last_error() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let s = FixedInterval::from_millis(1000);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let start = Instant::now();
+    let future = Retry::spawn(s, move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    })
+    .with_deadline((start + Duration::from_millis(50)).into_std());
+    let res = future.await;
+
+    assert_eq!(
+        res,
+        Err(RetryFailure::DeadlineExceeded {
+            last_error: Some(42)
+        })
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    assert!(start.elapsed() < Duration::from_millis(1000));
+}
+
+#[tokio::test]
+async fn deadline_interrupts_running_attempt() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let s = FixedInterval::from_millis(10);
+    let future = RetryIf::spawn(
+        s,
+        future::pending::<Result<(), RetryError<u64>>>,
+        |_: &u64| true,
+        |_: &u64, _: Duration| {},
+    )
+    .with_deadline((Instant::now() + Duration::from_millis(20)).into_std());
+    let res = future.await;
+
+    assert_eq!(
+        res,
+        Err(RetryFailure::DeadlineExceeded { last_error: None })
+    );
+}
+
+#[tokio::test]
+async fn deadline_does_not_affect_success() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let s = FixedInterval::from_millis(10);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = Retry::spawn(s, move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        if previous < 2 {
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        } else {
+            future::ready(Ok::<(), RetryError<u64>>(()))
+        }
+    })
+    .with_deadline((Instant::now() + Duration::from_secs(10)).into_std());
+    let res = future.await;
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn attempt_timeout_is_a_transient_error() {
+    use std::future::Future;
+    use std::pin::Pin;
+    use tokio_retry2::strategy::FixedInterval;
+
+    type Attempt = Pin<Box<dyn Future<Output = Result<usize, RetryError<std::io::Error>>> + Send>>;
+
+    let s = FixedInterval::from_millis(10).take(5);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let timeouts = Arc::new(AtomicUsize::new(0));
+    let cloned_timeouts = timeouts.clone();
+    let future = RetryIf::spawn_with_attempt_timeout(
+        s,
+        move || -> Attempt {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            if previous < 2 {
+                Box::pin(future::pending())
+            } else {
+                Box::pin(future::ready(Ok(previous)))
+            }
+        },
+        |e: &std::io::Error| e.kind() == std::io::ErrorKind::TimedOut,
+        move |_: &std::io::Error, _: Duration| {
+            cloned_timeouts.fetch_add(1, Ordering::SeqCst);
+        },
+        Duration::from_millis(20),
+    );
+    let res = future.await;
+
+    assert_eq!(res.unwrap(), 2);
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+    assert_eq!(timeouts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn attempt_timeout_exhausts_strategy() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::AttemptTimeout;
+
+    #[derive(Debug, PartialEq)]
+    struct TimedOut;
+    impl From<AttemptTimeout> for TimedOut {
+        fn from(_: AttemptTimeout) -> Self {
+            TimedOut
+        }
+    }
+
+    let s = FixedInterval::from_millis(10).take(1);
+    let future = RetryIf::spawn(
+        s,
+        future::pending::<Result<(), RetryError<TimedOut>>>,
+        |_: &TimedOut| true,
+        |_: &TimedOut, _: Duration| {},
+    )
+    .with_attempt_timeout(Duration::from_millis(10));
+    let res = future.await;
+
+    assert_eq!(res, Err(TimedOut));
+}
+
+#[cfg(feature = "cancellation")]
+#[tokio::test]
+async fn cancellation_interrupts_sleep_with_last_error() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+    use tokio_util::sync::CancellationToken;
+
+    let s = FixedInterval::from_millis(1000);
+    let token = CancellationToken::new();
+    let cloned_token = token.clone();
+    let start = Instant::now();
+    let future = Retry::spawn(s, || {
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    })
+    .with_cancellation(cloned_token);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        token.cancel();
+    });
+    let res = future.await;
+
+    assert_eq!(
+        res,
+        Err(RetryFailure::Cancelled {
+            last_error: Some(42)
+        })
+    );
+    assert!(start.elapsed() < Duration::from_millis(1000));
+}
+
+#[cfg(feature = "cancellation")]
+#[tokio::test]
+async fn cancelled_token_stops_running_attempt() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+    use tokio_util::sync::CancellationToken;
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let future = RetryIf::spawn(
+        FixedInterval::from_millis(10),
+        future::pending::<Result<(), RetryError<u64>>>,
+        |_: &u64| true,
+        |_: &u64, _: Duration| {},
+    )
+    .with_cancellation(token);
+
+    assert_eq!(
+        future.await,
+        Err(RetryFailure::Cancelled { last_error: None })
+    );
+}
+
+#[tokio::test]
+async fn builder_retries_with_defaults() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1).take(2))
+        .build(move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        })
+        .await;
+
+    assert_eq!(res, Err(RetryFailure::Error(42)));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn builder_holding_the_action_is_awaited() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1).take(2))
+        .action(move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(if previous < 1 {
+                Err(RetryError::transient(previous))
+            } else {
+                Ok(previous)
+            })
+        })
+        .await;
+
+    assert_eq!(res, Ok(1));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn builder_applies_condition_and_notify() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1))
+        .condition(|e: &u64| *e < 3)
+        .notify(move |_: &u64, _: Duration| {
+            cloned_notified.fetch_add(1, Ordering::SeqCst);
+        })
+        .build(move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(
+                previous as u64 + 1,
+            )))
+        })
+        .await;
+
+    assert_eq!(res, Err(RetryFailure::Error(3)));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+    assert_eq!(notified.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn builder_applies_deadline() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(10))
+        .deadline((Instant::now() + Duration::from_millis(25)).into_std())
+        .build(|| future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42))))
+        .await;
+
+    assert_eq!(
+        res,
+        Err(RetryFailure::DeadlineExceeded {
+            last_error: Some(42)
+        })
+    );
+}
+
+#[tokio::test]
+async fn max_elapsed_stops_scheduling_retries() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = RetryIf::spawn(
+        FixedInterval::from_millis(10),
+        move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        },
+        |_: &u64| true,
+        |_: &u64, _: Duration| {},
+    )
+    .with_max_elapsed(Duration::from_millis(25))
+    .await;
+
+    assert_eq!(res, Err(42));
+    let attempts = counter.load(Ordering::SeqCst);
+    assert!((3..=4).contains(&attempts), "{attempts} attempts");
+}
+
+#[tokio::test]
+async fn action_receives_attempt_context() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{ActionContext, AttemptContext};
+
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_seen = seen.clone();
+    let action = ActionContext::new(move |context: &AttemptContext<u64>| {
+        cloned_seen
+            .lock()
+            .unwrap()
+            .push((context.attempt, context.previous_error.copied()));
+        let attempt = context.attempt as u64;
+        future::ready(if attempt < 3 {
+            Err(RetryError::transient(attempt))
+        } else {
+            Ok(attempt)
+        })
+    });
+    let res = Retry::spawn(FixedInterval::from_millis(1), action).await;
+
+    assert_eq!(res, Ok(3));
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![(1, None), (2, Some(1)), (3, Some(2))]
+    );
+}
+
+#[tokio::test]
+async fn report_carries_attempt_history() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::spawn_with_report(FixedInterval::from_millis(1).take(2), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<usize>>(RetryError::transient(
+            previous,
+        )))
+    })
+    .await;
+
+    let report = res.unwrap_err();
+    assert_eq!(report.attempts, 3);
+    assert_eq!(report.errors, vec![0, 1]);
+    assert_eq!(report.error, 2);
+    assert!(report.elapsed >= Duration::from_millis(2));
+    assert_eq!(report.reason, StopReason::Exhausted);
+    assert_eq!(report.attempt_times.len(), 3);
+    assert!(report.attempt_times[2] - report.attempt_times[0] >= Duration::from_millis(2));
+}
+
+#[tokio::test]
+async fn report_keeps_transient_errors_before_permanent_one() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = RetryIf::spawn_with_report(
+        FixedInterval::from_millis(1),
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<usize>>(if previous < 2 {
+                RetryError::transient(previous)
+            } else {
+                RetryError::permanent(previous)
+            }))
+        },
+        |_: &usize| true,
+        |_: &usize, _: Duration| {},
+    )
+    .await;
+
+    let report = res.unwrap_err();
+    assert_eq!(report.attempts, 3);
+    assert_eq!(report.errors, vec![0, 1]);
+    assert_eq!(report.error, 2);
+    assert_eq!(report.reason, StopReason::Permanent);
+}
+
+#[tokio::test]
+async fn reporting_carries_attempts_on_success() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let retried = Retry::spawn_reporting(FixedInterval::from_millis(1), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 3 {
+            Err(RetryError::transient(previous))
+        } else {
+            Ok(previous)
+        })
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(retried.value, 3);
+    assert_eq!(retried.attempts, 4);
+    assert!(retried.retried);
+    assert!(retried.total_elapsed >= Duration::from_millis(3));
+
+    let first_try = Retry::spawn_reporting(FixedInterval::from_millis(1), || {
+        future::ready(Ok::<_, RetryError<()>>(42))
+    })
+    .await
+    .unwrap();
+    assert_eq!(first_try.attempts, 1);
+    assert!(!first_try.retried);
+    assert_eq!(first_try.into_inner(), 42);
+}
+
+#[tokio::test]
+async fn resumable_action_resumes_from_the_failed_attempt() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Resumable;
+
+    // uploads at most two parts per attempt, then fails with its progress
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_calls = calls.clone();
+    let action = Resumable::new(
+        move |resume_from: Option<usize>| {
+            let from = resume_from.unwrap_or(0);
+            cloned_calls.lock().unwrap().push(from);
+            future::ready(match from + 2 {
+                uploaded if uploaded < 5 => Err(RetryError::transient(uploaded)),
+                _ => Ok(5),
+            })
+        },
+        |uploaded: &usize| Some(*uploaded),
+    );
+    let res = Retry::spawn(FixedInterval::from_millis(1).take(5), action).await;
+
+    assert_eq!(res, Ok(5));
+    assert_eq!(*calls.lock().unwrap(), vec![0, 2, 4]);
+}
+
+#[tokio::test]
+async fn spawn_with_gives_every_attempt_the_input() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_sent = sent.clone();
+    let res = Retry::spawn_with(
+        FixedInterval::from_millis(1).take(5),
+        String::from("hello"),
+        move |message: String| {
+            let mut sent = cloned_sent.lock().unwrap();
+            sent.push(message);
+            future::ready(match sent.len() {
+                3 => Ok(sent.len()),
+                _ => Err(RetryError::transient(())),
+            })
+        },
+    )
+    .await;
+
+    assert_eq!(res, Ok(3));
+    assert_eq!(*sent.lock().unwrap(), ["hello", "hello", "hello"]);
+}
+
+#[tokio::test]
+async fn dead_letter_hook_receives_the_report() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let dead_letters = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_dead_letters = dead_letters.clone();
+    let res = RetryIf::spawn(
+        FixedInterval::from_millis(1),
+        || future::ready(Err::<(), RetryError<usize>>(RetryError::transient(7))),
+        |err: &usize| *err != 7,
+        |_: &usize, _: Duration| {},
+    )
+    .on_dead_letter(move |report| {
+        let error = report.error;
+        cloned_dead_letters
+            .lock()
+            .unwrap()
+            .push(("order-1", report));
+        error * 2
+    })
+    .await;
+
+    assert_eq!(res, Err(14));
+    let dead_letters = dead_letters.lock().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    let (id, report) = &dead_letters[0];
+    assert_eq!((*id, report.attempts), ("order-1", 1));
+    assert_eq!(report.reason, StopReason::Rejected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn map_give_up_converts_the_final_error() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::GiveUpContext;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res: Result<(), (u64, GiveUpContext)> =
+        Retry::spawn(FixedInterval::from_millis(10).take(2), move || {
+            let attempt = cloned_counter.fetch_add(1, Ordering::SeqCst) as u64;
+            future::ready(Err(RetryError::transient(attempt)))
+        })
+        .map_give_up(|err, context| (err, context))
+        .await;
+
+    let (err, context) = res.unwrap_err();
+    assert_eq!(err, 2);
+    assert_eq!(context.attempts, 3);
+    assert_eq!(context.elapsed, Duration::from_millis(20));
+    assert_eq!(context.reason, StopReason::Exhausted);
+}
+
+#[tokio::test(start_paused = true)]
+async fn spawn_handled_lets_the_handler_pick_every_delay() {
+    use tokio_retry2::RetryDecision;
+
+    let started = tokio::time::Instant::now();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res: Result<(), &str> = Retry::spawn_handled(
+        move || {
+            let attempt = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err(RetryError::transient(if attempt == 0 {
+                "busy"
+            } else {
+                "refused"
+            })))
+        },
+        |attempt, err: &&str| match *err {
+            _ if attempt >= 3 => RetryDecision::Stop,
+            "busy" => RetryDecision::RetryAfter(Duration::from_millis(100)),
+            _ => RetryDecision::Retry,
+        },
+    )
+    .await;
+
+    assert_eq!(res, Err("refused"));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn on_exhausted_fires_once_when_giving_up() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{OnExhausted, RetryContext};
+
+    let exhausted = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_exhausted = exhausted.clone();
+    let notify = OnExhausted::new(
+        |_: &u64, _: Duration| {},
+        move |err: &u64, context: &RetryContext| {
+            cloned_exhausted
+                .lock()
+                .unwrap()
+                .push((*err, context.attempts));
+        },
+    );
+    let res = Retry::spawn_notify(
+        FixedInterval::from_millis(1).take(2),
+        || future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42))),
+        notify,
+    )
+    .await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(*exhausted.lock().unwrap(), vec![(42, 3)]);
+}
+
+#[tokio::test]
+async fn on_exhausted_fires_when_condition_rejects() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryContext;
+
+    let exhausted = Arc::new(AtomicUsize::new(0));
+    let cloned_exhausted = exhausted.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1))
+        .condition(|_: &u64| false)
+        .on_exhausted(move |_: &u64, _: &RetryContext| {
+            cloned_exhausted.fetch_add(1, Ordering::SeqCst);
+        })
+        .build(|| future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42))))
+        .await;
+
+    assert!(res.is_err());
+    assert_eq!(exhausted.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn on_exhausted_ignores_permanent_errors() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryContext;
+
+    let exhausted = Arc::new(AtomicUsize::new(0));
+    let cloned_exhausted = exhausted.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1))
+        .on_exhausted(move |_: &u64, _: &RetryContext| {
+            cloned_exhausted.fetch_add(1, Ordering::SeqCst);
+        })
+        .build(|| future::ready(Err::<(), RetryError<u64>>(RetryError::permanent(42))))
+        .await;
+
+    assert!(res.is_err());
+    assert_eq!(exhausted.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn state_is_threaded_through_attempts() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let (state, res) = Retry::spawn_with_state(
+        FixedInterval::from_millis(1).take(5),
+        Vec::new(),
+        async |attempts: &mut Vec<usize>| {
+            attempts.push(attempts.len());
+            if attempts.len() < 3 {
+                Err(RetryError::transient("not yet"))
+            } else {
+                Ok(attempts.len())
+            }
+        },
+    )
+    .await;
+
+    assert_eq!(res, Ok(3));
+    assert_eq!(state, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn state_is_returned_on_failure() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let (state, res) = Retry::spawn_with_state(
+        FixedInterval::from_millis(1).take(2),
+        0,
+        async |attempts: &mut u64| {
+            *attempts += 1;
+            Err::<(), _>(RetryError::transient(*attempts))
+        },
+    )
+    .await;
+
+    assert_eq!(res, Err(3));
+    assert_eq!(state, 3);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn stream_yields_every_attempt() {
+    use futures_core::Stream;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{AttemptOutcome, RetryStream};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let stream = RetryStream::spawn(FixedInterval::from_millis(1), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 2 {
+            Err(RetryError::transient(previous))
+        } else {
+            Ok::<usize, RetryError<usize>>(previous)
+        })
+    });
+    let mut stream = std::pin::pin!(stream);
+
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        outcomes.push(outcome);
+    }
+
+    let delay = Duration::from_millis(1);
+    assert_eq!(
+        outcomes,
+        vec![
+            AttemptOutcome::Retrying {
+                attempt: 1,
+                error: 0,
+                delay
+            },
+            AttemptOutcome::Retrying {
+                attempt: 2,
+                error: 1,
+                delay
+            },
+            AttemptOutcome::Succeeded(2),
+        ]
+    );
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn stream_ends_with_failure_once_exhausted() {
+    use futures_core::Stream;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{AttemptOutcome, RetryStream};
+
+    let stream = RetryStream::spawn(FixedInterval::from_millis(1).take(1), || {
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    });
+    let mut stream = std::pin::pin!(stream);
+
+    let first = future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+    assert!(!first.unwrap().is_final());
+    let last = future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+    assert_eq!(last, Some(AttemptOutcome::Failed(42)));
+    let end = future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+    assert_eq!(end, None);
+}
+
+#[tokio::test]
+async fn spawn_async_borrows_from_scope() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let prefix = String::from("attempt");
+    let mut attempts = Vec::new();
+    let res = Retry::spawn_async(FixedInterval::from_millis(1), async || {
+        attempts.push(format!("{prefix} {}", attempts.len() + 1));
+        if attempts.len() < 2 {
+            Err(RetryError::transient(()))
+        } else {
+            Ok(attempts.len())
+        }
+    })
+    .await;
+
+    assert_eq!(res, Ok(2));
+    assert_eq!(attempts, vec!["attempt 1", "attempt 2"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn spawn_async_runs_as_a_task_and_honors_retry_after() {
+    use tokio::time::Instant;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let task = tokio::spawn(Retry::spawn_async(
+        [Duration::from_millis(10)],
+        async move || match cloned_counter.fetch_add(1, Ordering::SeqCst) {
+            0 => Err(RetryError::retry_after(42, Duration::from_secs(1))),
+            attempt => Ok::<_, RetryError<u64>>(attempt),
+        },
+    ));
+
+    assert_eq!(task.await.unwrap(), Ok(1));
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn spawn_async_caps_huge_delays() {
+    let mut attempts = 0;
+    let res = Retry::spawn_async([Duration::MAX], async || {
+        attempts += 1;
+        if attempts < 2 {
+            Err(RetryError::transient(()))
+        } else {
+            Ok(attempts)
+        }
+    })
+    .await;
+    assert_eq!(res, Ok(2));
+}
+
+#[tokio::test]
+async fn retry_macro_runs_block() {
+    use tokio_retry2::retry;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = AtomicUsize::new(0);
+    let res = retry!(FixedInterval::from_millis(1).take(2), {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(RetryError::transient(42))
+    })
+    .await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn retry_after_replaces_strategy_delay() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let res = Retry::spawn(FixedInterval::from_millis(10_000), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 1 {
+            Err(RetryError::retry_after(42, Duration::from_millis(10)))
+        } else {
+            Ok::<(), RetryError<u64>>(())
+        })
+    })
+    .await;
+
+    assert_eq!(res, Ok(()));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn clamp_retry_after_bounds_requested_delay() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let notified = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_notified = notified.clone();
+    let started = Instant::now();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(10))
+        .notify(move |_: &u64, duration: Duration| {
+            cloned_notified.lock().unwrap().push(duration);
+        })
+        .clamp_retry_after(Duration::from_millis(50), Duration::from_secs(1))
+        .build(move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(match previous {
+                0 => Err(RetryError::retry_after(42, Duration::from_secs(86_400))),
+                1 => Err(RetryError::retry_after(42, Duration::ZERO)),
+                _ => Ok::<(), RetryError<u64>>(()),
+            })
+        })
+        .await;
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(
+        *notified.lock().unwrap(),
+        vec![Duration::from_secs(1), Duration::from_millis(50)]
+    );
+    assert_eq!(started.elapsed(), Duration::from_millis(1050));
+}
+
+#[tokio::test(start_paused = true)]
+async fn notify_context_separates_delay_and_elapsed() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{DelayContext, NotifyContext};
+
+    let contexts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_contexts = contexts.clone();
+    let notify = NotifyContext::new(move |_: &u64, context: &DelayContext| {
+        cloned_contexts.lock().unwrap().push((
+            context.attempt,
+            context.next_delay,
+            context.total_elapsed,
+        ));
+    });
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::spawn_notify(
+        FixedInterval::from_millis(100),
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(match previous {
+                0 => Err(RetryError::transient(42)),
+                1 => Err(RetryError::retry_after(42, Duration::from_millis(30))),
+                _ => Ok::<(), RetryError<u64>>(()),
+            })
+        },
+        notify,
+    )
+    .await;
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(
+        *contexts.lock().unwrap(),
+        vec![
+            (1, Duration::from_millis(100), Duration::ZERO),
+            (2, Duration::from_millis(30), Duration::from_millis(100)),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn notify_context_measures_each_failed_attempt() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{DelayContext, NotifyContext};
+
+    let durations = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_durations = durations.clone();
+    let notify = NotifyContext::new(move |_: &u64, context: &DelayContext| {
+        cloned_durations
+            .lock()
+            .unwrap()
+            .push(context.attempt_duration);
+    });
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::spawn_notify(
+        FixedInterval::from_millis(100).take(2),
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst) as u64;
+            async move {
+                // the second attempt hangs before failing
+                tokio::time::sleep(Duration::from_secs(previous * 10)).await;
+                Err::<(), _>(RetryError::transient(previous))
+            }
+        },
+        notify,
+    )
+    .await;
+
+    assert_eq!(res, Err(2));
+    assert_eq!(
+        *durations.lock().unwrap(),
+        vec![Duration::ZERO, Duration::from_secs(10)]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn stats_track_running_retry() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let future = Retry::spawn(FixedInterval::from_millis(100).take(2), || {
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    });
+    let stats = future.stats();
+    let handle = tokio::spawn(future);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(stats.attempts(), 2);
+    assert_eq!(stats.total_slept(), Duration::from_millis(100));
+    assert_eq!(stats.backoff(), Some(Duration::from_millis(100)));
+
+    assert_eq!(handle.await.unwrap(), Err(42));
+    assert_eq!(stats.attempts(), 3);
+    assert_eq!(stats.total_slept(), Duration::from_millis(200));
+    assert_eq!(stats.backoff(), None);
+    assert!(stats.last_error_at().is_some());
+}
+
+#[tokio::test]
+async fn fallback_runs_after_primary_is_exhausted() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let fallback_counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = fallback_counter.clone();
+    let res = Retry::spawn(FixedInterval::from_millis(1).take(1), || {
+        future::ready(Err::<&str, RetryError<u64>>(RetryError::transient(1)))
+    })
+    .or_else_retry(FixedInterval::from_millis(1), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 1 {
+            Err(RetryError::transient(2))
+        } else {
+            Ok::<&str, RetryError<u64>>("mirror")
+        })
+    })
+    .await;
+
+    assert_eq!(res, Ok("mirror"));
+    assert_eq!(fallback_counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn fallback_error_keeps_both_errors() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::FallbackError;
+
+    let fallback_counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = fallback_counter.clone();
+    let res = RetryIf::spawn(
+        FixedInterval::from_millis(1),
+        || future::ready(Err::<(), RetryError<u64>>(RetryError::permanent(1))),
+        |_: &u64| true,
+        |_: &u64, _: Duration| {},
+    )
+    .or_else_retry(FixedInterval::from_millis(1).take(1), move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<&str>>(RetryError::transient("down")))
+    })
+    .await;
+
+    assert_eq!(
+        res,
+        Err(FallbackError {
+            primary: 1,
+            fallback: "down"
+        })
+    );
+    assert_eq!(fallback_counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn fallback_is_not_run_on_success() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let res = Retry::spawn(FixedInterval::from_millis(1), || {
+        future::ready(Ok::<u64, RetryError<u64>>(1))
+    })
+    .or_else_retry(
+        FixedInterval::from_millis(1),
+        || -> future::Ready<Result<u64, RetryError<u64>>> { unreachable!("fallback must not run") },
+    )
+    .await;
+
+    assert_eq!(res, Ok(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn hedge_resolves_with_fastest_attempt() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Hedge;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let res = Hedge::spawn(
+        FixedInterval::from_millis(1),
+        move || {
+            let attempt = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let latency = if attempt == 0 { 1_000 } else { 20 };
+                tokio::time::sleep(Duration::from_millis(latency)).await;
+                Ok::<usize, RetryError<()>>(attempt)
+            }
+        },
+        Duration::from_millis(50),
+    )
+    .await;
+
+    assert_eq!(res, Ok(1));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(started.elapsed(), Duration::from_millis(70));
+}
+
+#[tokio::test(start_paused = true)]
+async fn hedge_retries_once_all_attempts_failed() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Hedge;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Hedge::spawn(
+        FixedInterval::from_millis(10).take(2),
+        move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        },
+        Duration::from_millis(50),
+    )
+    .max_hedges(2)
+    .await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn hedge_stops_once_overlapping_failures_exhaust_the_strategy() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Hedge;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let hedge = |strategy: std::iter::Take<FixedInterval>| {
+        let counter = cloned_counter.clone();
+        Hedge::spawn(
+            strategy,
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {
+                    tokio::time::sleep(Duration::from_millis(60)).await;
+                    Err::<(), RetryError<u64>>(RetryError::transient(42))
+                }
+            },
+            Duration::from_millis(50),
+        )
+    };
+
+    // the first attempt fails while its hedge is still running
+    let started = Instant::now();
+    assert_eq!(hedge(FixedInterval::from_millis(10).take(0)).await, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(started.elapsed(), Duration::from_millis(110));
+
+    // every failure takes a delay, the hedge running at exhaustion is the last attempt
+    counter.store(0, Ordering::SeqCst);
+    assert_eq!(hedge(FixedInterval::from_millis(10).take(2)).await, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn retry_if_output_retries_pending_values() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryIfOutput;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = RetryIfOutput::spawn(
+        FixedInterval::from_millis(1),
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(match previous {
+                0 => Err(RetryError::transient("unavailable")),
+                1 | 2 => Ok("pending"),
+                _ => Ok("done"),
+            })
+        },
+        |output: &Result<&str, &str>| output != &Ok("done"),
+        |_: &Result<&str, &str>, _: Duration| {},
+    )
+    .await;
+
+    assert_eq!(res, Ok("done"));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn retry_if_output_returns_last_output_once_exhausted() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryIfOutput;
+
+    let res = RetryIfOutput::spawn(
+        FixedInterval::from_millis(1).take(2),
+        || future::ready(Ok::<&str, RetryError<u64>>("pending")),
+        |output: &Result<&str, u64>| output == &Ok("pending"),
+        |_: &Result<&str, u64>, _: Duration| {},
+    )
+    .await;
+    assert_eq!(res, Ok("pending"));
+
+    let res = RetryIfOutput::spawn(
+        FixedInterval::from_millis(1),
+        || future::ready(Err::<&str, RetryError<u64>>(RetryError::permanent(42))),
+        |_: &Result<&str, u64>| true,
+        |_: &Result<&str, u64>, _: Duration| {},
+    )
+    .await;
+    assert_eq!(res, Err(42));
+}
+
+#[tokio::test(start_paused = true)]
+async fn handle_pauses_and_resumes_sleep() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let future = Retry::spawn(FixedInterval::from_millis(100), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 1 {
+            Err(RetryError::transient(42))
+        } else {
+            Ok::<(), RetryError<u64>>(())
+        })
+    });
+    let handle = future.handle();
+    let started = Instant::now();
+    let task = tokio::spawn(future);
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    handle.pause();
+    assert!(handle.is_paused());
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+    handle.resume();
+    assert_eq!(task.await.unwrap(), Ok(()));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(started.elapsed(), Duration::from_millis(600));
+}
+
+#[tokio::test]
+async fn budget_is_shared_between_retries() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryBudget;
+
+    let budget = RetryBudget::new(3, 1.);
+    let counter = Arc::new(AtomicUsize::new(0));
+    let spawn = |budget: RetryBudget| {
+        let counter = counter.clone();
+        RetryIf::spawn(
+            FixedInterval::from_millis(1),
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+            },
+            |_: &u64| true,
+            |_: &u64, _: Duration| {},
+        )
+        .with_budget(budget)
+    };
+
+    assert_eq!(spawn(budget.clone()).await, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+    assert_eq!(spawn(budget.clone()).await, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 5);
+
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(1))
+        .budget(budget.clone())
+        .build(|| future::ready(Ok::<(), RetryError<u64>>(())))
+        .await;
+    assert_eq!(res, Ok(()));
+    assert_eq!(budget.remaining(), 1);
+}
+
+#[tokio::test]
+async fn circuit_breaker_short_circuits_when_open() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{CircuitBreaker, CircuitState, RetryFailure};
+
+    let breaker = CircuitBreaker::new(1.0, 2, Duration::from_secs(60));
+    let counter = Arc::new(AtomicUsize::new(0));
+    let spawn = || {
+        let counter = counter.clone();
+        Retry::spawn(FixedInterval::from_millis(1), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err::<(), RetryError<u64>>(RetryError::transient(42))
+            }
+        })
+        .with_circuit_breaker(breaker.clone())
+    };
+
+    // the second failure opens the circuit, refusing the next retry
+    assert_eq!(spawn().await, Err(RetryFailure::Error(42)));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    assert_eq!(spawn().await, Err(RetryFailure::CircuitOpen));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn circuit_breaker_closes_on_successful_probe() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{CircuitBreaker, CircuitState};
+
+    let breaker = CircuitBreaker::new(1.0, 1, Duration::ZERO);
+    breaker.record_failure();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    let res = Retry::spawn(FixedInterval::from_millis(1), || {
+        future::ready(Ok::<(), RetryError<u64>>(()))
+    })
+    .with_circuit_breaker(breaker.clone())
+    .await;
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn circuit_breaker_fails_dropped_probes() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{CircuitBreaker, CircuitState};
+
+    let open_for = Duration::from_secs(60);
+    let breaker = CircuitBreaker::new(1.0, 1, open_for);
+    breaker.record_failure();
+    tokio::time::advance(open_for).await;
+
+    let probe = Retry::spawn(FixedInterval::from_millis(1), || {
+        future::pending::<Result<(), RetryError<u64>>>()
+    })
+    .with_circuit_breaker(breaker.clone());
+    let res = tokio::time::timeout(Duration::from_secs(1), probe).await;
+
+    assert!(res.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+    tokio::time::advance(open_for).await;
+    assert!(breaker.try_acquire());
+}
+
+#[tokio::test(start_paused = true)]
+async fn deadline_fails_the_probe_and_fuses_the_retry() {
+    use std::future::Future;
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{CircuitBreaker, CircuitState, RetryFailure};
+
+    let open_for = Duration::from_secs(60);
+    let breaker = CircuitBreaker::new(1.0, 1, open_for);
+    breaker.record_failure();
+    tokio::time::advance(open_for).await;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let mut probe = Box::pin(
+        Retry::spawn(FixedInterval::from_millis(1), move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::pending::<Result<(), RetryError<u64>>>()
+        })
+        .with_circuit_breaker(breaker.clone())
+        .with_deadline((Instant::now() + Duration::from_secs(1)).into_std()),
+    );
+
+    let res = (&mut probe).await;
+    assert!(matches!(res, Err(RetryFailure::DeadlineExceeded { .. })));
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // polled again, it neither runs the action nor resolves twice
+    let polled = future::poll_fn(|cx| std::task::Poll::Ready(probe.as_mut().poll(cx))).await;
+    assert!(polled.is_pending());
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn initial_delay_holds_back_the_first_attempt() {
+    use std::future::Future;
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let mut retry = Box::pin(Retry::spawn_after(
+        Duration::from_millis(100),
+        FixedInterval::from_millis(10).take(1),
+        move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(if previous == 0 {
+                Err(RetryError::transient(previous))
+            } else {
+                Ok(previous)
+            })
+        },
+    ));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(
+        future::poll_fn(|cx| std::task::Poll::Ready(retry.as_mut().poll(cx)))
+            .await
+            .is_pending()
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 0);
+    assert_eq!(retry.attempts(), 0);
+
+    assert_eq!(retry.as_mut().await, Ok(1));
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    assert_eq!(started.elapsed(), Duration::from_millis(110));
+
+    let started = Instant::now();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(10))
+        .initial_delay(Duration::from_millis(100))
+        .build(|| future::ready(Ok::<_, RetryError<()>>(42)))
+        .await;
+    assert_eq!(res.ok(), Some(42));
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_stops_scheduling_retries() {
+    use tokio::time::{sleep, Instant};
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{OnShutdown, RetryFailure};
+
+    let slow_failure = || async {
+        sleep(Duration::from_millis(100)).await;
+        Err::<(), _>(RetryError::transient("unavailable"))
+    };
+    let started = Instant::now();
+    let policy = Retry::builder()
+        .strategy(FixedInterval::from_millis(1000))
+        .until(sleep(Duration::from_millis(50)))
+        .into_policy();
+
+    // both retries share the signal, the running attempts finish without being retried
+    let (first, second) = tokio::join!(policy.run(slow_failure), policy.run(slow_failure));
+    let shut_down = RetryFailure::ShutDown {
+        last_error: Some("unavailable"),
+    };
+    assert_eq!(first, Err(shut_down));
+    assert!(second.unwrap_err().is_shut_down());
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+
+    let started = Instant::now();
+    let aborted = Retry::builder()
+        .strategy(FixedInterval::from_millis(1000))
+        .until(sleep(Duration::from_millis(50)))
+        .on_shutdown(OnShutdown::Abort)
+        .build(slow_failure)
+        .await;
+    assert_eq!(aborted, Err(RetryFailure::ShutDown { last_error: None }));
+    assert_eq!(started.elapsed(), Duration::from_millis(50));
+
+    // a sleeping retry stops right away
+    let started = Instant::now();
+    let res = RetryIf::spawn(
+        FixedInterval::from_millis(1000),
+        || future::ready(Err::<(), _>(RetryError::transient(42))),
+        |_: &u64| true,
+        |_: &u64, _: Duration| {},
+    )
+    .until(sleep(Duration::from_millis(50)))
+    .await;
+    assert_eq!(
+        res,
+        Err(RetryFailure::ShutDown {
+            last_error: Some(42)
+        })
+    );
+    assert_eq!(started.elapsed(), Duration::from_millis(50));
+}
+
+#[tokio::test(start_paused = true)]
+async fn supervisor_resets_the_backoff_after_a_healthy_run() {
+    use tokio::time::{sleep, Instant};
+    use tokio_retry2::Supervisor;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let cloned_runs = runs.clone();
+    let started = Instant::now();
+    let res = Supervisor::new(ExponentialBackoff::from_millis(10))
+        .healthy_after(Duration::from_millis(50))
+        .run(move || {
+            let run = cloned_runs.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match run {
+                    0 => Ok(()),
+                    1 => Err(RetryError::transient("disconnected")),
+                    2 => {
+                        sleep(Duration::from_millis(60)).await;
+                        Err(RetryError::transient("disconnected"))
+                    }
+                    _ => Err(RetryError::permanent("unauthorized")),
+                }
+            }
+        })
+        .await;
+
+    assert_eq!(res, Err("unauthorized"));
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+    // 10ms and 100ms between the first runs, 10ms again after the healthy one
+    assert_eq!(started.elapsed(), Duration::from_millis(180));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_spaces_attempts() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RateLimiter;
+
+    let limiter = RateLimiter::new(2, Duration::from_secs(1));
+    let counter = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let spawn = || {
+        let counter = counter.clone();
+        Retry::builder()
+            .strategy(FixedInterval::from_millis(1).take(1))
+            .rate_limit(limiter.clone())
+            .build(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+            })
+    };
+
+    // 4 attempts sharing 2 per second, after an initial burst of 2
+    let (first, second) = tokio::join!(spawn(), spawn());
+    assert!(first.is_err() && second.is_err());
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_reserves_slots_once_attempts_are_due() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RateLimiter;
+
+    let limiter = RateLimiter::new(1, Duration::from_secs(1));
+    let spawn = || {
+        RetryBuilder::new()
+            .strategy(FixedInterval::from_millis(1).take(1))
+            .rate_limit(limiter.clone())
+            .build(|| future::ready(Ok::<u64, RetryError<u64>>(42)))
+    };
+    let started = Instant::now();
+
+    // a retry never polled doesn't take the slot of the next one
+    let idle = spawn();
+    assert_eq!(spawn().await, Ok(42));
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert_eq!(idle.await, Ok(42));
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+}
+
+#[cfg(feature = "group")]
+#[tokio::test(start_paused = true)]
+async fn group_limits_concurrent_attempts() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryGroup;
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let group = RetryGroup::new(FixedInterval::from_millis(10).take(2), 2);
+    let actions = (0..6u64).map(|job| {
+        let running = running.clone();
+        let max_running = max_running.clone();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        move || {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if job.is_multiple_of(2) && attempt == 0 {
+                    Err(RetryError::transient(job))
+                } else {
+                    Ok(job)
+                }
+            }
+        }
+    });
+    let results = group.run_all(actions).await;
+
+    assert_eq!(results, (0..6).map(Ok).collect::<Vec<_>>());
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "scheduler")]
+#[tokio::test(start_paused = true)]
+async fn scheduler_runs_jobs_in_the_background() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{BoxedRetryPolicy, JobOutcome, RetryScheduler};
+
+    let (scheduler, mut events) = RetryScheduler::new();
+    let policy = || BoxedRetryPolicy::new(FixedInterval::from_millis(60_000).take(3));
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let flaky = scheduler
+        .schedule("flaky", policy(), move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(if previous < 1 {
+                Err(RetryError::transient("down"))
+            } else {
+                Ok(previous)
+            })
+        })
+        .unwrap();
+    let stuck = scheduler
+        .schedule("stuck", policy(), || {
+            future::ready(Err::<usize, _>(RetryError::transient("down")))
+        })
+        .unwrap();
+    assert!(scheduler
+        .schedule("stuck", policy(), || future::ready(Ok(0)))
+        .is_err());
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(flaky.status().unwrap().attempts, 1);
+    assert!(flaky.retry_now());
+    let event = events.recv().await.unwrap();
+    assert_eq!(
+        (event.key, event.attempts, event.outcome),
+        ("flaky", 2, JobOutcome::Succeeded(1))
+    );
+    assert!(flaky.is_finished());
+    assert!(!flaky.cancel());
+
+    assert!(stuck.cancel());
+    let event = events.recv().await.unwrap();
+    assert_eq!((event.key, event.outcome), ("stuck", JobOutcome::Cancelled));
+    assert!(scheduler.is_empty());
+}
+
+#[cfg(feature = "detach")]
+#[tokio::test(start_paused = true)]
+async fn detached_retries_run_as_tasks() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let detached = Retry::spawn_detached(FixedInterval::from_millis(100).take(3), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 2 {
+            Err(RetryError::transient("down"))
+        } else {
+            Ok(previous)
+        })
+    });
+    let stats = detached.stats();
+    assert_eq!(detached.await.unwrap(), Ok(2));
+    assert_eq!(stats.attempts(), 3);
+
+    let stuck = Retry::spawn(FixedInterval::from_millis(60_000), || {
+        future::ready(Err::<(), _>(RetryError::transient("down")))
+    })
+    .detach_named("stuck");
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(!stuck.is_finished());
+    stuck.abort();
+    assert!(stuck.await.unwrap_err().is_cancelled());
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn tower_layer_retries_service_errors() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryLayer;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let service = service_fn(move |req: u64| {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 2 {
+            Err("down")
+        } else {
+            Ok(req * 2)
+        })
+    });
+    let service = RetryLayer::new(FixedInterval::from_millis(1)).layer(service);
+
+    assert_eq!(service.oneshot(21).await, Ok(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn tower_layer_calls_the_ready_service_first() {
+    use std::task::{Context, Poll};
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryLayer;
+    use tower::{Layer, Service, ServiceExt};
+
+    #[derive(Clone)]
+    struct Flaky {
+        ready: Arc<AtomicUsize>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Service<u64> for Flaky {
+        type Response = u64;
+        type Error = &'static str;
+        type Future = future::Ready<Result<u64, &'static str>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.ready.fetch_add(1, Ordering::SeqCst);
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: u64) -> Self::Future {
+            let previous = self.calls.fetch_add(1, Ordering::SeqCst);
+            future::ready(if previous < 1 {
+                Err("down")
+            } else {
+                Ok(req * 2)
+            })
+        }
+    }
+
+    let ready = Arc::new(AtomicUsize::new(0));
+    let service = Flaky {
+        ready: ready.clone(),
+        calls: Arc::new(AtomicUsize::new(0)),
+    };
+    let service = RetryLayer::new(FixedInterval::from_millis(1)).layer(service);
+
+    assert_eq!(service.oneshot(21).await, Ok(42));
+    // once by the caller, once for the retry on a clone
+    assert_eq!(ready.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn tower_layer_retries_responses() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryLayer;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let service = service_fn(move |_: ()| {
+        let status = if cloned_counter.fetch_add(1, Ordering::SeqCst) < 1 {
+            503
+        } else {
+            200
+        };
+        future::ready(Ok::<u16, &str>(status))
+    });
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let service = RetryLayer::new(FixedInterval::from_millis(1).take(3))
+        .condition(|output: &Result<u16, &str>| output != &Ok(200))
+        .notify(move |_: &Result<u16, &str>, _: Duration| {
+            cloned_notified.fetch_add(1, Ordering::SeqCst);
+        })
+        .layer(service);
+
+    assert_eq!(service.oneshot(()).await, Ok(200));
+    assert_eq!(notified.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn service_action_retries_a_single_call() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::ServiceAction;
+    use tower::service_fn;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let service = service_fn(move |req: u64| {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(if previous < 2 {
+            Err("down")
+        } else {
+            Ok(req * 2)
+        })
+    });
+    let action = ServiceAction::new(service, 21);
+
+    assert_eq!(
+        Retry::spawn(FixedInterval::from_millis(1), action).await,
+        Ok(42)
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "rt-smol")]
+#[test]
+fn smol_sleeper_sleeps_until_deadline() {
+    use tokio_retry2::{Instant, Sleeper, SmolSleeper};
+
+    let deadline = Instant::now() + Duration::from_millis(10);
+    async_io::block_on(SmolSleeper::sleep_until(deadline));
+    assert!(Instant::now() >= deadline);
+}
+
+#[cfg(feature = "rt-async-std")]
+#[test]
+fn async_std_sleeper_sleeps_until_deadline() {
+    use tokio_retry2::{AsyncStdSleeper, Instant, Sleeper};
+
+    let deadline = Instant::now() + Duration::from_millis(10);
+    async_std::task::block_on(AsyncStdSleeper::sleep_until(deadline));
+    assert!(Instant::now() >= deadline);
+}
+
+#[cfg(feature = "rt-futures-timer")]
+#[test]
+fn futures_timer_sleeper_runs_without_tokio_timer() {
+    use tokio_retry2::{FuturesTimerSleeper, Instant, Sleeper};
+
+    // no `enable_time`, the tokio timer would panic here
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_millis(10);
+    runtime.block_on(FuturesTimerSleeper::sleep_until(deadline));
+    assert!(Instant::now() >= deadline);
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn metrics_record_attempts_retries_and_exhaustion() {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryMetrics;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let metrics = RetryMetrics::new()
+        .prefix("client")
+        .label("endpoint", "/users");
+    let result = metrics::with_local_recorder(&recorder, || {
+        runtime.block_on(
+            Retry::spawn(FixedInterval::from_millis(1).take(2), || {
+                future::ready(Err::<(), _>(RetryError::transient(())))
+            })
+            .with_metrics(metrics),
+        )
+    });
+    assert_eq!(result, Err(()));
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let value = |name: &str| {
+        let (key, _, _, value) = snapshot
+            .iter()
+            .find(|(key, ..)| key.key().name() == name)
+            .unwrap_or_else(|| panic!("missing {name}"));
+        assert!(key
+            .key()
+            .labels()
+            .any(|label| label.key() == "endpoint" && label.value() == "/users"));
+        value
+    };
+    assert_eq!(value("client_attempts_total"), &DebugValue::Counter(3));
+    assert_eq!(value("client_retries_total"), &DebugValue::Counter(2));
+    assert_eq!(value("client_exhausted_total"), &DebugValue::Counter(1));
+    let DebugValue::Histogram(delays) = value("client_delay_seconds") else {
+        panic!("delay is a histogram");
+    };
+    assert_eq!(delays.len(), 2);
+    let DebugValue::Histogram(attempts) = value("client_attempt_duration_seconds") else {
+        panic!("attempt duration is a histogram");
+    };
+    assert_eq!(attempts.len(), 3);
+    let DebugValue::Histogram(durations) = value("client_duration_seconds") else {
+        panic!("duration is a histogram");
+    };
+    assert_eq!(durations.len(), 1);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_nests_retry_events_in_span() {
+    use std::io;
+    use std::sync::Mutex;
+    use tokio_retry2::strategy::FixedInterval;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .finish();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let result = tracing::subscriber::with_default(subscriber, || {
+        runtime.block_on(
+            Retry::spawn(FixedInterval::from_millis(1), move || {
+                let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+                future::ready(if previous < 2 {
+                    Err(RetryError::transient("unavailable"))
+                } else {
+                    Ok(())
+                })
+            })
+            .trace_errors(),
+        )
+    });
+    assert_eq!(result, Ok(()));
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 3, "{output}");
+    // the fmt subscriber appends recorded span fields instead of replacing them
+    assert!(lines
+        .iter()
+        .all(|line| line.contains(" retry{attempt=1 error=\"unavailable\"")));
+    assert!(
+        lines[0].contains("retrying after transient error attempt=1 delay_ms=1"),
+        "{output}"
+    );
+    assert!(
+        lines[1].contains("retrying after transient error attempt=2 delay_ms=1"),
+        "{output}"
+    );
+    assert!(
+        lines[2].contains("succeeded after retrying attempts=3"),
+        "{output}"
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn with_span_keeps_attempts_in_span_across_sleeps() {
+    use std::sync::Mutex;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(std::io::sink)
+        .finish();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let cloned_seen = seen.clone();
+    let (span, result) = tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("fetch", attempt = tracing::field::Empty);
+        let retry = Retry::spawn(FixedInterval::from_millis(1).take(2), move || {
+            cloned_seen
+                .lock()
+                .unwrap()
+                .push(tracing::Span::current().id());
+            future::ready(Err::<(), _>(RetryError::transient(())))
+        })
+        .with_span(span.clone());
+        (span, runtime.block_on(retry))
+    });
+    assert_eq!(result, Err(()));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 3);
+    // the first attempt is created by `spawn`, before the span is set
+    assert!(seen[1..].iter().all(|id| *id == span.id()));
+}
+
+#[cfg(feature = "reqwest")]
+async fn serve_statuses(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let served = requests.clone();
+    tokio::spawn(async move {
+        for status in statuses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await.unwrap();
+            served.fetch_add(1, Ordering::SeqCst);
+            let response = format!(
+                "HTTP/1.1 {status} Status\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (url, requests)
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn retry_request_retries_server_errors() {
+    use tokio_retry2::retry_request;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let (url, requests) = serve_statuses(vec![503, 429, 200]).await;
+    let client = reqwest::Client::new();
+    let strategy = FixedInterval::from_millis(60_000).take(5);
+    let response = retry_request(&client, client.get(&url), strategy)
+        .await
+        .unwrap();
+
+    // `retry-after: 0` replaces the strategy delay
+    assert_eq!(response.status(), 200);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn retry_request_returns_last_response_once_exhausted() {
+    use tokio_retry2::retry_request;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let (url, requests) = serve_statuses(vec![500, 502, 404]).await;
+    let client = reqwest::Client::new();
+    let response = retry_request(
+        &client,
+        client.get(&url),
+        FixedInterval::from_millis(1).take(1),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(response.status(), 502);
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "stream")]
+async fn resubscribe_outcomes(reset_after: Duration) -> Vec<Result<u32, &'static str>> {
+    use futures_core::Stream;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Resubscribe;
+
+    struct Items(VecDeque<Result<u32, &'static str>>);
+
+    impl Stream for Items {
+        type Item = Result<u32, &'static str>;
+
+        fn poll_next(
+            mut self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Self::Item>> {
+            std::task::Poll::Ready(self.0.pop_front())
+        }
+    }
+
+    let connections = Arc::new(Mutex::new(VecDeque::from([
+        Ok(vec![Ok(1), Err("dropped")]),
+        Ok(vec![Ok(2), Err("dropped")]),
+        Err("refused"),
+    ])));
+    let connect = move || {
+        let connection = connections.lock().unwrap().pop_front().unwrap();
+        future::ready(
+            connection
+                .map(|items| Items(items.into()))
+                .map_err(RetryError::transient),
+        )
+    };
+    let stream = Resubscribe::new(FixedInterval::from_millis(1).take(1), connect, reset_after);
+    let mut stream = std::pin::pin!(stream);
+
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        outcomes.push(outcome);
+    }
+    outcomes
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn resubscribe_reconnects_failed_streams() {
+    assert_eq!(
+        resubscribe_outcomes(Duration::from_secs(3600)).await,
+        [Ok(1), Ok(2), Err("dropped")]
+    );
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn resubscribe_resets_backoff_once_healthy() {
+    assert_eq!(
+        resubscribe_outcomes(Duration::ZERO).await,
+        [Ok(1), Ok(2), Err("refused")]
+    );
+}
+
+#[cfg(feature = "sink")]
+#[tokio::test]
+async fn sink_retries_pending_item() {
+    use futures_sink::Sink;
+    use std::sync::Mutex;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetrySink;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let cloned_sent = sent.clone();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let sink = RetrySink::new(FixedInterval::from_millis(1).take(2), move |item: u32| {
+        let result = if cloned_counter
+            .fetch_add(1, Ordering::SeqCst)
+            .is_multiple_of(2)
+        {
+            Err(RetryError::transient("busy"))
+        } else {
+            cloned_sent.lock().unwrap().push(item);
+            Ok(())
+        };
+        future::ready(result)
+    });
+    let mut sink = std::pin::pin!(sink);
+
+    for item in [1, 2] {
+        future::poll_fn(|cx| sink.as_mut().poll_ready(cx))
+            .await
+            .unwrap();
+        sink.as_mut().start_send(item).unwrap();
+    }
+    future::poll_fn(|cx| sink.as_mut().poll_flush(cx))
+        .await
+        .unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), [1, 2]);
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[cfg(feature = "sink")]
+#[tokio::test]
+async fn sink_fails_with_rejected_error() {
+    use futures_sink::Sink;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetrySink;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let sink = RetrySink::new(FixedInterval::from_millis(1), move |_: ()| {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err(RetryError::transient("unauthorized")))
+    })
+    .when(|err: &&str| *err != "unauthorized");
+    let mut sink = std::pin::pin!(sink);
+
+    sink.as_mut().start_send(()).unwrap();
+    let result = future::poll_fn(|cx| sink.as_mut().poll_flush(cx)).await;
+
+    assert_eq!(result, Err("unauthorized"));
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn retry_ext_retries_in_postfix_style() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryExt;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let result = (move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), _>(RetryError::transient(previous)))
+    })
+    .retry(FixedInterval::from_millis(1))
+    .when(|previous: &usize| *previous < 2)
+    .notify(move |_: &usize, _| {
+        cloned_notified.fetch_add(1, Ordering::SeqCst);
+    })
+    .await;
+
+    assert_eq!(result, Err(2));
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+    assert_eq!(notified.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn sleeps_the_full_delay_before_every_retry() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let res = Retry::spawn(FixedInterval::from_millis(100).take(3), move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    })
+    .await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+    assert_eq!(started.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn boxed_retries_share_one_future_type() {
+    use tokio_retry2::strategy::{ExponentialBackoff, FixedInterval};
+    use tokio_retry2::{BoxedRetry, BoxedRetryPolicy};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let retries: Vec<BoxedRetry<usize, usize>> = vec![
+        Retry::boxed(
+            BoxedRetryPolicy::new(FixedInterval::from_millis(1).take(5))
+                .when(|previous: &usize| *previous < 2)
+                .notify(move |_: &usize, _| {
+                    cloned_notified.fetch_add(1, Ordering::SeqCst);
+                }),
+            move || {
+                let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+                future::ready(Err::<usize, _>(RetryError::transient(previous)))
+            },
+        ),
+        BoxedRetryPolicy::new(ExponentialBackoff::from_millis(1).take(1))
+            .retry(|| async { Ok::<usize, RetryError<usize>>(7) }),
+    ];
+
+    let mut results = Vec::new();
+    for retry in retries {
+        results.push(retry.await);
+    }
+
+    assert_eq!(results, vec![Err(2), Ok(7)]);
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+    assert_eq!(notified.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_exposes_its_progress() {
+    use std::future::Future;
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryPhase;
+
+    let started = Instant::now();
+    let retry = Retry::spawn(FixedInterval::from_millis(100).take(1), || {
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    });
+    let mut retry = std::pin::pin!(retry);
+    assert_eq!(retry.attempts(), 1);
+    assert_eq!(retry.phase(), RetryPhase::Running);
+    assert_eq!(retry.next_planned_delay(), Some(Duration::from_millis(100)));
+
+    let poll = future::poll_fn(|cx| std::task::Poll::Ready(retry.as_mut().poll(cx))).await;
+    assert!(poll.is_pending());
+    assert_eq!(retry.phase(), RetryPhase::Sleeping);
+    assert_eq!(retry.duration(), Duration::from_millis(100));
+    assert_eq!(
+        retry.next_attempt_at(),
+        Some((started + Duration::from_millis(100)).into_std())
+    );
+    let debug = format!("{retry:?}");
+    assert!(debug.starts_with("Retry { retry_if: RetryIf { phase: Sleeping, attempts: 1"));
+
+    assert_eq!(retry.next_planned_delay(), Some(Duration::from_millis(100)));
+    assert_eq!(retry.total_slept(), Duration::ZERO);
+
+    assert_eq!(retry.as_mut().await, Err(42));
+    assert_eq!(retry.attempts(), 2);
+    assert_eq!(retry.next_attempt_at(), None);
+    assert_eq!(retry.total_slept(), Duration::from_millis(100));
+    assert_eq!(retry.next_planned_delay(), None);
+}
+
+#[tokio::test]
+async fn policy_runs_every_action_with_a_fresh_strategy() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let notified = Arc::new(AtomicUsize::new(0));
+    let cloned_notified = notified.clone();
+    let policy = Retry::builder()
+        .strategy(FixedInterval::from_millis(1).take(2))
+        .condition(|err: &u64| *err != 0)
+        .notify(move |_: &u64, _| {
+            cloned_notified.fetch_add(1, Ordering::SeqCst);
+        })
+        .into_policy();
+
+    for _ in 0..2 {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let cloned_counter = counter.clone();
+        let res = policy
+            .clone()
+            .run(move || {
+                cloned_counter.fetch_add(1, Ordering::SeqCst);
+                future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+            })
+            .await;
+        assert_eq!(res, Err(RetryFailure::Error(42)));
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+    }
+    let res = policy
+        .run(|| future::ready(Err::<(), RetryError<u64>>(RetryError::transient(0))))
+        .await;
+
+    assert_eq!(res, Err(RetryFailure::Error(0)));
+    assert_eq!(notified.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn retry_all_retries_every_action_concurrently() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{retry_all, try_retry_all, RetryFailure};
+
+    let policy = Retry::builder()
+        .strategy(FixedInterval::from_millis(100).take(3))
+        .into_policy();
+    let actions = |permanent: u64| {
+        (0..4u64).map(move |item| {
+            let counter = Arc::new(AtomicUsize::new(0));
+            move || {
+                let failures = counter.fetch_add(1, Ordering::SeqCst) as u64;
+                future::ready(match item {
+                    _ if item == permanent => Err(RetryError::permanent(item)),
+                    _ if failures < item => Err(RetryError::transient(item)),
+                    _ => Ok(item * 10),
+                })
+            }
+        })
+    };
+
+    let started = Instant::now();
+    let results = retry_all(&policy, actions(u64::MAX)).await;
+    assert_eq!(results, vec![Ok(0), Ok(10), Ok(20), Ok(30)]);
+    // the slowest item retried 3 times, concurrently with the others
+    assert_eq!(started.elapsed(), Duration::from_millis(300));
+
+    let results = retry_all(&policy, actions(1)).await;
+    assert_eq!(results[1], Err(RetryFailure::Error(1)));
+    assert_eq!(results[3], Ok(30));
+
+    let started = Instant::now();
+    let res = try_retry_all(&policy, actions(2)).await;
+    assert_eq!(res, Err(RetryFailure::Error(2)));
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert_eq!(
+        try_retry_all(&policy, actions(u64::MAX)).await,
+        Ok(vec![0, 10, 20, 30])
+    );
+}
+
+#[cfg(feature = "events")]
+#[tokio::test(start_paused = true)]
+async fn events_report_the_lifecycle_of_the_retry() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryEvent;
+
+    let mut retry = Retry::spawn(FixedInterval::from_millis(10).take(1), || {
+        future::ready(Err::<(), _>(RetryError::transient("unavailable")))
+    });
+    let mut events = retry.events(16);
+    assert_eq!(retry.await, Err("unavailable"));
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    assert_eq!(
+        received,
+        vec![
+            RetryEvent::AttemptStarted { attempt: 1 },
+            RetryEvent::AttemptFailed {
+                attempt: 1,
+                err: "unavailable",
+                delay: Some(Duration::from_millis(10)),
+            },
+            RetryEvent::Sleeping {
+                delay: Duration::from_millis(10),
+            },
+            RetryEvent::AttemptStarted { attempt: 2 },
+            RetryEvent::AttemptFailed {
+                attempt: 2,
+                err: "unavailable",
+                delay: None,
+            },
+            RetryEvent::Exhausted {
+                attempts: 2,
+                elapsed: Duration::from_millis(10),
+                reason: StopReason::Exhausted,
+            },
+        ]
+    );
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn mock_clock_drives_delays_and_deadlines_synchronously() {
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::test_util::MockClock;
+    use tokio_retry2::{Clock, RetryFailure};
+
+    let clock = MockClock::new();
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let retry = Retry::builder()
+        .strategy(FixedInterval::from_millis(100))
+        .clock(clock.clone())
+        .deadline(clock.now() + Duration::from_millis(250))
+        .build(move || {
+            cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+        });
+    let mut retry = std::pin::pin!(retry);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    assert!(retry.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    clock.advance(Duration::from_millis(100));
+    assert!(retry.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+    clock.advance(Duration::from_millis(100));
+
+    // the third attempt fails and the next one would start past the deadline
+    assert_eq!(
+        retry.as_mut().poll(&mut cx),
+        Poll::Ready(Err(RetryFailure::DeadlineExceeded {
+            last_error: Some(42)
+        }))
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn truncates_the_sleep_past_the_deadline_for_a_last_attempt() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::{RetryFailure, SleepPastDeadline};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let started = Instant::now();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(30_000))
+        .deadline((started + Duration::from_secs(1)).into_std())
+        .on_sleep_past_deadline(SleepPastDeadline::Truncate(Duration::from_millis(200)))
+        .build(move || {
+            let attempt = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(if attempt == 0 {
+                Err(RetryError::transient(42))
+            } else {
+                Ok::<usize, RetryError<u64>>(attempt)
+            })
+        })
+        .await;
+
+    assert_eq!(res, Ok::<usize, RetryFailure<u64>>(1));
+    assert_eq!(started.elapsed(), Duration::from_millis(800));
+}
+
+#[tokio::test(start_paused = true)]
+async fn gives_up_instead_of_sleeping_past_the_deadline() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RetryFailure;
+
+    let started = Instant::now();
+    let res = Retry::spawn(FixedInterval::from_millis(30_000), || {
+        future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42)))
+    })
+    .with_deadline((started + Duration::from_secs(1)).into_std())
+    .await;
+
+    assert_eq!(
+        res,
+        Err(RetryFailure::DeadlineExceeded {
+            last_error: Some(42)
+        })
+    );
+    assert_eq!(started.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn classify_retries_plain_transient_errors() {
+    use std::io;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::Classify;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let res = Retry::spawn(
+        FixedInterval::from_millis(1).take(5),
+        Classify::new(move || {
+            let attempt = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(match attempt {
+                0 => Err(io::Error::from(io::ErrorKind::TimedOut)),
+                _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+            })
+        }),
+    )
+    .await;
+
+    assert_eq!(
+        res.map_err(|err| err.kind()),
+        Err::<(), _>(io::ErrorKind::NotFound)
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn notifies_every_hook_of_a_tuple() {
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::NotifyAttempt;
+
+    let logged = Arc::new(AtomicUsize::new(0));
+    let cloned_logged = logged.clone();
+    let last_attempt = Arc::new(AtomicUsize::new(0));
+    let cloned_last_attempt = last_attempt.clone();
+    let notify = (
+        move |_: &u64, _| {
+            cloned_logged.fetch_add(1, Ordering::SeqCst);
+        },
+        NotifyAttempt::new(move |_: &u64, attempt, _| {
+            cloned_last_attempt.store(attempt, Ordering::SeqCst);
+        }),
+    );
+    let res = Retry::spawn_notify(
+        FixedInterval::from_millis(1).take(2),
+        || future::ready(Err::<(), RetryError<u64>>(RetryError::transient(42))),
+        notify,
+    )
+    .await;
+
+    assert_eq!(res, Err(42));
+    assert_eq!(logged.load(Ordering::SeqCst), 2);
+    assert_eq!(last_attempt.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn stays_pending_once_terminated() {
+    use std::future::Future;
+    use std::task::{Context, Waker};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let retry = Retry::spawn(ExponentialBackoff::from_millis(10).take(2), move || {
+        cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(Ok::<u64, RetryError<u64>>(42))
+    });
+    let mut retry = std::pin::pin!(retry);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    assert!(!retry.is_terminated());
+    assert_eq!(retry.as_mut().poll(&mut cx), std::task::Poll::Ready(Ok(42)));
+    assert!(retry.is_terminated());
+    assert!(retry.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(feature = "jitter")]
+#[tokio::test(start_paused = true)]
+async fn jitter_retry_after_spreads_requested_delay() {
+    use tokio_retry2::strategy::{jitter_range, FixedInterval};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let notified = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let cloned_notified = notified.clone();
+    let res = Retry::builder()
+        .strategy(FixedInterval::from_millis(10))
+        .notify(move |_: &u64, duration: Duration| {
+            cloned_notified.lock().unwrap().push(duration);
+        })
+        .jitter_retry_after(jitter_range(1.0, 1.2))
+        .clamp_retry_after(Duration::ZERO, Duration::from_secs(35))
+        .build(move || {
+            let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+            future::ready(match previous {
+                0 => Err(RetryError::retry_after(42, Duration::from_secs(30))),
+                1 => Err(RetryError::retry_after(42, Duration::from_secs(60))),
+                2 => Err(RetryError::transient(42)),
+                _ => Ok::<(), RetryError<u64>>(()),
+            })
+        })
+        .await;
+
+    assert_eq!(res, Ok(()));
+    let notified = notified.lock().unwrap();
+    assert!(notified[0] >= Duration::from_secs(30) && notified[0] <= Duration::from_secs(36));
+    assert_eq!(notified[1], Duration::from_secs(35));
+    assert_eq!(notified.len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn spawn_borrowing_lends_the_action_to_every_attempt() {
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::BorrowingAction;
+
+    struct Buffered {
+        buf: Vec<usize>,
+    }
+
+    struct Push<'a>(&'a mut Vec<usize>);
+
+    impl future::Future for Push<'_> {
+        type Output = Result<usize, RetryError<usize>>;
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Self::Output> {
+            let len = self.0.len();
+            self.0.push(len);
+            Poll::Ready(match len {
+                0 | 1 => Err(RetryError::transient(len)),
+                _ => Ok(len),
+            })
+        }
+    }
+
+    impl BorrowingAction for Buffered {
+        type Future<'a> = Push<'a>;
+        type Item = usize;
+        type Error = usize;
+
+        fn run_borrowed(&mut self) -> Push<'_> {
+            Push(&mut self.buf)
+        }
+    }
+
+    let mut action = Buffered { buf: Vec::new() };
+    let res = Retry::spawn_borrowing(FixedInterval::from_millis(10).take(3), &mut action).await;
+
+    assert_eq!(res, Ok(2));
+    assert_eq!(action.buf, [0, 1, 2]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn take_parts_hands_back_the_action_and_unused_strategy() {
+    use tokio_retry2::strategy::FixedInterval;
+
+    let mut calls = 0;
+    let action = move || {
+        calls += 1;
+        future::ready(match calls {
+            1 => Err(RetryError::transient(calls)),
+            _ => Ok::<_, RetryError<u64>>(calls),
+        })
+    };
+    let retry = Retry::spawn(FixedInterval::from_millis(10).take(3), action);
+    let mut retry = std::pin::pin!(retry);
+
+    assert_eq!(retry.as_mut().await, Ok(2));
+    let mut parts = retry.as_mut().take_parts().unwrap();
+    assert_eq!(parts.strategy.count(), 2);
+    assert_eq!((parts.action)().await, Ok(3));
+    assert!(retry.as_mut().take_parts().is_none());
+    assert!(retry.is_terminated());
+
+    let retry = Retry::spawn(FixedInterval::from_millis(10).take(3), || {
+        future::ready(Ok::<_, RetryError<u64>>(1))
+    });
+    assert_eq!(retry.into_parts().strategy.count(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wake_on_retries_once_the_trigger_fires() {
+    use tokio::sync::Notify;
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let cloned_counter = counter.clone();
+    let online = Arc::new(Notify::new());
+    let cloned_online = online.clone();
+    let started = Instant::now();
+    let retry = Retry::spawn(FixedInterval::from_millis(60_000).take(3), move || {
+        let previous = cloned_counter.fetch_add(1, Ordering::SeqCst);
+        future::ready(match previous {
+            0 | 1 => Err(RetryError::transient(42)),
+            _ => Ok::<_, RetryError<u64>>(previous),
+        })
+    })
+    .wake_on(move || {
+        let online = cloned_online.clone();
+        async move { online.notified().await }
+    });
+    let retry = tokio::spawn(retry);
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+    online.notify_waiters();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+    // without a signal, the delay of the strategy still bounds the wait
+    assert_eq!(retry.await.unwrap(), Ok(2));
+    assert_eq!(started.elapsed(), Duration::from_secs(61));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wake_on_and_retry_now_wait_for_the_rate_limiter() {
+    use tokio::time::Instant;
+    use tokio_retry2::strategy::FixedInterval;
+    use tokio_retry2::RateLimiter;
+
+    let limiter = RateLimiter::new(1, Duration::from_secs(1));
+    let spawn = |runs: Arc<AtomicUsize>| {
+        let action = move || {
+            let previous = runs.fetch_add(1, Ordering::SeqCst);
+            future::ready(match previous {
+                0 => Err(RetryError::transient(42)),
+                _ => Ok::<_, RetryError<u64>>(previous),
+            })
+        };
+        RetryIf::spawn(
+            FixedInterval::from_millis(60_000).take(1),
+            action,
+            |_: &u64| true,
+            |_: &u64, _| {},
+        )
+    };
+
+    // a trigger firing right away retries once the slot of the limiter comes
+    let started = Instant::now();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let retry = spawn(runs.clone())
+        .with_rate_limit(limiter.clone())
+        .wake_on(|| future::ready(()));
+    assert_eq!(retry.await, Ok(1));
+    assert_eq!(started.elapsed(), Duration::from_secs(1));
+
+    // so does `retry_now`, retrying right away without a limiter
+    let runs = Arc::new(AtomicUsize::new(0));
+    let retry = spawn(runs.clone()).with_rate_limit(limiter.clone());
+    let handle = retry.handle();
+    let retry = tokio::spawn(retry);
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    // the slot of the first attempt is taken, the next one is a second away
+    limiter.reserve_at(Instant::now().into_std());
+    handle.retry_now();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    assert_eq!(retry.await.unwrap(), Ok(1));
+}